@@ -0,0 +1,8 @@
+#![no_main]
+
+use intmap_integration_test_differential::FuzzInput;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: FuzzInput| {
+    input.run();
+});
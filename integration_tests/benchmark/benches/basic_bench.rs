@@ -4,10 +4,15 @@ use ahash::AHashMap;
 use divan::{bench, black_box, Bencher};
 use hashbrown::HashMap as BrownMap;
 use indexmap::IndexMap;
-use intmap::{Entry, IntMap};
+use intmap::{
+    Entry, IndexStrategy, IntIndexMap, IntMap, IntMapBuilder, IntSoaMap, IntSwissMap, SmallIntMap,
+    TableSizing,
+};
 use rustc_hash::{FxBuildHasher, FxHashMap};
 
 const VEC_COUNT: usize = 10_000;
+// For maps that don't fit into the CPU caches.
+const LARGE_COUNT: usize = 4_000_000;
 
 fn main() {
     divan::main();
@@ -179,6 +184,19 @@ fn u64_get_brown(bencher: Bencher) {
     });
 }
 
+#[bench]
+fn u64_get_missing_large_brown(bencher: Bencher) {
+    let data = get_random_range(LARGE_COUNT, 4242);
+    let missing = get_random_range(VEC_COUNT * 10, 4343);
+    let map: BrownMap<u64, u64> = data.iter().map(|&s| (s, s)).collect();
+
+    bencher.bench_local(|| {
+        for s in missing.iter() {
+            black_box(map.contains_key(s));
+        }
+    });
+}
+
 // ********** Ahash **********
 
 #[bench]
@@ -307,6 +325,157 @@ fn u64_get_indexmap(bencher: Bencher) {
     });
 }
 
+#[bench]
+fn u64_iter_indexmap(bencher: Bencher) {
+    let data = get_random_range(VEC_COUNT, 4242);
+    let map: IndexMap<u64, u64> = data.iter().map(|s| (*s, *s)).collect();
+
+    bencher.bench_local(|| {
+        for kv in map.iter() {
+            black_box(kv);
+        }
+    });
+}
+
+// ********** IntIndexMap **********
+
+#[bench]
+fn u64_insert_intindexmap(bencher: Bencher) {
+    let data = get_random_range(VEC_COUNT, 4242);
+    let mut map: IntIndexMap<u64, u64> = IntIndexMap::with_capacity(data.len());
+
+    bencher.bench_local(|| {
+        map.clear();
+
+        for s in data.iter() {
+            black_box(map.insert(*s, *s));
+        }
+    });
+}
+
+#[bench]
+fn u64_get_intindexmap(bencher: Bencher) {
+    let data = get_random_range(VEC_COUNT, 4242);
+    let mut map: IntIndexMap<u64, u64> = IntIndexMap::with_capacity(data.len());
+
+    for s in data.iter() {
+        black_box(map.insert(*s, *s));
+    }
+
+    bencher.bench_local(|| {
+        for s in data.iter() {
+            black_box(map.contains_key(*s));
+        }
+    });
+}
+
+#[bench]
+fn u64_iter_intindexmap(bencher: Bencher) {
+    let data = get_random_range(VEC_COUNT, 4242);
+    let map: IntIndexMap<u64, u64> = data.iter().map(|s| (*s, *s)).collect();
+
+    bencher.bench_local(|| {
+        for kv in map.iter() {
+            black_box(kv);
+        }
+    });
+}
+
+// ********** IntSwissMap **********
+
+#[bench]
+fn u64_insert_intswissmap(bencher: Bencher) {
+    let data = get_random_range(VEC_COUNT, 4242);
+    let mut map: IntSwissMap<u64, u64> = IntSwissMap::with_capacity(data.len());
+
+    bencher.bench_local(|| {
+        map.clear();
+
+        for s in data.iter() {
+            black_box(map.insert(*s, *s));
+        }
+    });
+}
+
+#[bench]
+fn u64_get_intswissmap(bencher: Bencher) {
+    let data = get_random_range(VEC_COUNT, 4242);
+    let map: IntSwissMap<u64, u64> = data.iter().map(|&s| (s, s)).collect();
+
+    bencher.bench_local(|| {
+        for s in data.iter() {
+            black_box(map.contains_key(*s));
+        }
+    });
+}
+
+#[bench]
+fn u64_get_missing_large_intswissmap(bencher: Bencher) {
+    let data = get_random_range(LARGE_COUNT, 4242);
+    let missing = get_random_range(VEC_COUNT * 10, 4343);
+    let map: IntSwissMap<u64, u64> = data.iter().map(|&s| (s, s)).collect();
+
+    bencher.bench_local(|| {
+        for s in missing.iter() {
+            black_box(map.contains_key(*s));
+        }
+    });
+}
+
+// ********** IntSoaMap **********
+
+#[bench]
+fn u64_keys_large_value_intmap(bencher: Bencher) {
+    let data = get_random_range(VEC_COUNT, 4242);
+    let map: IntMap<u64, [u64; 16]> = data.iter().map(|&s| (s, [s; 16])).collect();
+
+    bencher.bench_local(|| black_box(map.keys().fold(0, u64::wrapping_add)));
+}
+
+#[bench]
+fn u64_keys_large_value_intsoamap(bencher: Bencher) {
+    let data = get_random_range(VEC_COUNT, 4242);
+    let map: IntSoaMap<u64, [u64; 16]> = data.iter().map(|&s| (s, [s; 16])).collect();
+
+    bencher.bench_local(|| black_box(map.keys().fold(0, u64::wrapping_add)));
+}
+
+#[bench]
+fn u64_get_intsoamap(bencher: Bencher) {
+    let data = get_random_range(VEC_COUNT, 4242);
+    let map: IntSoaMap<u64, u64> = data.iter().map(|&s| (s, s)).collect();
+
+    bencher.bench_local(|| {
+        for s in data.iter() {
+            black_box(map.contains_key(*s));
+        }
+    });
+}
+
+// ********** SmallIntMap **********
+
+#[bench]
+fn u64_insert_few_intmap(bencher: Bencher) {
+    bencher.bench_local(|| {
+        let mut map: IntMap<u64, u64> = IntMap::new();
+        for s in 0..6 {
+            map.insert(s, s);
+        }
+        black_box(map.get(3));
+    });
+}
+
+#[bench]
+fn u64_insert_few_smallintmap(bencher: Bencher) {
+    bencher.bench_local(|| {
+        let mut map: SmallIntMap<u64, u64, 8> = SmallIntMap::new();
+        for s in 0..6 {
+            map.insert(s, s);
+        }
+        black_box(map.get(3));
+    });
+}
+
 // ********** Intmap **********
 
 #[bench]
@@ -395,6 +564,116 @@ fn u64_get_intmap(bencher: Bencher) {
     });
 }
 
+#[bench]
+fn u64_get_intmap_one_and_a_half(bencher: Bencher) {
+    let data = get_random_range(VEC_COUNT, 4242);
+
+    let mut map: IntMap<u64, u64> = IntMapBuilder::new()
+        .capacity(data.len())
+        .index_strategy(IndexStrategy::MultiplyShift)
+        .table_sizing(TableSizing::OneAndAHalf)
+        .build()
+        .unwrap();
+    for s in data.iter() {
+        map.insert(*s, *s);
+    }
+
+    bencher.bench_local(|| {
+        for s in data.iter() {
+            black_box(map.contains_key(*s));
+        }
+    });
+}
+
+#[bench]
+fn u64_get_loop_intmap(bencher: Bencher) {
+    let data = get_random_range(VEC_COUNT, 4242);
+    let lookups = get_random_lookups(&data, 4343);
+    let map: IntMap<u64, u64> = data.iter().map(|&s| (s, s)).collect();
+
+    bencher.bench_local(|| {
+        for s in lookups.iter() {
+            black_box(map.get(*s));
+        }
+    });
+}
+
+#[bench]
+fn u64_get_many_intmap(bencher: Bencher) {
+    let data = get_random_range(VEC_COUNT, 4242);
+    let lookups = get_random_lookups(&data, 4343);
+    let map: IntMap<u64, u64> = data.iter().map(|&s| (s, s)).collect();
+
+    bencher.bench_local(|| {
+        black_box(map.get_many(&lookups));
+    });
+}
+
+#[bench]
+fn u64_get_batch_intmap(bencher: Bencher) {
+    let data = get_random_range(VEC_COUNT, 4242);
+    let lookups = get_random_lookups(&data, 4343);
+    let map: IntMap<u64, u64> = data.iter().map(|&s| (s, s)).collect();
+    let mut out = vec![None; lookups.len()];
+
+    bencher.bench_local(|| {
+        map.get_batch(&lookups, &mut out);
+        black_box(&out);
+    });
+}
+
+#[bench]
+fn u64_get_loop_large_intmap(bencher: Bencher) {
+    let data = get_random_range(LARGE_COUNT, 4242);
+    // Every 16th key, so the lookups are spread over the whole map and miss the CPU cache.
+    let spread: Vec<u64> = data.iter().step_by(16).copied().collect();
+    let lookups = get_random_lookups(&spread, 4343);
+    let map: IntMap<u64, u64> = data.iter().map(|&s| (s, s)).collect();
+
+    bencher.bench_local(|| {
+        for s in lookups.iter() {
+            black_box(map.get(*s));
+        }
+    });
+}
+
+#[bench]
+fn u64_get_missing_large_intmap(bencher: Bencher) {
+    let data = get_random_range(LARGE_COUNT, 4242);
+    let missing = get_random_range(VEC_COUNT * 10, 4343);
+    let map: IntMap<u64, u64> = data.iter().map(|&s| (s, s)).collect();
+
+    bencher.bench_local(|| {
+        for s in missing.iter() {
+            black_box(map.contains_key(*s));
+        }
+    });
+}
+
+#[bench]
+fn u64_get_many_large_intmap(bencher: Bencher) {
+    let data = get_random_range(LARGE_COUNT, 4242);
+    let spread: Vec<u64> = data.iter().step_by(16).copied().collect();
+    let lookups = get_random_lookups(&spread, 4343);
+    let map: IntMap<u64, u64> = data.iter().map(|&s| (s, s)).collect();
+
+    bencher.bench_local(|| {
+        black_box(map.get_many(&lookups));
+    });
+}
+
+#[bench]
+fn u64_insert_batch_intmap(bencher: Bencher) {
+    let data = get_random_range(VEC_COUNT, 4242);
+    let pairs: Vec<(u64, u64)> = data.iter().map(|&s| (s, s)).collect();
+    let mut map: IntMap<u64, u64> = IntMap::with_capacity(data.len());
+
+    bencher.bench_local(|| {
+        map.clear();
+        map.insert_batch(pairs.iter().copied());
+    });
+}
+
 #[bench]
 fn u64_eq_intmap(bencher: Bencher) {
     let data1 = get_random_range(VEC_COUNT, 4242);
@@ -442,3 +721,14 @@ fn get_random_range(count: usize, seed: u64) -> Vec<u64> {
 
     vec
 }
+
+// Returns the keys of `data` in random order, unlike `get_random_range` which sorts them.
+fn get_random_lookups(data: &[u64], seed: u64) -> Vec<u64> {
+    use rand::prelude::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    let mut lookups = data.to_vec();
+    lookups.shuffle(&mut StdRng::seed_from_u64(seed));
+    lookups
+}
@@ -0,0 +1,126 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+
+use bincode::de::read::SliceReader;
+use bincode::de::DecoderImpl;
+use bincode::{config, BorrowDecode, Decode, Encode};
+use intmap::{IntKey, IntMap};
+use proptest::collection::hash_map;
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn test_roundtrip_u8(m in hash_map(any::<u8>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_u16(m in hash_map(any::<u16>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_u32(m in hash_map(any::<u32>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_u64(m in hash_map(any::<u64>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_u128(m in hash_map(any::<u128>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_usize(m in hash_map(any::<usize>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_i8(m in hash_map(any::<i8>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_i16(m in hash_map(any::<i16>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_i32(m in hash_map(any::<i32>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_i64(m in hash_map(any::<i64>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_i128(m in hash_map(any::<i128>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_isize(m in hash_map(any::<isize>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+}
+
+fn test_roundtrip<K>(m: HashMap<K, String>) -> Result<(), TestCaseError>
+where
+    K: IntKey + Ord + Encode + Decode<()> + for<'de> BorrowDecode<'de, ()> + Debug,
+{
+    let config = config::standard();
+
+    let im: IntMap<K, _> = m.clone().into_iter().collect();
+    let bytes = bincode::encode_to_vec(&im, config).unwrap();
+
+    // The encoding is deterministic and matches the one of a sorted map.
+    let sorted: BTreeMap<K, String> = m.into_iter().collect();
+    prop_assert_eq!(&bytes, &bincode::encode_to_vec(&sorted, config).unwrap());
+
+    let (im_copy, _): (IntMap<K, String>, _) = bincode::decode_from_slice(&bytes, config).unwrap();
+    prop_assert_eq!(&im, &im_copy);
+
+    let (im_copy, _): (IntMap<K, String>, _) =
+        bincode::borrow_decode_from_slice(&bytes, config).unwrap();
+    prop_assert_eq!(&im, &im_copy);
+
+    let mut decoder = DecoderImpl::new(SliceReader::new(&bytes), config, ());
+    let im_copy = IntMap::bincode_decode_bounded(&mut decoder, im.len()).unwrap();
+    prop_assert_eq!(im, im_copy);
+    Ok(())
+}
+
+#[test]
+fn decode_bounded_rejects_long_prefix() {
+    // A length prefix of u64::MAX without any pairs must fail before allocating.
+    let bytes = bincode::encode_to_vec(u64::MAX, config::standard()).unwrap();
+
+    let mut decoder = DecoderImpl::new(SliceReader::new(&bytes), config::standard(), ());
+    let result = IntMap::<u64, u64>::bincode_decode_bounded(&mut decoder, 1000);
+    assert!(matches!(
+        result,
+        Err(bincode::error::DecodeError::LimitExceeded)
+    ));
+
+    let config = config::standard().with_limit::<1000>();
+    assert!(bincode::decode_from_slice::<IntMap<u64, u64>, _>(&bytes, config).is_err());
+}
+
+#[test]
+fn decode_rejects_unordered_keys() {
+    let config = config::standard();
+
+    let pairs: Vec<(u64, u8)> = vec![(2, 0), (1, 0)];
+    let bytes = bincode::encode_to_vec(&pairs, config).unwrap();
+    assert!(bincode::decode_from_slice::<IntMap<u64, u8>, _>(&bytes, config).is_err());
+
+    let pairs: Vec<(u64, u8)> = vec![(1, 0), (1, 0)];
+    let bytes = bincode::encode_to_vec(&pairs, config).unwrap();
+    assert!(bincode::decode_from_slice::<IntMap<u64, u8>, _>(&bytes, config).is_err());
+}
@@ -0,0 +1,107 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use intmap::{IntKey, IntMap};
+use proptest::collection::hash_map;
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn test_roundtrip_u8(m in hash_map(any::<u8>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_u16(m in hash_map(any::<u16>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_u32(m in hash_map(any::<u32>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_u64(m in hash_map(any::<u64>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_u128(m in hash_map(any::<u128>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_usize(m in hash_map(any::<usize>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_i8(m in hash_map(any::<i8>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_i16(m in hash_map(any::<i16>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_i32(m in hash_map(any::<i32>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_i64(m in hash_map(any::<i64>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_i128(m in hash_map(any::<i128>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+
+    #[test]
+    fn test_roundtrip_isize(m in hash_map(any::<isize>(), any::<String>(), 0..20)) {
+        test_roundtrip(m)?;
+    }
+}
+
+fn test_roundtrip<K>(m: HashMap<K, String>) -> Result<(), TestCaseError>
+where
+    K: IntKey + Ord + BorshSerialize + BorshDeserialize + Debug,
+{
+    let im: IntMap<K, _> = m.clone().into_iter().collect();
+    let bytes = borsh::to_vec(&im).unwrap();
+
+    // The encoding is deterministic and matches the one of a sorted map.
+    let sorted: BTreeMap<K, String> = m.into_iter().collect();
+    prop_assert_eq!(&bytes, &borsh::to_vec(&sorted).unwrap());
+
+    let im_copy: IntMap<K, String> = borsh::from_slice(&bytes[..]).unwrap();
+    prop_assert_eq!(&im, &im_copy);
+
+    let im_copy = IntMap::borsh_deserialize_bounded(&mut &bytes[..], im.len()).unwrap();
+    prop_assert_eq!(im, im_copy);
+    Ok(())
+}
+
+#[test]
+fn deserialize_bounded_rejects_long_prefix() {
+    // A length prefix of u32::MAX without any pairs must fail before allocating.
+    let bytes = u32::MAX.to_le_bytes();
+    assert!(IntMap::<u64, u64>::borsh_deserialize_bounded(&mut &bytes[..], 1000).is_err());
+    assert!(borsh::from_slice::<IntMap<u64, u64>>(&bytes[..]).is_err());
+}
+
+#[test]
+fn deserialize_rejects_unordered_keys() {
+    let pairs: Vec<(u64, u8)> = vec![(2, 0), (1, 0)];
+    let bytes = borsh::to_vec(&pairs).unwrap();
+    assert!(borsh::from_slice::<IntMap<u64, u8>>(&bytes[..]).is_err());
+
+    let pairs: Vec<(u64, u8)> = vec![(1, 0), (1, 0)];
+    let bytes = borsh::to_vec(&pairs).unwrap();
+    assert!(borsh::from_slice::<IntMap<u64, u8>>(&bytes[..]).is_err());
+}
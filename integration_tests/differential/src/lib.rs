@@ -0,0 +1,470 @@
+//! Differential testing of [`IntMap`] against [`HashMap`].
+//!
+//! An [`Input`] is a constructor followed by a sequence of [`Op`]s. Every op is applied to both
+//! maps and the results are compared, so any divergence from the reference implementation fails
+//! with a panic. Inputs implement [`arbitrary::Arbitrary`] so they can be driven by cargo-fuzz
+//! (see the `fuzz` directory in the repository root) as well as by plain tests.
+//!
+//! If an input fails, [`run_with_reproducer`] prints a ready-to-paste test case to stderr.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::panic::{self, AssertUnwindSafe};
+
+use arbitrary::{Arbitrary, Unstructured};
+use intmap::{Entry, IntKey, IntMap};
+
+/// Bundles all traits a key needs for the differential tests.
+pub trait DiffKey:
+    IntKey
+    + Debug
+    + Eq
+    + Hash
+    + Ord
+    + serde::Serialize
+    + serde::de::DeserializeOwned
+    + borsh::BorshSerialize
+    + borsh::BorshDeserialize
+    + bincode::Encode
+    + bincode::Decode<()>
+    + for<'a> Arbitrary<'a>
+{
+}
+
+impl<K> DiffKey for K where
+    K: IntKey
+        + Debug
+        + Eq
+        + Hash
+        + Ord
+        + serde::Serialize
+        + serde::de::DeserializeOwned
+        + borsh::BorshSerialize
+        + borsh::BorshDeserialize
+        + bincode::Encode
+        + bincode::Decode<()>
+        + for<'a> Arbitrary<'a>
+{
+}
+
+/// Input for the fuzz targets that selects the key type.
+#[derive(Arbitrary, Clone, Debug)]
+pub enum FuzzInput {
+    U8(Input<u8>),
+    U16(Input<u16>),
+    U64(Input<u64>),
+    I32(Input<i32>),
+    U128(Input<u128>),
+}
+
+impl FuzzInput {
+    pub fn run(&self) {
+        match self {
+            Self::U8(input) => run_with_reproducer(input),
+            Self::U16(input) => run_with_reproducer(input),
+            Self::U64(input) => run_with_reproducer(input),
+            Self::I32(input) => run_with_reproducer(input),
+            Self::U128(input) => run_with_reproducer(input),
+        }
+    }
+}
+
+/// A constructor followed by a sequence of operations.
+#[derive(Clone, Debug)]
+pub struct Input<K> {
+    pub ctor: Ctor<K>,
+    pub ops: Vec<Op<K>>,
+}
+
+impl<'a, K: Arbitrary<'a>> Arbitrary<'a> for Input<K> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let ctor = u.arbitrary()?;
+
+        // The derived implementation for `Vec` stops after every element with a probability of
+        // 50%, which makes long sequences of ops unlikely. We consume all remaining data instead.
+        let mut ops = Vec::new();
+        while !u.is_empty() {
+            ops.push(u.arbitrary()?);
+        }
+
+        Ok(Self { ctor, ops })
+    }
+}
+
+#[derive(Arbitrary, Clone, Debug)]
+pub enum Ctor<K> {
+    New,
+    WithCapacity(u8),
+    Default,
+    FromIter(Vec<(K, u8)>),
+    Arbitrary(Vec<u8>),
+}
+
+#[derive(Arbitrary, Clone, Debug)]
+pub enum Op<K> {
+    SetLoadFactor(u8),
+    GetLoadFactor,
+    Reserve(u8),
+    Insert(K, u8),
+    InsertChecked(K, u8),
+    Get(K),
+    GetMut(K, u8),
+    Remove(K),
+    ContainsKey(K),
+    Clear,
+    Retain(u8),
+    IsEmpty,
+    Iter,
+    IterMut(u8),
+    Keys,
+    Values,
+    ValuesMut(u8),
+    Drain(u8),
+    IntoIter,
+    Len,
+    Load,
+    LoadRate,
+    Capacity,
+    Collisions,
+    Stats,
+    Entry(K),
+    EntryOrInsert(K, u8),
+    EntryOrInsertWithKey(K, u8),
+    EntryOrDefault(K),
+    EntryInsert(K, u8),
+    EntryGetMut(K, u8),
+    EntryRemove(K),
+    Clone,
+    Eq(K),
+    Debug,
+    Extend(Vec<(K, u8)>),
+    FromIter(Vec<(K, u8)>),
+    Serde,
+    Borsh,
+    Bincode,
+}
+
+/// Runs `input`, on failure the reproducer is printed to stderr before the panic continues.
+pub fn run_with_reproducer<K: DiffKey>(input: &Input<K>) {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| run(input)));
+    if let Err(err) = result {
+        eprintln!("{}", input.reproducer());
+        panic::resume_unwind(err);
+    }
+}
+
+/// Runs `input` against `IntMap` and `HashMap` and panics on the first difference.
+pub fn run<K: DiffKey>(input: &Input<K>) {
+    let (mut map, mut reference) = input.ctor.apply();
+    assert_map(&map, &reference);
+
+    for op in &input.ops {
+        op.apply(&mut map, &mut reference);
+        assert_map(&map, &reference);
+    }
+}
+
+impl<K: DiffKey> Input<K> {
+    /// Returns a Rust test case that reproduces this input.
+    pub fn reproducer(&self) -> String {
+        let mut code = String::new();
+        code.push_str("#[test]\n");
+        code.push_str("fn reproducer() {\n");
+        code.push_str("    use intmap_integration_test_differential::{run, Ctor, Input, Op};\n\n");
+        code.push_str("    run(&Input {\n");
+        code.push_str(&format!("        ctor: {},\n", self.ctor.code()));
+        code.push_str("        ops: vec![\n");
+        for op in &self.ops {
+            code.push_str(&format!("            {},\n", op.code()));
+        }
+        code.push_str("        ],\n");
+        code.push_str("    });\n");
+        code.push_str("}\n");
+        code
+    }
+}
+
+impl<K: DiffKey> Ctor<K> {
+    pub fn apply(&self) -> (IntMap<K, u8>, HashMap<K, u8>) {
+        match self {
+            Self::New => (IntMap::new(), HashMap::new()),
+            Self::WithCapacity(capacity) => (
+                IntMap::with_capacity(*capacity as usize),
+                HashMap::with_capacity(*capacity as usize),
+            ),
+            Self::Default => (IntMap::default(), HashMap::default()),
+            Self::FromIter(pairs) => (
+                pairs.iter().copied().collect(),
+                pairs.iter().copied().collect(),
+            ),
+            Self::Arbitrary(data) => {
+                let map: IntMap<K, u8> = Unstructured::new(data).arbitrary().unwrap_or_default();
+                let reference = map.iter().map(|(k, &v)| (k, v)).collect();
+                (map, reference)
+            }
+        }
+    }
+
+    fn code(&self) -> String {
+        match self {
+            Self::FromIter(pairs) => format!("Ctor::FromIter(vec!{pairs:?})"),
+            Self::Arbitrary(data) => format!("Ctor::Arbitrary(vec!{data:?})"),
+            ctor => format!("Ctor::{ctor:?}"),
+        }
+    }
+}
+
+impl<K: DiffKey> Op<K> {
+    pub fn apply(&self, map: &mut IntMap<K, u8>, reference: &mut HashMap<K, u8>) {
+        match *self {
+            Self::SetLoadFactor(load_factor) => {
+                map.set_load_factor(f32::from(load_factor % 100 + 1) / 10.0);
+            }
+            Self::GetLoadFactor => {
+                let load_factor = map.get_load_factor();
+                assert!(load_factor > 0.0);
+            }
+            Self::Reserve(additional) => {
+                map.reserve(additional as usize);
+                assert!(map.capacity() >= map.len() + additional as usize);
+            }
+            Self::Insert(key, value) => {
+                assert_eq!(map.insert(key, value), reference.insert(key, value));
+            }
+            Self::InsertChecked(key, value) => {
+                let inserted = !reference.contains_key(&key);
+                if inserted {
+                    reference.insert(key, value);
+                }
+                assert_eq!(map.insert_checked(key, value), inserted);
+            }
+            Self::Get(key) => {
+                assert_eq!(map.get(key), reference.get(&key));
+            }
+            Self::GetMut(key, value) => {
+                let actual = map.get_mut(key);
+                let expected = reference.get_mut(&key);
+                assert_eq!(actual, expected);
+                if let (Some(a), Some(e)) = (actual, expected) {
+                    *a = value;
+                    *e = value;
+                }
+            }
+            Self::Remove(key) => {
+                assert_eq!(map.remove(key), reference.remove(&key));
+            }
+            Self::ContainsKey(key) => {
+                assert_eq!(map.contains_key(key), reference.contains_key(&key));
+            }
+            Self::Clear => {
+                map.clear();
+                reference.clear();
+            }
+            Self::Retain(value) => {
+                let mut visited = 0;
+                map.retain(|k, &v| {
+                    visited += 1;
+                    assert_eq!(reference.get(&k), Some(&v));
+                    v != value
+                });
+                assert_eq!(visited, reference.len());
+                reference.retain(|_, &mut v| v != value);
+            }
+            Self::IsEmpty => {
+                assert_eq!(map.is_empty(), reference.is_empty());
+            }
+            Self::Iter => {
+                let actual = sorted(map.iter().map(|(k, &v)| (k, v)));
+                assert_eq!(actual, sorted_reference(reference));
+                assert_eq!(sorted((&*map).into_iter().map(|(k, &v)| (k, v))), actual);
+            }
+            Self::IterMut(add) => {
+                for (k, v) in map.iter_mut() {
+                    *v = v.wrapping_add(add);
+                    let e = reference.get_mut(&k).expect("key missing in reference");
+                    *e = e.wrapping_add(add);
+                }
+            }
+            Self::Keys => {
+                let actual = sorted(map.keys());
+                assert_eq!(actual, sorted(reference.keys().copied()));
+            }
+            Self::Values => {
+                let actual = sorted(map.values().copied());
+                assert_eq!(actual, sorted(reference.values().copied()));
+            }
+            Self::ValuesMut(add) => {
+                for v in map.values_mut() {
+                    *v = v.wrapping_add(add);
+                }
+                for v in reference.values_mut() {
+                    *v = v.wrapping_add(add);
+                }
+            }
+            Self::Drain(take) => {
+                // Dropping the drain early must still remove all remaining pairs.
+                let drained: Vec<_> = map.drain().take(take as usize).collect();
+                for (k, v) in drained {
+                    assert_eq!(reference.get(&k), Some(&v));
+                }
+                reference.clear();
+            }
+            Self::IntoIter => {
+                let pairs = sorted(map.clone().into_iter());
+                assert_eq!(pairs, sorted_reference(reference));
+                *map = pairs.into_iter().collect();
+            }
+            Self::Len => {
+                assert_eq!(map.len(), reference.len());
+            }
+            Self::Load => {
+                assert!(map.load() as usize <= map.len());
+            }
+            Self::LoadRate => {
+                if map.capacity() > 0 {
+                    let expected = map.len() as f64 / map.capacity() as f64 * 100.0;
+                    assert_eq!(map.load_rate(), expected);
+                }
+            }
+            Self::Capacity => {
+                assert!(map.capacity() == 0 || map.capacity().is_power_of_two());
+            }
+            Self::Collisions => {
+                let collisions = map.collisions();
+                let colliding: u64 = collisions.iter().map(|(len, count)| len * count).sum();
+                assert!(colliding as usize <= map.len());
+            }
+            Self::Stats => {
+                let stats = map.stats();
+                assert_eq!(stats.len, reference.len());
+                assert_eq!(stats.capacity, map.capacity());
+                assert_eq!(stats.histogram.iter().sum::<usize>(), stats.capacity);
+                assert_eq!(
+                    stats.colliding_slots() as u64,
+                    map.collisions().values().sum::<u64>()
+                );
+            }
+            Self::Entry(key) => match map.entry(key) {
+                Entry::Occupied(entry) => assert_eq!(Some(entry.get()), reference.get(&key)),
+                Entry::Vacant(_) => assert!(!reference.contains_key(&key)),
+            },
+            Self::EntryOrInsert(key, value) => {
+                assert_eq!(
+                    *map.entry(key).or_insert(value),
+                    *reference.entry(key).or_insert(value)
+                );
+            }
+            Self::EntryOrInsertWithKey(key, value) => {
+                let actual = *map.entry(key).or_insert_with_key(|k| {
+                    assert_eq!(k, key);
+                    value
+                });
+                assert_eq!(actual, *reference.entry(key).or_insert(value));
+            }
+            Self::EntryOrDefault(key) => {
+                let actual = map.entry(key).or_default();
+                *actual = actual.wrapping_add(1);
+                let expected = reference.entry(key).or_default();
+                *expected = expected.wrapping_add(1);
+                assert_eq!(actual, expected);
+            }
+            Self::EntryInsert(key, value) => {
+                let old = match map.entry(key) {
+                    Entry::Occupied(mut entry) => Some(entry.insert(value)),
+                    Entry::Vacant(entry) => {
+                        assert_eq!(*entry.insert(value), value);
+                        None
+                    }
+                };
+                assert_eq!(old, reference.insert(key, value));
+            }
+            Self::EntryGetMut(key, value) => {
+                if let Entry::Occupied(mut entry) = map.entry(key) {
+                    *entry.get_mut() = value;
+                    reference.insert(key, value);
+                }
+            }
+            Self::EntryRemove(key) => {
+                let removed = match map.entry(key) {
+                    Entry::Occupied(entry) => Some(entry.remove()),
+                    Entry::Vacant(_) => None,
+                };
+                assert_eq!(removed, reference.remove(&key));
+            }
+            Self::Clone => {
+                let clone = map.clone();
+                assert_eq!(clone, *map);
+                *map = clone;
+            }
+            Self::Eq(key) => {
+                let mut other = map.clone();
+                assert_eq!(other, *map);
+                match other.remove(key) {
+                    Some(_) => assert_ne!(other, *map),
+                    None => {
+                        other.insert(key, 0);
+                        assert_ne!(other, *map);
+                    }
+                }
+            }
+            Self::Debug => {
+                let debug = format!("{map:?}");
+                assert_eq!(map.is_empty(), debug == "{}");
+            }
+            Self::Extend(ref pairs) => {
+                map.extend(pairs.iter().copied());
+                reference.extend(pairs.iter().copied());
+            }
+            Self::FromIter(ref pairs) => {
+                *map = pairs.iter().copied().collect();
+                *reference = pairs.iter().copied().collect();
+            }
+            Self::Serde => {
+                let json = serde_json::to_string(&*map).unwrap();
+                let copy: IntMap<K, u8> = serde_json::from_str(&json).unwrap();
+                assert_eq!(copy, *map);
+            }
+            Self::Borsh => {
+                let bytes = borsh::to_vec(&*map).unwrap();
+                let copy: IntMap<K, u8> = borsh::from_slice(&bytes).unwrap();
+                assert_eq!(copy, *map);
+                assert_eq!(bytes, borsh::to_vec(&copy).unwrap());
+            }
+            Self::Bincode => {
+                let config = bincode::config::standard();
+                let bytes = bincode::encode_to_vec(&*map, config).unwrap();
+                let (copy, _): (IntMap<K, u8>, _) =
+                    bincode::decode_from_slice(&bytes, config).unwrap();
+                assert_eq!(copy, *map);
+                assert_eq!(bytes, bincode::encode_to_vec(&copy, config).unwrap());
+            }
+        }
+    }
+
+    fn code(&self) -> String {
+        match self {
+            Self::Extend(pairs) => format!("Op::Extend(vec!{pairs:?})"),
+            Self::FromIter(pairs) => format!("Op::FromIter(vec!{pairs:?})"),
+            op => format!("Op::{op:?}"),
+        }
+    }
+}
+
+fn sorted<T: Ord>(iter: impl Iterator<Item = T>) -> Vec<T> {
+    let mut vec: Vec<T> = iter.collect();
+    vec.sort();
+    vec
+}
+
+fn sorted_reference<K: DiffKey>(reference: &HashMap<K, u8>) -> Vec<(K, u8)> {
+    sorted(reference.iter().map(|(&k, &v)| (k, v)))
+}
+
+fn assert_map<K: DiffKey>(map: &IntMap<K, u8>, reference: &HashMap<K, u8>) {
+    assert_eq!(map.validate(), Ok(()));
+    assert_eq!(map.len(), reference.len());
+    for (k, v) in reference {
+        assert_eq!(map.get(*k), Some(v));
+    }
+}
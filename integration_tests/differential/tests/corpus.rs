@@ -0,0 +1,63 @@
+use std::fs;
+use std::path::Path;
+
+use arbitrary::{Arbitrary, Unstructured};
+use intmap_integration_test_differential::{FuzzInput, Input, Op};
+use rand::prelude::StdRng;
+use rand::{Rng, SeedableRng};
+
+// Replays the corpus of the `differential` fuzz target, so every checked in input is also
+// covered by `cargo test`.
+#[test]
+fn replay_fuzz_corpus() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../fuzz/corpus/differential");
+    let mut count = 0;
+
+    for entry in fs::read_dir(&dir).unwrap() {
+        let data = fs::read(entry.unwrap().path()).unwrap();
+        if let Ok(input) = FuzzInput::arbitrary_take_rest(Unstructured::new(&data)) {
+            input.run();
+            count += 1;
+        }
+    }
+
+    assert!(count > 0, "no inputs found in {dir:?}");
+}
+
+#[test]
+fn random_inputs() {
+    let mut rng = StdRng::seed_from_u64(4242);
+
+    for _ in 0..500 {
+        let len = rng.gen_range(0..4096);
+        let data: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+        if let Ok(input) = FuzzInput::arbitrary_take_rest(Unstructured::new(&data)) {
+            input.run();
+        }
+    }
+}
+
+#[test]
+fn reproducer_is_valid_rust() {
+    let input = Input::<i32> {
+        ctor: intmap_integration_test_differential::Ctor::FromIter(vec![(-1, 2)]),
+        ops: vec![Op::Insert(-1, 3), Op::Extend(vec![(4, 5)]), Op::Clear],
+    };
+
+    let code = input.reproducer();
+    assert!(code.contains("ctor: Ctor::FromIter(vec![(-1, 2)]),"));
+    assert!(code.contains("Op::Insert(-1, 3),"));
+    assert!(code.contains("Op::Extend(vec![(4, 5)]),"));
+    assert!(code.contains("Op::Clear,"));
+}
+
+// The output of `reproducer_is_valid_rust`, which makes sure it actually compiles.
+#[test]
+fn reproducer() {
+    use intmap_integration_test_differential::{run, Ctor, Input, Op};
+
+    run(&Input {
+        ctor: Ctor::FromIter(vec![(-1, 2)]),
+        ops: vec![Op::Insert(-1, 3), Op::Extend(vec![(4, 5)]), Op::Clear],
+    });
+}
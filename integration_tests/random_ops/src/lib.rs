@@ -5,7 +5,7 @@ use intmap::{IntKey, IntMap};
 use proptest::collection::vec;
 use proptest::prelude::*;
 
-pub trait TestIntKey: IntKey + Debug + Eq + Hash + 'static {
+pub trait TestIntKey: IntKey + Arbitrary + Debug + Eq + Hash + 'static {
     type Range: Strategy<Value = Self>;
 
     fn small_range() -> Self::Range;
@@ -44,10 +44,10 @@ impl_test_int_for_int!(i128);
 pub struct Capacity(usize);
 
 impl Capacity {
-    const MAX: usize = 100;
+    const MAX: usize = intmap::proptest::MAX_CAPACITY;
 
     fn arb() -> impl Strategy<Value = Self> {
-        (0usize..=Self::MAX).prop_map(Self)
+        intmap::proptest::capacity().prop_map(Self)
     }
 }
 
@@ -56,7 +56,7 @@ pub struct LoadFactor(f32);
 
 impl LoadFactor {
     fn arb() -> impl Strategy<Value = Self> {
-        (0.1f32..=10.0f32).prop_map(Self)
+        intmap::proptest::load_factor().prop_map(Self)
     }
 }
 
@@ -98,11 +98,12 @@ impl<K: TestIntKey> Pairs<K> {
 }
 
 #[derive(Clone, Debug)]
-pub enum Ctor<K> {
+pub enum Ctor<K: TestIntKey> {
     New,
     WithCapacity(Capacity),
     Default,
     FromIter(Pairs<K>),
+    Arbitrary(IntMap<K, u8>),
 }
 
 impl<K: TestIntKey> Ctor<K> {
@@ -112,6 +113,7 @@ impl<K: TestIntKey> Ctor<K> {
             Capacity::arb().prop_map(Self::WithCapacity),
             Just(Self::Default),
             Pairs::arb().prop_map(Self::FromIter),
+            any::<IntMap<K, u8>>().prop_map(Self::Arbitrary),
         ]
     }
 
@@ -124,6 +126,7 @@ impl<K: TestIntKey> Ctor<K> {
                 IntMap::from_iter(pairs.0.clone()),
                 HashMap::from_iter(pairs.0.clone()),
             ),
+            Self::Arbitrary(map) => (map.clone(), map.iter().map(|(k, &v)| (k, v)).collect()),
         }
     }
 }
@@ -153,6 +156,7 @@ pub enum Op<K> {
     LoadRate,
     Capacity,
     Collisions,
+    Stats,
     Entry(Key<K>),
     EntryInsert((Key<K>, Value)),
     EntryRemove(Key<K>),
@@ -191,6 +195,7 @@ impl<K: TestIntKey> Op<K> {
             1 => Just(Self::LoadRate),
             1 => Just(Self::Capacity),
             1 => Just(Self::Collisions),
+            1 => Just(Self::Stats),
             10 => Key::arb().prop_map(Self::Entry),
             10 => (Key::arb(), Value::arb()).prop_map(Self::EntryInsert),
             10 => Key::arb().prop_map(Self::EntryRemove),
@@ -274,6 +279,9 @@ impl<K: TestIntKey> Op<K> {
             Self::Collisions => {
                 map.collisions();
             }
+            Self::Stats => {
+                assert_eq!(map.stats().len, reference.len());
+            }
             Self::Entry(key) => {
                 map.entry(key.0);
             }
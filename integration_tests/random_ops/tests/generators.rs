@@ -0,0 +1,42 @@
+use arbitrary::Unstructured;
+use intmap::IntMap;
+use quickcheck::{Arbitrary, Gen};
+
+#[test]
+fn quickcheck_maps_are_consistent() {
+    let mut gen = Gen::new(100);
+
+    for _ in 0..100 {
+        let map = IntMap::<u8, u8>::arbitrary(&mut gen);
+        assert!(map.assert_count());
+
+        for shrunk in map.shrink().take(10) {
+            assert!(shrunk.assert_count());
+            assert!(shrunk.len() <= map.len());
+        }
+    }
+}
+
+#[test]
+fn quickcheck_property() {
+    fn len_matches_iter(map: IntMap<i64, u8>) -> bool {
+        map.len() == map.iter().count()
+    }
+
+    quickcheck::quickcheck(len_matches_iter as fn(IntMap<i64, u8>) -> bool);
+}
+
+#[test]
+fn arbitrary_maps_are_consistent() {
+    let data: Vec<u8> = (0..=255u8).cycle().take(4096).collect();
+
+    for start in 0..64 {
+        let mut u = Unstructured::new(&data[start..]);
+        let map: IntMap<u16, u8> = u.arbitrary().unwrap();
+        assert!(map.assert_count());
+
+        let map: IntMap<u16, u8> =
+            arbitrary::Arbitrary::arbitrary_take_rest(Unstructured::new(&data[start..])).unwrap();
+        assert!(map.assert_count());
+    }
+}
@@ -100,6 +100,6 @@ fn assert_map<K: IntKey>(map: &IntMap<K, u8>, reference: &HashMap<K, u8>) {
         );
     }
 
-    map.assert_count();
+    assert_eq!(map.validate(), Ok(()));
     assert_eq!(map.len(), reference.len());
 }
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::fmt::Debug;
 
-use intmap::{IntKey, IntMap};
+use intmap::{IntBiMap, IntIndexMap, IntKey, IntMap};
 use proptest::collection::hash_map;
 use proptest::prelude::*;
 use serde::{de::DeserializeOwned, Serialize};
@@ -78,3 +78,31 @@ where
     prop_assert_eq!(im, im_copy);
     Ok(())
 }
+
+proptest! {
+    #[test]
+    fn test_roundtrip_bi_map(m in hash_map(any::<u32>(), any::<i64>(), 0..20)) {
+        let bm: IntBiMap<u32, i64> = m.into_iter().collect();
+        let bytes = serde_json::to_vec(&bm).unwrap();
+        let bm_copy: IntBiMap<u32, i64> = serde_json::from_slice(&bytes[..]).unwrap();
+        prop_assert_eq!(bm, bm_copy);
+    }
+
+    #[test]
+    fn test_roundtrip_index_map(v in proptest::collection::vec((any::<u16>(), any::<String>()), 0..20)) {
+        let im: IntIndexMap<u16, String> = v.into_iter().collect();
+        let bytes = serde_json::to_vec(&im).unwrap();
+        let im_copy: IntIndexMap<u16, String> = serde_json::from_slice(&bytes[..]).unwrap();
+        prop_assert_eq!(im.iter().collect::<Vec<_>>(), im_copy.iter().collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn test_bi_map_rejects_duplicates() {
+    let ok: IntBiMap<u8, u8> = serde_json::from_str(r#"{"1": 2, "2": 1}"#).unwrap();
+    assert_eq!(ok.get_by_right(1), Some(2));
+
+    let err = serde_json::from_str::<IntBiMap<u8, u8>>(r#"{"1": 2, "3": 2}"#).unwrap_err();
+    assert!(err.to_string().contains("duplicate value"), "{err}");
+    assert!(serde_json::from_str::<IntBiMap<u8, u8>>(r#"{"1": 2, "1": 3}"#).is_err());
+}
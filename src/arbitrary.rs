@@ -0,0 +1,44 @@
+use crate::{IntKey, IntMap};
+use arbitrary::{size_hint, Arbitrary, Result, Unstructured};
+
+impl<'a, K, V> Arbitrary<'a> for IntMap<K, V>
+where
+    K: IntKey + Arbitrary<'a>,
+    V: Arbitrary<'a>,
+{
+    /// Consumes the initial capacity and load factor first, so fuzzers explore the internal
+    /// layout as well as the pairs.
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut map = Self::with_layout(u)?;
+        for pair in u.arbitrary_iter()? {
+            let (k, v) = pair?;
+            map.insert(k, v);
+        }
+        Ok(map)
+    }
+
+    fn arbitrary_take_rest(mut u: Unstructured<'a>) -> Result<Self> {
+        let mut map = Self::with_layout(&mut u)?;
+        for pair in u.arbitrary_take_rest_iter()? {
+            let (k, v) = pair?;
+            map.insert(k, v);
+        }
+        Ok(map)
+    }
+
+    #[inline]
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        size_hint::and(<(u8, u8) as Arbitrary>::size_hint(depth), (0, None))
+    }
+}
+
+impl<K: IntKey, V> IntMap<K, V> {
+    fn with_layout(u: &mut Unstructured<'_>) -> Result<Self> {
+        let capacity = u.int_in_range(0..=100)?;
+        let load_factor = f32::from(u.int_in_range(1u8..=100)?) / 10.0;
+
+        let mut map = Self::with_capacity(capacity);
+        map.set_load_factor(load_factor);
+        Ok(map)
+    }
+}
@@ -0,0 +1,169 @@
+use crate::{IntKey, IntMap};
+
+// The number of keys whose indices are computed before any of their buckets is searched.
+const CHUNK_LEN: usize = 16;
+
+impl<K: IntKey, V> IntMap<K, V> {
+    /// Returns the values of all keys in the same order as the keys.
+    ///
+    /// See [`IntMap::get_batch`] for how the keys are looked up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntMap;
+    ///
+    /// let map: IntMap<u64, &str> = [(1, "a"), (2, "b")].into_iter().collect();
+    /// assert_eq!(map.get_many(&[2, 3, 1]), [Some(&"b"), None, Some(&"a")]);
+    /// ```
+    pub fn get_many(&self, keys: &[K]) -> Vec<Option<&V>> {
+        let mut out = vec![None; keys.len()];
+        self.get_batch(keys, &mut out);
+        out
+    }
+
+    /// Writes the value of `keys[i]` into `out[i]` for every key.
+    ///
+    /// The keys are processed in chunks of 16. The bucket indices of a whole chunk are computed
+    /// before the first bucket is searched, so the lookups of a chunk don't depend on each other
+    /// and the CPU may overlap them. The keys are neither reordered nor prefetched, so whether
+    /// this beats calling [`IntMap::get`] in a loop depends on the map and the CPU, see the
+    /// `get_many`/`get_batch` benchmarks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `keys` and `out` have different lengths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntMap;
+    ///
+    /// let map: IntMap<u64, u64> = [(1, 10), (2, 20)].into_iter().collect();
+    /// let mut out = [None; 3];
+    /// map.get_batch(&[1, 2, 3], &mut out);
+    /// assert_eq!(out, [Some(&10), Some(&20), None]);
+    /// ```
+    pub fn get_batch<'a>(&'a self, keys: &[K], out: &mut [Option<&'a V>]) {
+        assert_eq!(
+            keys.len(),
+            out.len(),
+            "keys and out must have the same length"
+        );
+
+        if self.is_empty() {
+            out.iter_mut().for_each(|value| *value = None);
+            return;
+        }
+
+        for (keys, out) in keys.chunks(CHUNK_LEN).zip(out.chunks_mut(CHUNK_LEN)) {
+            let mut buckets: [&[(K, V)]; CHUNK_LEN] = [&[]; CHUNK_LEN];
+            for (bucket, key) in buckets.iter_mut().zip(keys) {
+                *bucket = &self.cache[self.index_of(*key)];
+            }
+
+            for ((value, key), bucket) in out.iter_mut().zip(keys).zip(&buckets) {
+                let k = key.into_int();
+                *value = bucket
+                    .iter()
+                    .find_map(|kv| (kv.0.into_int() == k).then(|| &kv.1));
+            }
+        }
+    }
+
+    /// Inserts all key/value pairs.
+    ///
+    /// Like [`IntMap::get_batch`], the buckets of 16 pairs are searched before any of them is
+    /// changed. The map only grows for the keys of a chunk that weren't found, so replacing
+    /// existing keys never resizes it. If a key occurs multiple times, the last value wins.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntMap;
+    ///
+    /// let mut map: IntMap<u64, &str> = IntMap::new();
+    /// map.insert_batch(vec![(1, "a"), (2, "b"), (1, "c")]);
+    /// assert_eq!(map, [(1, "c"), (2, "b")].into_iter().collect());
+    /// ```
+    pub fn insert_batch<I>(&mut self, pairs: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let pairs: Vec<(K, V)> = pairs.into_iter().collect();
+        if pairs.is_empty() {
+            return;
+        }
+
+        let mut pairs = pairs.into_iter();
+        while !pairs.as_slice().is_empty() {
+            let chunk_len = pairs.as_slice().len().min(CHUNK_LEN);
+            let chunk = &pairs.as_slice()[..chunk_len];
+
+            // Keys that occur twice in the chunk are counted twice, so the map might grow a few
+            // pairs early.
+            let mut found = self.search_chunk(chunk);
+            let added = found[..chunk_len]
+                .iter()
+                .filter(|(_, pos, _)| pos.is_none())
+                .count();
+            let cache_len = self.cache.len();
+            while crate::exceeds_load_factor(self.count + added, self.cache.len(), self.load_factor)
+            {
+                self.increase_cache();
+            }
+            if self.cache.len() != cache_len {
+                found = self.search_chunk(chunk);
+            }
+
+            for ((key, value), (ix, pos, len)) in pairs.by_ref().take(chunk_len).zip(found) {
+                let k = key.into_int();
+                let vals = &mut self.cache[ix];
+                // Pairs are only appended within a chunk, so a key that wasn't found can only be
+                // among the pairs appended since the search.
+                let pos = pos.or_else(|| {
+                    vals[len..]
+                        .iter()
+                        .position(|kv| kv.0.into_int() == k)
+                        .map(|pos| len + pos)
+                });
+                match pos {
+                    Some(pos) => vals[pos].1 = value,
+                    None => {
+                        vals.push((key, value));
+                        self.count += 1;
+                    }
+                }
+            }
+        }
+
+        self.debug_validate();
+    }
+
+    // Returns the bucket index, the position of the key if it's present and the length of the
+    // bucket for every key of the chunk. The entries after the chunk are unused, and so are all
+    // entries if the map has no buckets yet.
+    fn search_chunk(&self, chunk: &[(K, V)]) -> [(usize, Option<usize>, usize); CHUNK_LEN] {
+        let mut found = [(0, None, 0); CHUNK_LEN];
+        if self.cache.is_empty() {
+            return found;
+        }
+
+        for (found, (key, _)) in found.iter_mut().zip(chunk) {
+            let k = key.into_int();
+            let ix = self.calc_index(k);
+            let vals = &self.cache[ix];
+            *found = (
+                ix,
+                vals.iter().position(|kv| kv.0.into_int() == k),
+                vals.len(),
+            );
+        }
+        found
+    }
+
+    #[inline]
+    fn index_of(&self, key: K) -> usize {
+        self.calc_index(key.into_int())
+    }
+}
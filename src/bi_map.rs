@@ -0,0 +1,345 @@
+//! A bidirectional map between two integer based keys, see [`IntBiMap`].
+
+use std::iter::FromIterator;
+
+use crate::{IntKey, IntMap};
+
+/// A bidirectional map between an integer based `L` and an integer based `R`.
+///
+/// Every left value is paired with exactly one right value and vice versa, so both can be used
+/// for lookups, e.g. for mapping internal dense ids to external ids and back.
+///
+/// # Examples
+///
+/// ```
+/// use intmap::bi_map::Overwritten;
+/// use intmap::IntBiMap;
+///
+/// let mut ids: IntBiMap<u32, u64> = IntBiMap::new();
+/// assert_eq!(ids.insert(0, 1_000), Overwritten::Neither);
+/// assert_eq!(ids.insert(1, 2_000), Overwritten::Neither);
+///
+/// assert_eq!(ids.get_by_left(1), Some(2_000));
+/// assert_eq!(ids.get_by_right(1_000), Some(0));
+///
+/// // Pairing 0 with 2_000 displaces both existing pairs.
+/// assert_eq!(
+///     ids.insert(0, 2_000),
+///     Overwritten::Both((0, 1_000), (1, 2_000))
+/// );
+/// assert_eq!(ids.len(), 1);
+/// assert_eq!(ids.remove_by_right(2_000), Some((0, 2_000)));
+/// assert!(ids.is_empty());
+/// ```
+#[derive(Clone)]
+pub struct IntBiMap<L, R> {
+    left: IntMap<L, R>,
+    right: IntMap<R, L>,
+}
+
+/// The pairs that were displaced by [`IntBiMap::insert`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Overwritten<L, R> {
+    /// Neither value was paired before.
+    Neither,
+    /// The left value was paired with another right value. Contains the displaced pair.
+    Left(L, R),
+    /// The right value was paired with another left value. Contains the displaced pair.
+    Right(L, R),
+    /// The exact same pair was already present.
+    Pair(L, R),
+    /// Both values were paired with other values. Contains the pair of the left value first and
+    /// the pair of the right value second.
+    Both((L, R), (L, R)),
+}
+
+impl<L, R> Overwritten<L, R> {
+    /// Returns true if a pair was displaced, including the exact same pair.
+    pub fn did_overwrite(&self) -> bool {
+        !matches!(self, Overwritten::Neither)
+    }
+}
+
+impl<L, R> IntBiMap<L, R> {
+    /// Creates a new [`IntBiMap`].
+    ///
+    /// The [`IntBiMap`] is initially created with a capacity of 0, so it will not allocate until
+    /// it is first inserted into.
+    pub const fn new() -> Self {
+        Self {
+            left: IntMap::new(),
+            right: IntMap::new(),
+        }
+    }
+}
+
+impl<L: IntKey, R: IntKey> IntBiMap<L, R> {
+    /// Creates a new [`IntBiMap`] with space for at least the given number of pairs.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            left: IntMap::with_capacity(capacity),
+            right: IntMap::with_capacity(capacity),
+        }
+    }
+
+    /// Inserts a pair and returns the pairs it displaced.
+    ///
+    /// Any existing pair that contains either `left` or `right` is removed first.
+    pub fn insert(&mut self, left: L, right: R) -> Overwritten<L, R> {
+        let overwritten = match (self.remove_by_left(left), self.remove_by_right(right)) {
+            (None, None) => Overwritten::Neither,
+            (Some(pair), None) => {
+                if pair.1.into_int() == right.into_int() {
+                    Overwritten::Pair(pair.0, pair.1)
+                } else {
+                    Overwritten::Left(pair.0, pair.1)
+                }
+            }
+            (None, Some(pair)) => Overwritten::Right(pair.0, pair.1),
+            (Some(left_pair), Some(right_pair)) => Overwritten::Both(left_pair, right_pair),
+        };
+
+        self.left.insert(left, right);
+        self.right.insert(right, left);
+        overwritten
+    }
+
+    /// Inserts a pair only if neither value is paired yet.
+    ///
+    /// Returns the pair as error if either value is already paired.
+    pub fn insert_no_overwrite(&mut self, left: L, right: R) -> Result<(), (L, R)> {
+        if self.contains_left(left) || self.contains_right(right) {
+            return Err((left, right));
+        }
+
+        self.left.insert(left, right);
+        self.right.insert(right, left);
+        Ok(())
+    }
+
+    /// Returns the right value paired with the left value.
+    pub fn get_by_left(&self, left: L) -> Option<R> {
+        self.left.get(left).copied()
+    }
+
+    /// Returns the left value paired with the right value.
+    pub fn get_by_right(&self, right: R) -> Option<L> {
+        self.right.get(right).copied()
+    }
+
+    /// Returns true if the left value is paired.
+    pub fn contains_left(&self, left: L) -> bool {
+        self.left.contains_key(left)
+    }
+
+    /// Returns true if the right value is paired.
+    pub fn contains_right(&self, right: R) -> bool {
+        self.right.contains_key(right)
+    }
+
+    /// Removes the pair that contains the left value and returns it.
+    pub fn remove_by_left(&mut self, left: L) -> Option<(L, R)> {
+        let right = self.left.remove(left)?;
+        self.right.remove(right);
+        Some((left, right))
+    }
+
+    /// Removes the pair that contains the right value and returns it.
+    pub fn remove_by_right(&mut self, right: R) -> Option<(L, R)> {
+        let left = self.right.remove(right)?;
+        self.left.remove(left);
+        Some((left, right))
+    }
+
+    /// Retains only the pairs specified by the predicate.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(L, R) -> bool,
+    {
+        self.left.retain(|left, &right| f(left, right));
+        let left = &self.left;
+        self.right.retain(|_, &l| left.contains_key(l));
+    }
+
+    /// Removes all pairs, but keeps the allocated memory for reuse.
+    pub fn clear(&mut self) {
+        self.left.clear();
+        self.right.clear();
+    }
+
+    /// Returns the number of pairs.
+    pub fn len(&self) -> usize {
+        self.left.len()
+    }
+
+    /// Returns true if the map contains no pairs.
+    pub fn is_empty(&self) -> bool {
+        self.left.is_empty()
+    }
+
+    /// Returns an iterator over all pairs.
+    pub fn iter(&self) -> Iter<'_, L, R> {
+        Iter {
+            inner: self.left.iter(),
+        }
+    }
+
+    /// Returns an iterator over all left values.
+    pub fn left_values(&self) -> LeftValues<'_, L, R> {
+        LeftValues {
+            inner: self.left.keys(),
+        }
+    }
+
+    /// Returns an iterator over all right values.
+    pub fn right_values(&self) -> RightValues<'_, L, R> {
+        RightValues {
+            inner: self.right.keys(),
+        }
+    }
+}
+
+impl<L, R> Default for IntBiMap<L, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ***************** Equality *********************
+
+impl<L: IntKey, R: IntKey> PartialEq for IntBiMap<L, R> {
+    fn eq(&self, other: &IntBiMap<L, R>) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(l, r)| other.get_by_left(l).map(IntKey::into_int) == Some(r.into_int()))
+    }
+}
+impl<L: IntKey, R: IntKey> Eq for IntBiMap<L, R> {}
+
+// ***************** Debug *********************
+
+impl<L, R> std::fmt::Debug for IntBiMap<L, R>
+where
+    L: IntKey + std::fmt::Debug,
+    R: IntKey + std::fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_map().entries(self.iter()).finish()
+    }
+}
+
+// ***************** Extend *********************
+
+impl<L: IntKey, R: IntKey> Extend<(L, R)> for IntBiMap<L, R> {
+    /// Inserts all pairs, later pairs displace earlier ones like [`IntBiMap::insert`].
+    #[inline]
+    fn extend<T: IntoIterator<Item = (L, R)>>(&mut self, iter: T) {
+        for (left, right) in iter {
+            self.insert(left, right);
+        }
+    }
+}
+
+// ***************** FromIterator *********************
+
+impl<L: IntKey, R: IntKey> FromIterator<(L, R)> for IntBiMap<L, R> {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = (L, R)>>(iter: T) -> Self {
+        let mut map = IntBiMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+// ***************** Iter *********************
+
+impl<'a, L: IntKey, R: IntKey> IntoIterator for &'a IntBiMap<L, R> {
+    type Item = (L, R);
+    type IntoIter = Iter<'a, L, R>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the pairs of a [`IntBiMap`].
+///
+/// This struct is created by [`IntBiMap::iter`].
+pub struct Iter<'a, L: IntKey, R> {
+    inner: crate::Iter<'a, L, R>,
+}
+
+impl<'a, L: IntKey, R: IntKey> Iterator for Iter<'a, L, R> {
+    type Item = (L, R);
+
+    #[inline]
+    fn next(&mut self) -> Option<(L, R)> {
+        self.inner.next().map(|(left, &right)| (left, right))
+    }
+}
+
+// ***************** Left Values Iter *********************
+
+/// An iterator over the left values of a [`IntBiMap`].
+///
+/// This struct is created by [`IntBiMap::left_values`].
+pub struct LeftValues<'a, L: IntKey, R> {
+    inner: crate::Keys<'a, L, R>,
+}
+
+impl<'a, L: IntKey, R> Iterator for LeftValues<'a, L, R> {
+    type Item = L;
+
+    #[inline]
+    fn next(&mut self) -> Option<L> {
+        self.inner.next()
+    }
+}
+
+// ***************** Right Values Iter *********************
+
+/// An iterator over the right values of a [`IntBiMap`].
+///
+/// This struct is created by [`IntBiMap::right_values`].
+pub struct RightValues<'a, L, R: IntKey> {
+    inner: crate::Keys<'a, R, L>,
+}
+
+impl<'a, L, R: IntKey> Iterator for RightValues<'a, L, R> {
+    type Item = R;
+
+    #[inline]
+    fn next(&mut self) -> Option<R> {
+        self.inner.next()
+    }
+}
+
+// ***************** Into Iter *********************
+
+impl<L: IntKey, R: IntKey> IntoIterator for IntBiMap<L, R> {
+    type Item = (L, R);
+    type IntoIter = IntoIter<L, R>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.left.into_iter(),
+        }
+    }
+}
+
+/// An owning iterator over the pairs of a [`IntBiMap`].
+///
+/// This struct is created by [`IntBiMap::into_iter`].
+pub struct IntoIter<L: IntKey, R> {
+    inner: crate::IntoIter<L, R>,
+}
+
+impl<L: IntKey, R> Iterator for IntoIter<L, R> {
+    type Item = (L, R);
+
+    #[inline]
+    fn next(&mut self) -> Option<(L, R)> {
+        self.inner.next()
+    }
+}
@@ -0,0 +1,199 @@
+//! Finds a good `IntKey::PRIME` and `IntKey::into_int` for a sample of keys.
+//!
+//! Reads integer keys separated by whitespace or commas from a file or stdin, evaluates them with
+//! `intmap::tune::tune` and prints the best candidates together with a ready-to-paste `IntKey`
+//! impl. Run with `--help` for the options.
+
+use std::fmt::LowerHex;
+use std::io::Read;
+use std::process;
+
+use intmap::tune::{tune, TuneOptions};
+use intmap::{Int, IntKey};
+
+const USAGE: &str = "\
+Usage: intmap-tune [OPTIONS] [FILE]
+
+Reads integer keys separated by whitespace or commas from FILE or stdin. Keys may be decimal,
+negative or hexadecimal with a 0x prefix.
+
+Options:
+    --bits <8|16|32|64|128>   Width of the underlying integer [default: 64]
+    --type <NAME>             Name of the key type in the generated impl [default: MyKey]
+    --key <EXPR>              Expression returning the underlying integer [default: self.0]
+    --candidates <N>          Number of random multipliers [default: 64]
+    --seed <N>                Seed for the random multipliers [default: 0]
+    --capacity <N>            Capacity to evaluate at, can be repeated
+    --hit-ratio <RATIO>       Share of lookups for present keys [default: 0.5]
+    --top <N>                 Number of candidates to list [default: 5]
+    -h, --help                Prints this help
+";
+
+struct Args {
+    bits: u32,
+    type_name: String,
+    key: String,
+    top: usize,
+    file: Option<String>,
+    options: TuneOptions,
+}
+
+fn main() {
+    let args = parse_args().unwrap_or_else(|err| exit(&err));
+    let input = read_input(args.file.as_deref()).unwrap_or_else(|err| exit(&err));
+
+    let keys = input
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .map(|s| parse_key(s, args.bits))
+        .collect::<Result<Vec<u128>, String>>()
+        .unwrap_or_else(|err| exit(&err));
+    if keys.is_empty() {
+        exit("no keys found");
+    }
+
+    match args.bits {
+        8 => report::<u8>(&keys, &args),
+        16 => report::<u16>(&keys, &args),
+        32 => report::<u32>(&keys, &args),
+        64 => report::<u64>(&keys, &args),
+        128 => report::<u128>(&keys, &args),
+        _ => unreachable!(),
+    }
+}
+
+fn report<I>(keys: &[u128], args: &Args)
+where
+    I: Int + IntKey<Int = I> + TryFrom<u128> + LowerHex,
+{
+    // Keys were already truncated to `args.bits` while parsing.
+    let keys: Vec<I> = keys.iter().filter_map(|&k| I::try_from(k).ok()).collect();
+    let candidates = tune(&keys, &args.options);
+    let best = &candidates[0];
+
+    println!(
+        "Evaluated {} candidates for {} keys",
+        candidates.len(),
+        keys.len()
+    );
+    println!();
+    println!(
+        "{:>4}  {:<36}  {:<16}  {:>8}",
+        "rank", "prime", "strategy", "cost"
+    );
+    for (rank, candidate) in candidates.iter().take(args.top).enumerate() {
+        println!(
+            "{:>4}  {:<36}  {:<16}  {:>8.4}",
+            rank + 1,
+            format!("{:#x}", candidate.prime),
+            format!("{:?}", candidate.strategy),
+            candidate.cost
+        );
+    }
+
+    println!();
+    println!("Best candidate:");
+    println!(
+        "{:>10}  {:>8}  {:>9}  {:>10}  {:>10}  {:>11}",
+        "capacity", "len", "max chain", "probe hit", "probe miss", "empty slots"
+    );
+    for stats in &best.stats {
+        println!(
+            "{:>10}  {:>8}  {:>9}  {:>10.4}  {:>10.4}  {:>10.2}%",
+            stats.capacity,
+            stats.len,
+            stats.max_chain_len,
+            stats.avg_probe_hit,
+            stats.avg_probe_miss,
+            stats.empty_slot_ratio * 100.0
+        );
+    }
+
+    println!();
+    print!("{}", best.int_key_impl(&args.type_name, &args.key));
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        bits: 64,
+        type_name: "MyKey".to_string(),
+        key: "self.0".to_string(),
+        top: 5,
+        file: None,
+        options: TuneOptions::default(),
+    };
+
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().ok_or(format!("missing value for {arg}"));
+        match arg.as_str() {
+            "-h" | "--help" => {
+                print!("{USAGE}");
+                process::exit(0);
+            }
+            "--bits" => {
+                args.bits = parse(&value()?)?;
+                if ![8, 16, 32, 64, 128].contains(&args.bits) {
+                    return Err(format!("unsupported bit width {}", args.bits));
+                }
+            }
+            "--type" => args.type_name = value()?,
+            "--key" => args.key = value()?,
+            "--candidates" => args.options.random_candidates = parse(&value()?)?,
+            "--seed" => args.options.seed = parse(&value()?)?,
+            "--capacity" => args.options.capacities.push(parse(&value()?)?),
+            "--hit-ratio" => args.options.hit_ratio = parse(&value()?)?,
+            "--top" => args.top = parse(&value()?)?,
+            _ if arg.starts_with('-') && arg != "-" => return Err(format!("unknown option {arg}")),
+            _ if args.file.is_none() => args.file = Some(arg),
+            _ => return Err(format!("unexpected argument {arg}")),
+        }
+    }
+
+    Ok(args)
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid value {value}"))
+}
+
+fn read_input(file: Option<&str>) -> Result<String, String> {
+    match file {
+        Some(path) if path != "-" => {
+            std::fs::read_to_string(path).map_err(|err| format!("cannot read {path}: {err}"))
+        }
+        _ => {
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|err| format!("cannot read stdin: {err}"))?;
+            Ok(input)
+        }
+    }
+}
+
+/// Parses a key and truncates it to `bits` like `as` would, so negative keys match the `IntKey`
+/// impls of the signed integers.
+fn parse_key(s: &str, bits: u32) -> Result<u128, String> {
+    let invalid = || format!("invalid key {s}");
+    let value = if let Some(hex) = s.strip_prefix("0x") {
+        u128::from_str_radix(hex, 16).map_err(|_| invalid())?
+    } else if s.starts_with('-') {
+        s.parse::<i128>().map_err(|_| invalid())? as u128
+    } else {
+        s.parse::<u128>().map_err(|_| invalid())?
+    };
+
+    if bits == 128 {
+        Ok(value)
+    } else if s.starts_with('-') || value >> bits == 0 {
+        Ok(value & ((1 << bits) - 1))
+    } else {
+        Err(format!("key {s} does not fit into {bits} bits"))
+    }
+}
+
+fn exit(err: &str) -> ! {
+    eprintln!("error: {err}");
+    process::exit(1);
+}
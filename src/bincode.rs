@@ -0,0 +1,133 @@
+use crate::{IntKey, IntMap};
+use bincode::{
+    de::{BorrowDecoder, Decoder},
+    enc::Encoder,
+    error::{DecodeError, EncodeError},
+    BorrowDecode, Decode, Encode,
+};
+
+impl<K, V> Encode for IntMap<K, V>
+where
+    K: IntKey + Ord + Encode,
+    V: Encode,
+{
+    /// Writes the pairs sorted by key, which is the same encoding as for a `BTreeMap<K, V>`.
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        let mut pairs: Vec<(K, &V)> = self.iter().collect();
+        pairs.sort_unstable_by_key(|(k, _)| *k);
+
+        (self.len() as u64).encode(encoder)?;
+        for (k, v) in pairs {
+            k.encode(encoder)?;
+            v.encode(encoder)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<Context, K, V> Decode<Context> for IntMap<K, V>
+where
+    K: IntKey + Ord + Decode<Context>,
+    V: Decode<Context>,
+{
+    /// Reads pairs that were encoded in ascending key order.
+    ///
+    /// Only the limit of the bincode configuration applies. Use
+    /// [`IntMap::bincode_decode_bounded`] for untrusted input.
+    fn decode<D: Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, DecodeError> {
+        Self::bincode_decode_bounded(decoder, usize::MAX)
+    }
+}
+
+impl<'de, Context, K, V> BorrowDecode<'de, Context> for IntMap<K, V>
+where
+    K: IntKey + Ord + BorrowDecode<'de, Context>,
+    V: BorrowDecode<'de, Context>,
+{
+    fn borrow_decode<D: BorrowDecoder<'de, Context = Context>>(
+        decoder: &mut D,
+    ) -> Result<Self, DecodeError> {
+        let len = decode_len::<D, K, V>(decoder, usize::MAX)?;
+        decode_pairs(decoder, len, K::borrow_decode, V::borrow_decode)
+    }
+}
+
+impl<K: IntKey + Ord, V> IntMap<K, V> {
+    /// Decodes a bincode encoded [`IntMap`] that contains at most `max_len` pairs.
+    ///
+    /// The length prefix is checked before anything is allocated. Fails with
+    /// [`DecodeError::LimitExceeded`] if the prefix exceeds `max_len` and with
+    /// [`DecodeError::Other`] if the keys are not in strictly ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bincode::{config, de::DecoderImpl, de::read::SliceReader};
+    /// use intmap::IntMap;
+    ///
+    /// let map: IntMap<u64, u8> = (0..10).map(|i| (i, i as u8)).collect();
+    /// let bytes = bincode::encode_to_vec(&map, config::standard()).unwrap();
+    ///
+    /// let mut decoder = DecoderImpl::new(SliceReader::new(&bytes), config::standard(), ());
+    /// let copy = IntMap::<u64, u8>::bincode_decode_bounded(&mut decoder, 10).unwrap();
+    /// assert_eq!(map, copy);
+    ///
+    /// let mut decoder = DecoderImpl::new(SliceReader::new(&bytes), config::standard(), ());
+    /// assert!(IntMap::<u64, u8>::bincode_decode_bounded(&mut decoder, 9).is_err());
+    /// ```
+    pub fn bincode_decode_bounded<D>(decoder: &mut D, max_len: usize) -> Result<Self, DecodeError>
+    where
+        D: Decoder,
+        K: Decode<D::Context>,
+        V: Decode<D::Context>,
+    {
+        let len = decode_len::<D, K, V>(decoder, max_len)?;
+        decode_pairs(decoder, len, K::decode, V::decode)
+    }
+}
+
+fn decode_len<D: Decoder, K, V>(decoder: &mut D, max_len: usize) -> Result<usize, DecodeError> {
+    let len = u64::decode(decoder)?;
+    let len = usize::try_from(len).map_err(|_| DecodeError::OutsideUsizeRange(len))?;
+    if len > max_len {
+        return Err(DecodeError::LimitExceeded);
+    }
+
+    decoder.claim_container_read::<(K, V)>(len)?;
+
+    Ok(len)
+}
+
+fn decode_pairs<D, K, V>(
+    decoder: &mut D,
+    len: usize,
+    decode_key: fn(&mut D) -> Result<K, DecodeError>,
+    decode_value: fn(&mut D) -> Result<V, DecodeError>,
+) -> Result<IntMap<K, V>, DecodeError>
+where
+    D: Decoder,
+    K: IntKey + Ord,
+{
+    let mut map = IntMap::with_capacity(len.min(crate::MAX_PREALLOCATION));
+    let mut prev: Option<K> = None;
+
+    for _ in 0..len {
+        // Each pair was claimed by `decode_len`, the actual reads will claim them again.
+        decoder.unclaim_bytes_read(std::mem::size_of::<(K, V)>());
+
+        let key = decode_key(decoder)?;
+        let value = decode_value(decoder)?;
+
+        if prev.map_or(false, |prev| prev >= key) {
+            return Err(DecodeError::Other(
+                "IntMap keys were not encoded in ascending order",
+            ));
+        }
+        prev = Some(key);
+
+        map.insert(key, value);
+    }
+
+    Ok(map)
+}
@@ -0,0 +1,96 @@
+use crate::{IntKey, IntMap};
+use borsh::{
+    io::{Error, ErrorKind, Read, Result, Write},
+    BorshDeserialize, BorshSerialize,
+};
+
+impl<K, V> BorshSerialize for IntMap<K, V>
+where
+    K: IntKey + Ord + BorshSerialize,
+    V: BorshSerialize,
+{
+    /// Writes the pairs sorted by key, which is the same encoding as for a `BTreeMap<K, V>`.
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let len = u32::try_from(self.len())
+            .map_err(|_| Error::new(ErrorKind::InvalidData, "IntMap is too large to serialize"))?;
+
+        let mut pairs: Vec<(K, &V)> = self.iter().collect();
+        pairs.sort_unstable_by_key(|(k, _)| *k);
+
+        len.serialize(writer)?;
+        for (k, v) in pairs {
+            k.serialize(writer)?;
+            v.serialize(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<K, V> BorshDeserialize for IntMap<K, V>
+where
+    K: IntKey + Ord + BorshDeserialize,
+    V: BorshDeserialize,
+{
+    /// Reads pairs that were serialized in ascending key order.
+    ///
+    /// Only the length prefix limit of borsh itself applies. Use
+    /// [`IntMap::borsh_deserialize_bounded`] for untrusted input.
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        Self::borsh_deserialize_bounded(reader, usize::MAX)
+    }
+}
+
+impl<K, V> IntMap<K, V>
+where
+    K: IntKey + Ord + BorshDeserialize,
+    V: BorshDeserialize,
+{
+    /// Deserializes a borsh encoded [`IntMap`] that contains at most `max_len` pairs.
+    ///
+    /// The length prefix is checked before anything is allocated. Fails with
+    /// [`ErrorKind::InvalidData`] if the prefix exceeds `max_len` or if the keys are not in
+    /// strictly ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntMap;
+    ///
+    /// let map: IntMap<u64, u8> = (0..10).map(|i| (i, i as u8)).collect();
+    /// let bytes = borsh::to_vec(&map).unwrap();
+    ///
+    /// let copy = IntMap::<u64, u8>::borsh_deserialize_bounded(&mut &bytes[..], 10).unwrap();
+    /// assert_eq!(map, copy);
+    /// assert!(IntMap::<u64, u8>::borsh_deserialize_bounded(&mut &bytes[..], 9).is_err());
+    /// ```
+    pub fn borsh_deserialize_bounded<R: Read>(reader: &mut R, max_len: usize) -> Result<Self> {
+        let len = u32::deserialize_reader(reader)? as usize;
+        if len > max_len {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "IntMap length prefix exceeds the maximum",
+            ));
+        }
+
+        let mut map = IntMap::with_capacity(len.min(crate::MAX_PREALLOCATION));
+        let mut prev: Option<K> = None;
+
+        for _ in 0..len {
+            let key = K::deserialize_reader(reader)?;
+            let value = V::deserialize_reader(reader)?;
+
+            if prev.map_or(false, |prev| prev >= key) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "IntMap keys were not serialized in ascending order",
+                ));
+            }
+            prev = Some(key);
+
+            map.insert(key, value);
+        }
+
+        Ok(map)
+    }
+}
@@ -0,0 +1,466 @@
+// ***************** Builder *********************
+
+use std::fmt;
+
+use crate::{int::SealedInt, IntKey, IntMap, IntSoaMap};
+
+/// A builder for a [`IntMap`] with a validated configuration.
+///
+/// All settings are optional and default to the behavior of [`IntMap::new`]. The settings are
+/// checked together by [`IntMapBuilder::build`], which returns a [`ConfigError`] instead of
+/// creating a map that would misbehave, e.g. grow forever because of a load factor of 0.
+/// [`IntMapBuilder::build_soa`] creates an [`IntSoaMap`] with the same configuration.
+///
+/// # Examples
+///
+/// ```
+/// use intmap::{ConfigError, IndexStrategy, IntMap, IntMapBuilder, ShrinkPolicy, TableSizing};
+///
+/// let map: IntMap<u64, u64> = IntMapBuilder::new()
+///     .capacity(1_000)
+///     .load_factor(0.5)
+///     .shrink_policy(ShrinkPolicy::BelowLoadFactor(0.1))
+///     .index_strategy(IndexStrategy::MultiplyShift)
+///     .seed(42)
+///     .table_sizing(TableSizing::OneAndAHalf)
+///     .build()
+///     .unwrap();
+/// assert!(map.capacity() >= 2_000);
+///
+/// let err = IntMapBuilder::new().load_factor(f32::NAN).build::<u64, u64>();
+/// assert!(matches!(err, Err(ConfigError::InvalidLoadFactor { .. })));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IntMapBuilder {
+    capacity: usize,
+    load_factor: f32,
+    shrink_policy: ShrinkPolicy,
+    index_strategy: IndexStrategy,
+    seed: Option<u64>,
+    table_sizing: TableSizing,
+}
+
+impl IntMapBuilder {
+    /// Creates a builder with the default configuration.
+    pub const fn new() -> Self {
+        Self {
+            capacity: 0,
+            load_factor: 0.909,
+            shrink_policy: ShrinkPolicy::Never,
+            index_strategy: IndexStrategy::Prime,
+            seed: None,
+            table_sizing: TableSizing::PowerOfTwo,
+        }
+    }
+
+    /// Sets the number of key/value pairs the map can hold without growing.
+    ///
+    /// Unlike [`IntMap::with_capacity`], the load factor is taken into account, so inserting
+    /// `capacity` pairs doesn't resize the map.
+    pub const fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sets the load factor, see [`IntMap::set_load_factor`].
+    pub const fn load_factor(mut self, load_factor: f32) -> Self {
+        self.load_factor = load_factor;
+        self
+    }
+
+    /// Sets when the map releases slots after pairs were removed.
+    pub const fn shrink_policy(mut self, shrink_policy: ShrinkPolicy) -> Self {
+        self.shrink_policy = shrink_policy;
+        self
+    }
+
+    /// Sets how keys are mapped to slots.
+    pub const fn index_strategy(mut self, index_strategy: IndexStrategy) -> Self {
+        self.index_strategy = index_strategy;
+        self
+    }
+
+    /// Sets the seed for the multiplier of [`IndexStrategy::MultiplyShift`].
+    ///
+    /// Maps with different seeds put colliding keys into different slots, which makes it harder
+    /// to provoke long chains with chosen keys.
+    pub const fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets how the number of slots grows.
+    pub const fn table_sizing(mut self, table_sizing: TableSizing) -> Self {
+        self.table_sizing = table_sizing;
+        self
+    }
+
+    /// Validates the configuration and creates the map.
+    pub fn build<K: IntKey, V>(&self) -> Result<IntMap<K, V>, ConfigError> {
+        let load_factor = load_factor_to_int(self.load_factor)?;
+        let shrink_load_factor = shrink_load_factor_to_int(self.shrink_policy, load_factor)?;
+        let hashing = self.hashing::<K>()?;
+
+        let slots = slots_for(
+            self.capacity,
+            load_factor,
+            std::mem::size_of::<Vec<(K, V)>>(),
+        )?;
+        let size = table_size(hashing, slots);
+
+        let mut map = IntMap::new();
+        map.load_factor = load_factor;
+        map.shrink_load_factor = shrink_load_factor;
+        map.hashing = hashing;
+        if size > 0 {
+            map.resize_cache(size);
+        }
+
+        map.debug_validate();
+        Ok(map)
+    }
+
+    /// Validates the configuration and creates an [`IntSoaMap`], which stores the keys and
+    /// values in separate arrays.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::{IndexStrategy, IntMapBuilder, IntSoaMap};
+    ///
+    /// let mut map: IntSoaMap<u64, [u8; 64]> = IntMapBuilder::new()
+    ///     .capacity(100)
+    ///     .load_factor(0.5)
+    ///     .index_strategy(IndexStrategy::MultiplyShift)
+    ///     .build_soa()
+    ///     .unwrap();
+    /// assert!(map.capacity() >= 200);
+    ///
+    /// map.insert(1 << 40, [1; 64]);
+    /// assert!(map.contains_key(1 << 40));
+    /// ```
+    pub fn build_soa<K: IntKey, V>(&self) -> Result<IntSoaMap<K, V>, ConfigError> {
+        let load_factor = load_factor_to_int(self.load_factor)?;
+        let shrink_load_factor = shrink_load_factor_to_int(self.shrink_policy, load_factor)?;
+        let hashing = self.hashing::<K>()?;
+
+        // The positions of the pairs are stored as `u32`, see `IntSoaMap`.
+        if self.capacity >= u32::MAX as usize {
+            return Err(ConfigError::CapacityOverflow {
+                capacity: self.capacity,
+            });
+        }
+        let slots = slots_for(self.capacity, load_factor, std::mem::size_of::<u32>())?;
+        let size = table_size(hashing, slots);
+
+        let mut map = IntSoaMap::new();
+        map.load_factor = load_factor;
+        map.shrink_load_factor = shrink_load_factor;
+        map.hashing = hashing;
+        if size > 0 {
+            map.resize(size);
+        }
+        map.reserve(self.capacity);
+        Ok(map)
+    }
+
+    // Returns how the slots of a map with keys of type `K` are computed.
+    fn hashing<K: IntKey>(&self) -> Result<Hashing, ConfigError> {
+        let hashing = match (self.index_strategy, self.seed, self.table_sizing) {
+            (IndexStrategy::Prime, Some(_), _) => {
+                return Err(ConfigError::SeedWithoutMultiplyShift)
+            }
+            (IndexStrategy::Prime, None, TableSizing::PowerOfTwo) => Hashing::Prime,
+            (IndexStrategy::Prime, None, TableSizing::OneAndAHalf) => {
+                return Err(ConfigError::TableSizingWithoutMultiplyShift)
+            }
+            (IndexStrategy::MultiplyShift, seed, TableSizing::PowerOfTwo) => {
+                Hashing::MultiplyShift(Multiplier::new(multiplier(seed, K::Int::BITS)))
+            }
+            (IndexStrategy::MultiplyShift, seed, TableSizing::OneAndAHalf) => {
+                Hashing::MultiplyRange(Multiplier::new(multiplier(seed, K::Int::BITS)))
+            }
+        };
+        Ok(hashing)
+    }
+}
+
+impl Default for IntMapBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// When a [`IntMap`] releases slots after key/value pairs were removed.
+///
+/// Shrinking is checked after [`IntMap::remove`], the retain methods and the set operations
+/// that remove keys. [`IntMap::clear`] and [`IntMap::drain`] always keep the slots.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShrinkPolicy {
+    /// The map never shrinks by itself, see [`IntMap::shrink_to_fit`]. This is the default.
+    Never,
+    /// The map steps down one table size at a time while the ratio between pairs and slots is
+    /// below the given load factor. That halves the slots with [`TableSizing::PowerOfTwo`] and
+    /// takes about two thirds of them with [`TableSizing::OneAndAHalf`].
+    ///
+    /// Must be at most half the load factor of the map, otherwise the map would grow again right
+    /// after shrinking.
+    BelowLoadFactor(f32),
+}
+
+impl Default for ShrinkPolicy {
+    fn default() -> Self {
+        ShrinkPolicy::Never
+    }
+}
+
+/// How a [`IntMap`] maps keys to slots.
+///
+/// Not to be confused with [`tune::HashStrategy`](crate::tune::HashStrategy), which describes
+/// an [`IntKey::into_int`] implementation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndexStrategy {
+    /// Multiplies the key with [`IntKey::PRIME`] and uses the lowest bits. This is the default.
+    ///
+    /// It's the fastest strategy and spreads sequential keys perfectly, but keys that only differ
+    /// in their high bits collide.
+    Prime,
+    /// Multiplies the key with an odd constant and uses the highest bits, so all bits of the key
+    /// affect the slot.
+    ///
+    /// The constant is derived from the golden ratio, or from the seed if one is set.
+    MultiplyShift,
+}
+
+impl Default for IndexStrategy {
+    fn default() -> Self {
+        IndexStrategy::Prime
+    }
+}
+
+/// How the number of slots of a [`IntMap`] grows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableSizing {
+    /// The number of slots is a power of two and doubles on growth. This is the default.
+    ///
+    /// Slots are computed with a bit mask, but right after growing only half of the slots are
+    /// needed for the load factor.
+    PowerOfTwo,
+    /// The number of slots grows by a factor of 1.5, i.e. 2, 3, 4, 6, 9, 13, ...
+    ///
+    /// This wastes less memory for large maps. Slots are computed with Lemire's multiply-shift
+    /// range reduction, which uses the highest bits of the hash, so this requires
+    /// [`IndexStrategy::MultiplyShift`].
+    OneAndAHalf,
+}
+
+impl Default for TableSizing {
+    fn default() -> Self {
+        TableSizing::PowerOfTwo
+    }
+}
+
+/// An invalid configuration of a [`IntMap`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ConfigError {
+    /// The load factor is NaN, infinite or below 0.001.
+    InvalidLoadFactor {
+        /// The rejected load factor.
+        load_factor: f32,
+    },
+    /// The load factor of [`ShrinkPolicy::BelowLoadFactor`] is NaN, infinite, not positive or
+    /// more than half the load factor of the map.
+    InvalidShrinkLoadFactor {
+        /// The rejected load factor of the shrink policy.
+        shrink_load_factor: f32,
+        /// The load factor of the map.
+        load_factor: f32,
+    },
+    /// The number of slots for the capacity doesn't fit into memory, or the capacity exceeds the
+    /// `u32::MAX - 1` pairs of an [`IntSoaMap`].
+    CapacityOverflow {
+        /// The requested capacity.
+        capacity: usize,
+    },
+    /// A seed was set, but only [`IndexStrategy::MultiplyShift`] uses one.
+    SeedWithoutMultiplyShift,
+    /// [`TableSizing::OneAndAHalf`] was set without [`IndexStrategy::MultiplyShift`].
+    TableSizingWithoutMultiplyShift,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::InvalidLoadFactor { load_factor } => {
+                write!(f, "load factor {load_factor} is not a number of at least 0.001")
+            }
+            Self::InvalidShrinkLoadFactor {
+                shrink_load_factor,
+                load_factor,
+            } => write!(
+                f,
+                "shrink load factor {shrink_load_factor} is not a positive number of at most half the load factor {load_factor}"
+            ),
+            Self::CapacityOverflow { capacity } => {
+                write!(f, "capacity {capacity} needs too many slots")
+            }
+            Self::SeedWithoutMultiplyShift => {
+                write!(f, "a seed requires the multiply-shift index strategy")
+            }
+            Self::TableSizingWithoutMultiplyShift => {
+                write!(
+                    f,
+                    "growing by a factor of 1.5 requires the multiply-shift index strategy"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// How a map computes the slot of a key and the number of slots, see `IndexStrategy` and
+// `TableSizing`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Hashing {
+    Prime,
+    // Contains the multiplier, truncated to the width of the key.
+    MultiplyShift(Multiplier),
+    // Like `MultiplyShift`, but for a number of slots that isn't a power of two.
+    MultiplyRange(Multiplier),
+}
+
+// A `u128` stored as two halves, so `Hashing` doesn't force a 16 byte alignment onto `IntMap`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Multiplier([u64; 2]);
+
+impl Multiplier {
+    fn new(value: u128) -> Self {
+        Self([(value >> 64) as u64, value as u64])
+    }
+
+    fn get(self) -> u128 {
+        (u128::from(self.0[0]) << 64) | u128::from(self.0[1])
+    }
+}
+
+impl Hashing {
+    // Returns the number of slots after growing `size` times from zero.
+    pub(crate) fn table_len(self, size: u32) -> usize {
+        match (self, size) {
+            (_, 0) => 0,
+            (Hashing::MultiplyRange(_), _) => (1..size).fold(2, |len, _| len + len / 2),
+            _ => 2usize.pow(size),
+        }
+    }
+
+    // Returns the slot of `k` in a cache with `table_len(size)` slots.
+    //
+    // `mod_mask` is the number of slots minus one, which is a bit mask for powers of two.
+    #[inline(always)]
+    pub(crate) fn calc_index<I: SealedInt>(
+        self,
+        k: I,
+        prime: I,
+        size: u32,
+        mod_mask: usize,
+    ) -> usize {
+        match self {
+            Hashing::Prime => k.calc_index(mod_mask, prime),
+            Hashing::MultiplyShift(multiplier) => {
+                cold();
+                k.calc_index_shift(size, I::from_u128(multiplier.get()))
+            }
+            Hashing::MultiplyRange(multiplier) => {
+                cold();
+                k.calc_index_range(mod_mask + 1, I::from_u128(multiplier.get()))
+            }
+        }
+    }
+}
+
+// Marks the calling branch as unlikely, so the code of the default `Prime` strategy is laid out
+// without jumps. Inlined away, unlike an out of line `#[cold]` function.
+#[cold]
+#[inline]
+fn cold() {}
+
+// Converts the load factor to the value stored in `IntMap::load_factor`.
+pub(crate) fn load_factor_to_int(load_factor: f32) -> Result<usize, ConfigError> {
+    let int = load_factor * 1000.;
+    // Also rejects NaN. A load factor of 0 would make the map grow forever.
+    if load_factor.is_finite() && int >= 1. {
+        Ok(int as usize)
+    } else {
+        Err(ConfigError::InvalidLoadFactor { load_factor })
+    }
+}
+
+// Converts the shrink policy to the value stored in `IntMap::shrink_load_factor`.
+pub(crate) fn shrink_load_factor_to_int(
+    policy: ShrinkPolicy,
+    load_factor: usize,
+) -> Result<usize, ConfigError> {
+    let shrink_load_factor = match policy {
+        ShrinkPolicy::Never => return Ok(0),
+        ShrinkPolicy::BelowLoadFactor(shrink_load_factor) => shrink_load_factor,
+    };
+
+    let int = shrink_load_factor * 1000.;
+    // Halving the slots doubles the load, which must not exceed the load factor.
+    if shrink_load_factor.is_finite() && int >= 1. && (int as usize) * 2 <= load_factor {
+        Ok(int as usize)
+    } else {
+        Err(ConfigError::InvalidShrinkLoadFactor {
+            shrink_load_factor,
+            load_factor: load_factor as f32 / 1000.,
+        })
+    }
+}
+
+// Returns the minimum number of slots for `capacity` pairs, each slot taking `slot_size` bytes.
+fn slots_for(capacity: usize, load_factor: usize, slot_size: usize) -> Result<usize, ConfigError> {
+    let slots = capacity
+        .checked_mul(1000)
+        .and_then(|c| c.checked_add(load_factor - 1))
+        .map(|c| c / load_factor);
+
+    // Leaves room for growing to the next table length.
+    let max_slots = isize::MAX as usize / slot_size / 2;
+    match slots {
+        Some(slots) if slots <= max_slots => Ok(slots),
+        _ => Err(ConfigError::CapacityOverflow { capacity }),
+    }
+}
+
+// Returns the number of times the slots must grow from zero to reach `slots` slots.
+fn table_size(hashing: Hashing, slots: usize) -> u32 {
+    let mut size = 0;
+    while hashing.table_len(size) < slots {
+        size += 1;
+    }
+    size
+}
+
+// Returns an odd multiplier with the given width for `Hashing::MultiplyShift` and
+// `Hashing::MultiplyRange`.
+fn multiplier(seed: Option<u64>, bits: u32) -> u128 {
+    // 2^128 divided by the golden ratio.
+    const GOLDEN: u128 = 0x9E37_79B9_7F4A_7C15_F39C_C060_5CED_C835;
+
+    let base = match seed {
+        Some(seed) => (u128::from(splitmix64(seed)) << 64) | u128::from(splitmix64(!seed)),
+        None => GOLDEN,
+    };
+
+    // The high bits of the golden ratio constant are the constant for smaller widths.
+    (base >> (128 - bits)) | 1
+}
+
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,290 @@
+use std::cmp::Reverse;
+use std::iter::FromIterator;
+
+use crate::{IntKey, IntMap, Iter, Keys};
+
+/// Counts the occurrences of integer based keys.
+///
+/// Keys with a count of zero are not stored.
+///
+/// # Examples
+///
+/// ```
+/// use intmap::IntCounter;
+///
+/// let mut counter: IntCounter<u64> = [1, 2, 2, 3, 3, 3].into_iter().collect();
+/// counter.add(4);
+/// counter.subtract(1, 1);
+///
+/// assert_eq!(counter.get(3), 3);
+/// assert_eq!(counter.get(1), 0);
+/// assert_eq!(counter.total(), 6);
+/// assert_eq!(counter.most_common(2), [(3, 3), (2, 2)]);
+/// ```
+#[derive(Clone)]
+pub struct IntCounter<K> {
+    counts: IntMap<K, u64>,
+    // The sum of all counts.
+    total: u64,
+}
+
+impl<K> IntCounter<K> {
+    /// Creates a new [`IntCounter`].
+    ///
+    /// The [`IntCounter`] is initially created with a capacity of 0, so it will not allocate
+    /// until it is first inserted into.
+    pub const fn new() -> Self {
+        Self {
+            counts: IntMap::new(),
+            total: 0,
+        }
+    }
+}
+
+impl<K: IntKey> IntCounter<K> {
+    /// Creates a new [`IntCounter`] with space for at least the given number of keys.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            counts: IntMap::with_capacity(capacity),
+            total: 0,
+        }
+    }
+
+    /// Increments the count of the key by one and returns the new count.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the total would overflow a `u64`, see [`IntCounter::add_n`].
+    pub fn add(&mut self, key: K) -> u64 {
+        self.add_n(key, 1)
+    }
+
+    /// Increments the count of the key by `n` and returns the new count.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the total would overflow a `u64`, in debug and release builds alike. The counter
+    /// is left unchanged in that case. No count can overflow before the total does.
+    pub fn add_n(&mut self, key: K, n: u64) -> u64 {
+        if n == 0 {
+            return self.get(key);
+        }
+
+        self.total = self
+            .total
+            .checked_add(n)
+            .expect("IntCounter total overflowed u64");
+        let count = self.counts.entry(key).or_insert(0);
+        *count += n;
+        *count
+    }
+
+    /// Decrements the count of the key by `n` and returns the new count.
+    ///
+    /// The count doesn't go below zero, and the key is removed once its count reaches zero.
+    pub fn subtract(&mut self, key: K, n: u64) -> u64 {
+        let count = match self.counts.get_mut(key) {
+            Some(count) => count,
+            None => return 0,
+        };
+
+        if *count > n {
+            *count -= n;
+            self.total -= n;
+            *count
+        } else {
+            self.total -= *count;
+            self.counts.remove(key);
+            0
+        }
+    }
+
+    /// Returns the count of the key, which is zero for absent keys.
+    pub fn get(&self, key: K) -> u64 {
+        self.counts.get(key).copied().unwrap_or(0)
+    }
+
+    /// Removes the key and returns its count.
+    pub fn remove(&mut self, key: K) -> u64 {
+        let count = self.counts.remove(key).unwrap_or(0);
+        self.total -= count;
+        count
+    }
+
+    /// Returns true if the key has a count above zero.
+    pub fn contains_key(&self, key: K) -> bool {
+        self.counts.contains_key(key)
+    }
+
+    /// Returns the sum of all counts.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Returns the number of keys with a count above zero.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Returns true if no key has a count above zero.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Removes all keys, but keeps the allocated memory for reuse.
+    pub fn clear(&mut self) {
+        self.counts.clear();
+        self.total = 0;
+    }
+
+    /// Returns the `n` keys with the highest counts, ordered from the highest to the lowest count.
+    ///
+    /// The order of keys with equal counts is unspecified. Only the returned keys are sorted, so
+    /// this is faster than sorting all keys if `n` is small.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntCounter;
+    ///
+    /// let counter: IntCounter<u8> = b"mississippi".iter().copied().collect();
+    /// let top = counter.most_common(3);
+    /// assert_eq!(top[0].1, 4);
+    /// assert_eq!(top[1].1, 4);
+    /// assert_eq!(top[2], (b'p', 2));
+    /// ```
+    pub fn most_common(&self, n: usize) -> Vec<(K, u64)> {
+        let mut counts: Vec<(K, u64)> = self.iter().map(|(k, &c)| (k, c)).collect();
+
+        if n < counts.len() {
+            if n == 0 {
+                return Vec::new();
+            }
+            counts.select_nth_unstable_by_key(n - 1, |&(_, c)| Reverse(c));
+            counts.truncate(n);
+        }
+
+        counts.sort_unstable_by_key(|&(_, c)| Reverse(c));
+        counts
+    }
+
+    /// Adds the counts of `other` to the counts of `self`.
+    ///
+    /// Panics like [`IntCounter::add_n`] if the total would overflow a `u64`. The counts merged
+    /// before that are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntCounter;
+    ///
+    /// let mut a: IntCounter<u64> = [1, 2].into_iter().collect();
+    /// let b: IntCounter<u64> = [2, 3].into_iter().collect();
+    /// a.merge(&b);
+    /// assert_eq!(a.get(2), 2);
+    /// assert_eq!(a.total(), 4);
+    /// ```
+    pub fn merge(&mut self, other: &IntCounter<K>) {
+        self.counts.reserve(other.len());
+        for (key, &count) in other.iter() {
+            self.add_n(key, count);
+        }
+    }
+
+    /// Returns an iterator over the keys and their counts.
+    pub fn iter(&self) -> Iter<'_, K, u64> {
+        self.counts.iter()
+    }
+
+    /// Returns an iterator over the keys.
+    pub fn keys(&self) -> Keys<'_, K, u64> {
+        self.counts.keys()
+    }
+
+    /// Returns the underlying [`IntMap`] that maps the keys to their counts.
+    pub fn as_map(&self) -> &IntMap<K, u64> {
+        &self.counts
+    }
+
+    /// Converts the counter into the underlying [`IntMap`] that maps the keys to their counts.
+    pub fn into_map(self) -> IntMap<K, u64> {
+        self.counts
+    }
+}
+
+impl<K> Default for IntCounter<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ***************** Equality *********************
+
+impl<K: IntKey> PartialEq for IntCounter<K> {
+    fn eq(&self, other: &IntCounter<K>) -> bool {
+        self.total == other.total && self.counts == other.counts
+    }
+}
+impl<K: IntKey> Eq for IntCounter<K> {}
+
+// ***************** Debug *********************
+
+impl<K> std::fmt::Debug for IntCounter<K>
+where
+    K: IntKey + std::fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.counts.fmt(fmt)
+    }
+}
+
+// ***************** Iter *********************
+
+impl<'a, K: IntKey> IntoIterator for &'a IntCounter<K> {
+    type Item = (K, &'a u64);
+    type IntoIter = Iter<'a, K, u64>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// ***************** Extend *********************
+
+impl<K: IntKey> Extend<K> for IntCounter<K> {
+    #[inline]
+    fn extend<T: IntoIterator<Item = K>>(&mut self, iter: T) {
+        for key in iter {
+            self.add(key);
+        }
+    }
+}
+
+impl<K: IntKey> Extend<(K, u64)> for IntCounter<K> {
+    #[inline]
+    fn extend<T: IntoIterator<Item = (K, u64)>>(&mut self, iter: T) {
+        for (key, n) in iter {
+            self.add_n(key, n);
+        }
+    }
+}
+
+// ***************** FromIterator *********************
+
+impl<K: IntKey> FromIterator<K> for IntCounter<K> {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = K>>(iter: T) -> Self {
+        let mut counter = IntCounter::new();
+        counter.extend(iter);
+        counter
+    }
+}
+
+impl<K: IntKey> FromIterator<(K, u64)> for IntCounter<K> {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = (K, u64)>>(iter: T) -> Self {
+        let mut counter = IntCounter::new();
+        counter.extend(iter);
+        counter
+    }
+}
@@ -0,0 +1,593 @@
+//! A copy-on-write map for integer based keys with cheap snapshots, see [`CowIntMap`].
+
+use std::iter::FromIterator;
+use std::sync::Arc;
+
+use crate::{builder::Hashing, IntKey, IntMap};
+
+type Bucket<K, V> = Arc<Vec<(K, V)>>;
+
+/// A hashmap that maps an integer based `K` to `V` and supports cheap read-only snapshots.
+///
+/// It uses the same hashing as [`IntMap`], but the buckets are reference counted. Taking a
+/// [`Snapshot`] is O(1) and shares all buckets. The first mutation after a snapshot copies the
+/// list of bucket pointers, and every mutation copies only the bucket it touches if that bucket
+/// is still shared with a snapshot. This suits read-mostly maps like configurations that are read
+/// by many threads while a writer keeps updating them.
+///
+/// Mutations need `V: Clone` for copying shared buckets.
+///
+/// A map converted from an [`IntMap`] keeps its load factor and index strategy, but it's only
+/// shrunk again once it's converted back.
+///
+/// # Examples
+///
+/// ```
+/// use intmap::CowIntMap;
+///
+/// let mut config: CowIntMap<u32, &str> = CowIntMap::new();
+/// config.insert(1, "a");
+/// config.insert(2, "b");
+///
+/// let snapshot = config.snapshot();
+/// config.insert(1, "c");
+/// config.remove(2);
+///
+/// assert_eq!(snapshot.get(1), Some(&"a"));
+/// assert_eq!(snapshot.get(2), Some(&"b"));
+/// assert_eq!(config.get(1), Some(&"c"));
+/// assert_eq!(config.get(2), None);
+/// ```
+pub struct CowIntMap<K, V> {
+    table: Table<K, V>,
+    // The load factors of the `IntMap` the map is converted from and to when resizing.
+    load_factor: usize,
+    shrink_load_factor: usize,
+}
+
+/// A read-only view of a map at the time [`CowIntMap::snapshot`] or [`IntMap::snapshot`] was
+/// called.
+///
+/// Later mutations of the map are not visible. Cloning a snapshot is O(1).
+pub struct Snapshot<K, V> {
+    table: Table<K, V>,
+}
+
+// The part of the map that is shared with snapshots.
+//
+// The fields mirror the ones of `IntMap`, so the buckets are laid out exactly like in an `IntMap`
+// and conversions don't need to rehash.
+struct Table<K, V> {
+    cache: Arc<Vec<Bucket<K, V>>>,
+    size: u32,
+    mod_mask: usize,
+    count: usize,
+    hashing: Hashing,
+}
+
+// Cloning a table shares its buckets with the clone, so unlike derived impls these work for any
+// `K` and `V`.
+impl<K, V> Clone for Table<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            cache: Arc::clone(&self.cache),
+            size: self.size,
+            mod_mask: self.mod_mask,
+            count: self.count,
+            hashing: self.hashing,
+        }
+    }
+}
+
+impl<K, V> Clone for Snapshot<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            table: self.table.clone(),
+        }
+    }
+}
+
+impl<K, V> Clone for CowIntMap<K, V> {
+    /// Returns a map that shares all buckets with `self`, like [`CowIntMap::snapshot`].
+    fn clone(&self) -> Self {
+        Self {
+            table: self.table.clone(),
+            load_factor: self.load_factor,
+            shrink_load_factor: self.shrink_load_factor,
+        }
+    }
+}
+
+impl<K, V> Table<K, V> {
+    fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            buckets: self.cache.iter(),
+            bucket: [].iter(),
+            remaining: self.count,
+        }
+    }
+}
+
+impl<K: IntKey, V> Table<K, V> {
+    fn get(&self, key: K) -> Option<&V> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let k = key.into_int();
+        self.cache[self.calc_index(k)]
+            .iter()
+            .find_map(|kv| (kv.0.into_int() == k).then(|| &kv.1))
+    }
+
+    #[inline(always)]
+    fn calc_index(&self, k: K::Int) -> usize {
+        self.hashing
+            .calc_index(k, K::PRIME, self.size, self.mod_mask)
+    }
+}
+
+impl<K, V> CowIntMap<K, V> {
+    /// Creates a new [`CowIntMap`].
+    ///
+    /// The [`CowIntMap`] is initially created with a capacity of 0, so it will not allocate
+    /// buckets until it is first inserted into.
+    pub fn new() -> Self {
+        Self::from_int_map(IntMap::new())
+    }
+
+    // Shares the buckets of the map without rehashing.
+    fn from_int_map(map: IntMap<K, V>) -> Self {
+        Self {
+            table: Table {
+                cache: Arc::new(map.cache.into_iter().map(Arc::new).collect()),
+                size: map.size,
+                mod_mask: map.mod_mask,
+                count: map.count,
+                hashing: map.hashing,
+            },
+            load_factor: map.load_factor,
+            shrink_load_factor: map.shrink_load_factor,
+        }
+    }
+
+    /// Returns a read-only snapshot of the current contents in O(1).
+    pub fn snapshot(&self) -> Snapshot<K, V> {
+        Snapshot {
+            table: self.table.clone(),
+        }
+    }
+
+    /// Returns the number of key/value pairs.
+    pub fn len(&self) -> usize {
+        self.table.count
+    }
+
+    /// Returns true if the map contains no key/value pairs.
+    pub fn is_empty(&self) -> bool {
+        self.table.count == 0
+    }
+
+    /// Returns the number of available slots.
+    pub fn capacity(&self) -> usize {
+        self.table.cache.len()
+    }
+
+    /// Returns an [`Iterator`] over all key/value pairs.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.table.iter()
+    }
+
+    /// Returns an [`Iterator`] over all keys.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.iter() }
+    }
+
+    /// Returns an [`Iterator`] over all values.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+}
+
+impl<K: IntKey, V> CowIntMap<K, V> {
+    /// Returns the value of the key.
+    pub fn get(&self, key: K) -> Option<&V> {
+        self.table.get(key)
+    }
+
+    /// Returns true if the key is present.
+    pub fn contains_key(&self, key: K) -> bool {
+        self.get(key).is_some()
+    }
+}
+
+impl<K: IntKey, V: Clone> CowIntMap<K, V> {
+    /// Creates a new [`CowIntMap`] with at least the given capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::from(IntMap::with_capacity(capacity))
+    }
+
+    /// Ensures that the map has space for at least `additional` more elements.
+    pub fn reserve(&mut self, additional: usize) {
+        if self.table.cache.len() < self.table.count + additional {
+            self.resize_with(|map| map.reserve(additional));
+        }
+    }
+
+    /// Inserts a key/value pair and returns the previous value if any.
+    ///
+    /// Copies the touched bucket if it's shared with a snapshot.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if crate::exceeds_load_factor(self.table.count, self.table.cache.len(), self.load_factor) {
+            self.resize_with(|map| {
+                map.increase_cache_if_needed();
+            });
+        }
+
+        let k = key.into_int();
+        let vals = self.bucket_mut(k);
+        let pos = vals.iter().position(|kv| kv.0.into_int() == k);
+
+        match pos {
+            Some(pos) => Some(std::mem::replace(&mut vals[pos].1, value)),
+            None => {
+                vals.push((key, value));
+                self.table.count += 1;
+                None
+            }
+        }
+    }
+
+    /// Returns the mutable value of the key.
+    ///
+    /// Copies the touched bucket if the key is present and the bucket is shared with a snapshot.
+    pub fn get_mut(&mut self, key: K) -> Option<&mut V> {
+        if !self.contains_key(key) {
+            return None;
+        }
+
+        let k = key.into_int();
+        self.bucket_mut(k)
+            .iter_mut()
+            .find_map(|kv| (kv.0.into_int() == k).then(move || &mut kv.1))
+    }
+
+    /// Removes the key and returns its value.
+    ///
+    /// Copies the touched bucket if the key is present and the bucket is shared with a snapshot.
+    pub fn remove(&mut self, key: K) -> Option<V> {
+        if !self.contains_key(key) {
+            return None;
+        }
+
+        let k = key.into_int();
+        let vals = self.bucket_mut(k);
+        let pos = vals.iter().position(|kv| kv.0.into_int() == k)?;
+        let value = vals.swap_remove(pos).1;
+        self.table.count -= 1;
+        Some(value)
+    }
+
+    /// Removes all key/value pairs.
+    ///
+    /// The buckets that are shared with snapshots are released instead of cleared.
+    pub fn clear(&mut self) {
+        for vals in Arc::make_mut(&mut self.table.cache) {
+            match Arc::get_mut(vals) {
+                Some(vals) => vals.clear(),
+                None => *vals = Arc::default(),
+            }
+        }
+        self.table.count = 0;
+    }
+
+    /// Retains only the key/value pairs specified by the predicate.
+    ///
+    /// Only the buckets that lose a pair are copied.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(K, &V) -> bool,
+    {
+        let count = &mut self.table.count;
+        for vals in Arc::make_mut(&mut self.table.cache) {
+            let mut ix = match vals.iter().position(|(k, v)| !f(*k, v)) {
+                Some(ix) => ix,
+                None => continue,
+            };
+
+            // The count is updated per pair, so it stays right if `f` panics.
+            let vals = Arc::make_mut(vals);
+            vals.swap_remove(ix);
+            *count -= 1;
+            while ix < vals.len() {
+                let (k, v) = &vals[ix];
+                if f(*k, v) {
+                    ix += 1;
+                } else {
+                    vals.swap_remove(ix);
+                    *count -= 1;
+                }
+            }
+        }
+    }
+
+    /// Converts the map into an [`IntMap`], cloning the buckets that are shared with snapshots.
+    pub fn into_int_map(self) -> IntMap<K, V> {
+        let table = self.table;
+        let cache = Arc::try_unwrap(table.cache).unwrap_or_else(|cache| (*cache).clone());
+        IntMap {
+            cache: cache
+                .into_iter()
+                .map(|vals| Arc::try_unwrap(vals).unwrap_or_else(|vals| (*vals).clone()))
+                .collect(),
+            size: table.size,
+            mod_mask: table.mod_mask,
+            count: table.count,
+            load_factor: self.load_factor,
+            shrink_load_factor: self.shrink_load_factor,
+            hashing: table.hashing,
+        }
+    }
+
+    //**** Internal hash stuff *****
+
+    // Returns the bucket of the key, copying it if it's shared.
+    fn bucket_mut(&mut self, k: K::Int) -> &mut Vec<(K, V)> {
+        let ix = self.table.calc_index(k);
+        Arc::make_mut(&mut Arc::make_mut(&mut self.table.cache)[ix])
+    }
+
+    // Resizes with the logic of `IntMap`. All buckets are rehashed anyway, so the ones that are
+    // shared with snapshots are copied.
+    fn resize_with<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut IntMap<K, V>),
+    {
+        let mut map = std::mem::take(self).into_int_map();
+        f(&mut map);
+        *self = Self::from_int_map(map);
+    }
+}
+
+impl<K: IntKey, V: Clone> IntMap<K, V> {
+    /// Returns a read-only snapshot of the current contents.
+    ///
+    /// The buckets of an [`IntMap`] aren't shared, so this clones all key/value pairs. Convert the
+    /// map into a [`CowIntMap`] to take snapshots in O(1) while it's mutated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntMap;
+    ///
+    /// let mut map: IntMap<u64, u64> = (0..10).map(|i| (i, i)).collect();
+    /// let snapshot = map.snapshot();
+    /// map.clear();
+    ///
+    /// assert_eq!(snapshot.len(), 10);
+    /// assert_eq!(snapshot.get(3), Some(&3));
+    /// ```
+    pub fn snapshot(&self) -> Snapshot<K, V> {
+        CowIntMap::from_int_map(self.clone()).snapshot()
+    }
+}
+
+impl<K, V> Snapshot<K, V> {
+    /// Returns the number of key/value pairs.
+    pub fn len(&self) -> usize {
+        self.table.count
+    }
+
+    /// Returns true if the snapshot contains no key/value pairs.
+    pub fn is_empty(&self) -> bool {
+        self.table.count == 0
+    }
+
+    /// Returns an [`Iterator`] over all key/value pairs.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.table.iter()
+    }
+
+    /// Returns an [`Iterator`] over all keys.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.iter() }
+    }
+
+    /// Returns an [`Iterator`] over all values.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+}
+
+impl<K: IntKey, V> Snapshot<K, V> {
+    /// Returns the value of the key.
+    pub fn get(&self, key: K) -> Option<&V> {
+        self.table.get(key)
+    }
+
+    /// Returns true if the key is present.
+    pub fn contains_key(&self, key: K) -> bool {
+        self.get(key).is_some()
+    }
+}
+
+impl<K, V> Default for CowIntMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ***************** Conversion *********************
+
+impl<K: IntKey, V> From<IntMap<K, V>> for CowIntMap<K, V> {
+    /// Converts the map without rehashing. The map keeps the configuration of the [`IntMap`],
+    /// e.g. its load factor and [`IndexStrategy`].
+    ///
+    /// [`IndexStrategy`]: crate::IndexStrategy
+    fn from(map: IntMap<K, V>) -> Self {
+        Self::from_int_map(map)
+    }
+}
+
+impl<K: IntKey, V: Clone> From<CowIntMap<K, V>> for IntMap<K, V> {
+    fn from(map: CowIntMap<K, V>) -> Self {
+        map.into_int_map()
+    }
+}
+
+// ***************** Equality *********************
+
+impl<K: IntKey, V: PartialEq> PartialEq for CowIntMap<K, V> {
+    fn eq(&self, other: &CowIntMap<K, V>) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, a)| other.get(k) == Some(a))
+    }
+}
+impl<K: IntKey, V: Eq> Eq for CowIntMap<K, V> {}
+
+impl<K: IntKey, V: PartialEq> PartialEq for Snapshot<K, V> {
+    fn eq(&self, other: &Snapshot<K, V>) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, a)| other.get(k) == Some(a))
+    }
+}
+impl<K: IntKey, V: Eq> Eq for Snapshot<K, V> {}
+
+// ***************** Debug *********************
+
+impl<K, V> std::fmt::Debug for CowIntMap<K, V>
+where
+    K: IntKey + std::fmt::Debug,
+    V: std::fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> std::fmt::Debug for Snapshot<K, V>
+where
+    K: IntKey + std::fmt::Debug,
+    V: std::fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_map().entries(self.iter()).finish()
+    }
+}
+
+// ***************** Extend *********************
+
+impl<K: IntKey, V: Clone> Extend<(K, V)> for CowIntMap<K, V> {
+    #[inline]
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+// ***************** FromIterator *********************
+
+impl<K: IntKey, V: Clone> FromIterator<(K, V)> for CowIntMap<K, V> {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = CowIntMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+// ***************** Iter *********************
+
+impl<'a, K: IntKey, V> IntoIterator for &'a CowIntMap<K, V> {
+    type Item = (K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: IntKey, V> IntoIterator for &'a Snapshot<K, V> {
+    type Item = (K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the key/value pairs of a [`CowIntMap`] or [`Snapshot`].
+///
+/// This struct is created by [`CowIntMap::iter`] and [`Snapshot::iter`].
+pub struct Iter<'a, K, V> {
+    buckets: std::slice::Iter<'a, Bucket<K, V>>,
+    bucket: std::slice::Iter<'a, (K, V)>,
+    remaining: usize,
+}
+
+impl<'a, K: Copy, V> Iterator for Iter<'a, K, V> {
+    type Item = (K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, &'a V)> {
+        loop {
+            if let Some((k, v)) = self.bucket.next() {
+                self.remaining -= 1;
+                return Some((*k, v));
+            }
+            self.bucket = self.buckets.next()?.iter();
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K: Copy, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+// ***************** Keys Iter *********************
+
+/// An iterator over the keys of a [`CowIntMap`] or [`Snapshot`].
+///
+/// This struct is created by [`CowIntMap::keys`] and [`Snapshot::keys`].
+pub struct Keys<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K: Copy, V> Iterator for Keys<'a, K, V> {
+    type Item = K;
+
+    #[inline]
+    fn next(&mut self) -> Option<K> {
+        self.inner.next().map(|(k, _)| k)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+// ***************** Values Iter *********************
+
+/// An iterator over the values of a [`CowIntMap`] or [`Snapshot`].
+///
+/// This struct is created by [`CowIntMap::values`] and [`Snapshot::values`].
+pub struct Values<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K: Copy, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a V> {
+        self.inner.next().map(|(_, v)| v)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
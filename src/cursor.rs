@@ -0,0 +1,188 @@
+// ***************** Cursor *********************
+
+use crate::{IntKey, IntMap};
+
+/// A cursor over the key/value pairs of a [`IntMap`] that can modify, remove and insert pairs
+/// while traversing.
+///
+/// The cursor is created by [`IntMap::cursor_mut`] and starts at the first pair. It visits the
+/// pairs in bucket order, and every pair that was present when the cursor was created is visited
+/// exactly once unless it's removed before. Removing the current pair moves the cursor to the next
+/// pair.
+///
+/// # Examples
+///
+/// ```
+/// use intmap::IntMap;
+///
+/// let mut map: IntMap<u64, u64> = (0..10).map(|i| (i, i)).collect();
+///
+/// let mut cursor = map.cursor_mut();
+/// while let Some((key, value)) = cursor.current_mut() {
+///     if key >= 100 {
+///         // Moved pairs might be visited again.
+///         cursor.move_next();
+///     } else if key % 2 == 0 {
+///         *value *= 10;
+///         cursor.move_next();
+///     } else {
+///         let (key, value) = cursor.remove_current().unwrap();
+///         cursor.insert(key + 100, value);
+///     }
+/// }
+/// drop(cursor);
+///
+/// assert_eq!(map.get(4), Some(&40));
+/// assert_eq!(map.get(5), None);
+/// assert_eq!(map.get(105), Some(&5));
+/// assert_eq!(map.len(), 10);
+/// ```
+pub struct CursorMut<'a, K: IntKey, V> {
+    map: &'a mut IntMap<K, V>,
+    // The position of the current pair, or `map.cache.len()` if the cursor is past the last pair.
+    //
+    // Each bucket is traversed from its last to its first pair. That way removing the current
+    // pair with `swap_remove` only moves an already visited pair, and pushing a new pair to a
+    // bucket doesn't move any pair that is still to be visited.
+    bucket: usize,
+    index: usize,
+}
+
+impl<'a, K: IntKey, V> CursorMut<'a, K, V> {
+    pub(crate) fn new(map: &'a mut IntMap<K, V>) -> Self {
+        let (bucket, index) = first_position(&map.cache, 0);
+        CursorMut { map, bucket, index }
+    }
+
+    /// Returns the current key/value pair, or `None` if the cursor is past the last pair.
+    pub fn current(&self) -> Option<(K, &V)> {
+        self.map
+            .cache
+            .get(self.bucket)
+            .map(|vals| (vals[self.index].0, &vals[self.index].1))
+    }
+
+    /// Returns the current key and a mutable reference to its value, or `None` if the cursor is
+    /// past the last pair.
+    pub fn current_mut(&mut self) -> Option<(K, &mut V)> {
+        let index = self.index;
+        self.map
+            .cache
+            .get_mut(self.bucket)
+            .map(|vals| (vals[index].0, &mut vals[index].1))
+    }
+
+    /// Returns the pair after the current one without moving the cursor.
+    pub fn peek_next(&self) -> Option<(K, &V)> {
+        let (bucket, index) = next_position(&self.map.cache, self.bucket, self.index);
+        self.map
+            .cache
+            .get(bucket)
+            .map(|vals| (vals[index].0, &vals[index].1))
+    }
+
+    /// Moves the cursor to the next pair. Does nothing if the cursor is past the last pair.
+    pub fn move_next(&mut self) {
+        let (bucket, index) = next_position(&self.map.cache, self.bucket, self.index);
+        self.bucket = bucket;
+        self.index = index;
+    }
+
+    /// Removes the current pair, moves the cursor to the next pair and returns the removed pair.
+    ///
+    /// Like [`IntMap::remove`], but the map isn't shrunk while the cursor exists. It's shrunk
+    /// according to its [`ShrinkPolicy`] when the cursor is dropped.
+    ///
+    /// Returns `None` if the cursor is past the last pair.
+    ///
+    /// [`ShrinkPolicy`]: crate::ShrinkPolicy
+    pub fn remove_current(&mut self) -> Option<(K, V)> {
+        let vals = self.map.cache.get_mut(self.bucket)?;
+        let kv = vals.swap_remove(self.index);
+        self.map.count -= 1;
+
+        let (bucket, index) = next_position(&self.map.cache, self.bucket, self.index);
+        self.bucket = bucket;
+        self.index = index;
+
+        self.map.debug_validate();
+        Some(kv)
+    }
+
+    /// Replaces the value of the current pair and returns the old value.
+    ///
+    /// Returns `None` and drops `value` if the cursor is past the last pair.
+    pub fn replace_current(&mut self, value: V) -> Option<V> {
+        let (_, current) = self.current_mut()?;
+        Some(std::mem::replace(current, value))
+    }
+
+    /// Inserts a key/value pair without moving the cursor and returns the previous value if any.
+    ///
+    /// Like [`IntMap::insert`], but the map isn't resized while the cursor exists, so the
+    /// traversal stays valid. It's resized when the cursor is dropped if necessary. A new pair may
+    /// or may not be visited later, depending on its bucket.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if self.map.cache.is_empty() {
+            // An empty map has nothing to traverse.
+            self.map.increase_cache();
+            self.bucket = self.map.cache.len();
+        }
+
+        let k = key.into_int();
+        let ix = self.map.calc_index(k);
+        let vals = &mut self.map.cache[ix];
+
+        let old = match vals.iter().position(|kv| kv.0.into_int() == k) {
+            Some(pos) => Some(std::mem::replace(&mut vals[pos].1, value)),
+            None => {
+                vals.push((key, value));
+                self.map.count += 1;
+                None
+            }
+        };
+
+        self.map.debug_validate();
+        old
+    }
+}
+
+impl<'a, K: IntKey, V> Drop for CursorMut<'a, K, V> {
+    fn drop(&mut self) {
+        // Catch up on the resizing that `insert` and `remove_current` skipped.
+        self.map.increase_cache_if_needed();
+        self.map.decrease_cache_if_needed();
+        self.map.debug_validate();
+    }
+}
+
+impl<'a, K, V> std::fmt::Debug for CursorMut<'a, K, V>
+where
+    K: IntKey + std::fmt::Debug,
+    V: std::fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_tuple("CursorMut").field(&self.current()).finish()
+    }
+}
+
+// Returns the position of the last pair in the first nonempty bucket starting at `bucket`.
+fn first_position<K, V>(cache: &[Vec<(K, V)>], bucket: usize) -> (usize, usize) {
+    cache[bucket.min(cache.len())..]
+        .iter()
+        .position(|vals| !vals.is_empty())
+        .map_or((cache.len(), 0), |offset| {
+            (bucket + offset, cache[bucket + offset].len() - 1)
+        })
+}
+
+// Returns the position after the given one.
+fn next_position<K, V>(cache: &[Vec<(K, V)>], bucket: usize, index: usize) -> (usize, usize) {
+    if bucket >= cache.len() {
+        (cache.len(), 0)
+    } else if index > 0 {
+        (bucket, index - 1)
+    } else {
+        first_position(cache, bucket + 1)
+    }
+}
@@ -1,6 +1,6 @@
 // ***************** Entry *********************
 
-use crate::{int::SealedInt, IntKey, IntMap};
+use crate::{IntKey, IntMap};
 
 /// A view into a single entry in a [`IntMap`], which may either be vacant or occupied.
 ///
@@ -20,9 +20,9 @@ impl<'a, K: IntKey, V> Entry<'a, K, V> {
 
         match vals_ix {
             Some(vals_ix) => Entry::Occupied(OccupiedEntry {
+                cache_ix,
                 vals_ix,
-                vals: &mut int_map.cache[cache_ix],
-                count: &mut int_map.count,
+                int_map,
             }),
             None => Entry::Vacant(VacantEntry {
                 key,
@@ -40,7 +40,7 @@ impl<'a, K: IntKey, V> Entry<'a, K, V> {
         }
 
         let k = key.into_int();
-        let cache_ix = k.calc_index(int_map.mod_mask, K::PRIME);
+        let cache_ix = int_map.calc_index(k);
 
         let vals = &int_map.cache[cache_ix];
         let vals_ix = vals.iter().position(|(key, _)| key.into_int() == k);
@@ -100,44 +100,45 @@ where
 
 /// A view into an occupied entry in a [`IntMap`]. It is part of the [`Entry`] enum.
 pub struct OccupiedEntry<'a, K: IntKey, V: 'a> {
-    // Index to vals, guaranteed to be valid
+    // Index to IntMap::cache, guaranteed to be valid
+    cache_ix: usize,
+    // Index to the element of IntMap::cache, guaranteed to be valid
     vals_ix: usize,
-    // Element of IntMap::cache, guaranteed to be non-empty
-    vals: &'a mut Vec<(K, V)>,
-    // IntMap::count, guaranteed to be non-zero
-    count: &'a mut usize,
+    int_map: &'a mut IntMap<K, V>,
 }
 
 impl<'a, K: IntKey, V> OccupiedEntry<'a, K, V> {
     /// Gets a reference to the value in the entry.
     pub fn get(&self) -> &V {
         // Safety: We didn't modify the cache since we calculated the index
-        &self.vals.get(self.vals_ix).unwrap().1
+        &self.int_map.cache[self.cache_ix][self.vals_ix].1
     }
 
     /// Gets a mutable reference to the value in the entry.
     pub fn get_mut(&mut self) -> &mut V {
         // Safety: We didn't modify the cache since we calculated the index
-        &mut self.vals.get_mut(self.vals_ix).unwrap().1
+        &mut self.int_map.cache[self.cache_ix][self.vals_ix].1
     }
 
     /// Converts the entry into a mutable reference to the value in the entry with a
     /// lifetime bound to the [`IntMap`] itself.
     pub fn into_mut(self) -> &'a mut V {
         // Safety: We didn't modify the cache since we calculated the index
-        &mut self.vals.get_mut(self.vals_ix).unwrap().1
+        &mut self.int_map.cache[self.cache_ix][self.vals_ix].1
     }
 
     /// Sets the value of the entry and returns the old value.
     pub fn insert(&mut self, value: V) -> V {
-        std::mem::replace(&mut self.vals[self.vals_ix].1, value)
+        std::mem::replace(self.get_mut(), value)
     }
 
     /// Removes the value out of the entry and returns it.
     pub fn remove(self) -> V {
         // Warning: We modify the cache here, so the index is now invalid
-        *self.count -= 1;
-        let kv = self.vals.swap_remove(self.vals_ix);
+        self.int_map.count -= 1;
+        let kv = self.int_map.cache[self.cache_ix].swap_remove(self.vals_ix);
+        self.int_map.decrease_cache_if_needed();
+        self.int_map.debug_validate();
 
         kv.1
     }
@@ -155,12 +156,14 @@ impl<'a, K: IntKey, V: 'a> VacantEntry<'a, K, V> {
         if self.int_map.increase_cache_if_needed() {
             // Recompute cache_ix for the new size.
             let k = self.key.into_int();
-            self.cache_ix = k.calc_index(self.int_map.mod_mask, K::PRIME);
+            self.cache_ix = self.int_map.calc_index(k);
         }
 
         self.int_map.count += 1;
+        self.int_map.cache[self.cache_ix].push((self.key, value));
+        self.int_map.debug_validate();
+
         let vals = &mut self.int_map.cache[self.cache_ix];
-        vals.push((self.key, value));
         &mut vals.last_mut().unwrap().1
     }
 }
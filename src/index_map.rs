@@ -0,0 +1,793 @@
+//! A map with integer based keys that preserves the insertion order, see [`IntIndexMap`].
+
+use std::cmp::Ordering;
+use std::iter::FromIterator;
+
+use crate::{IntKey, IntMap};
+
+/// A map with integer based keys that iterates in insertion order.
+///
+/// The pairs are stored in a dense `Vec<(K, V)>` and an [`IntMap`] maps every key to the
+/// position of its pair, so lookups by key cost the same as in an [`IntMap`] while iteration and
+/// serialization are deterministic. Pairs can also be accessed by their position.
+///
+/// Replacing the value of an existing key keeps its position. [`IntIndexMap::swap_remove`]
+/// removes a pair in O(1) by moving the last pair into its place, [`IntIndexMap::shift_remove`]
+/// keeps the order of the remaining pairs but takes O(n).
+///
+/// # Examples
+///
+/// ```
+/// use intmap::IntIndexMap;
+///
+/// let mut map: IntIndexMap<u64, &str> = IntIndexMap::new();
+/// map.insert(30, "c");
+/// map.insert(10, "a");
+/// map.insert(20, "b");
+///
+/// assert_eq!(map.keys().collect::<Vec<_>>(), [30, 10, 20]);
+/// assert_eq!(map.get(10), Some(&"a"));
+/// assert_eq!(map.get_index(0), Some((30, &"c")));
+/// assert_eq!(map.get_index_of(20), Some(2));
+///
+/// assert_eq!(map.swap_remove(30), Some("c"));
+/// assert_eq!(map.keys().collect::<Vec<_>>(), [20, 10]);
+///
+/// map.sort_keys();
+/// assert_eq!(map.keys().collect::<Vec<_>>(), [10, 20]);
+/// ```
+#[derive(Clone)]
+pub struct IntIndexMap<K, V> {
+    // The pairs in their order.
+    entries: Vec<(K, V)>,
+    // Maps every key to the position of its pair in `entries`.
+    index: IntMap<K, usize>,
+}
+
+impl<K, V> IntIndexMap<K, V> {
+    /// Creates a new [`IntIndexMap`].
+    ///
+    /// The [`IntIndexMap`] is initially created with a capacity of 0, so it will not allocate
+    /// until it is first inserted into.
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            index: IntMap::new(),
+        }
+    }
+}
+
+impl<K: IntKey, V> IntIndexMap<K, V> {
+    /// Creates a new [`IntIndexMap`] with space for at least the given number of pairs.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            index: IntMap::with_capacity(capacity),
+        }
+    }
+
+    /// Ensures that the map has space for at least `additional` more pairs.
+    pub fn reserve(&mut self, additional: usize) {
+        self.entries.reserve(additional);
+        self.index.reserve(additional);
+    }
+
+    /// Inserts a key/value pair and returns the previous value if any.
+    ///
+    /// A new key is appended at the end, an existing key keeps its position.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_full(key, value).1
+    }
+
+    /// Like [`IntIndexMap::insert`], but also returns the position of the pair.
+    pub fn insert_full(&mut self, key: K, value: V) -> (usize, Option<V>) {
+        match self.index.get(key) {
+            Some(&pos) => {
+                let old = std::mem::replace(&mut self.entries[pos].1, value);
+                (pos, Some(old))
+            }
+            None => {
+                let pos = self.entries.len();
+                self.index.insert(key, pos);
+                self.entries.push((key, value));
+                (pos, None)
+            }
+        }
+    }
+
+    /// Returns the value of the key.
+    pub fn get(&self, key: K) -> Option<&V> {
+        let pos = *self.index.get(key)?;
+        Some(&self.entries[pos].1)
+    }
+
+    /// Returns a mutable reference to the value of the key.
+    pub fn get_mut(&mut self, key: K) -> Option<&mut V> {
+        let pos = *self.index.get(key)?;
+        Some(&mut self.entries[pos].1)
+    }
+
+    /// Returns the position and the value of the key.
+    pub fn get_full(&self, key: K) -> Option<(usize, &V)> {
+        let pos = *self.index.get(key)?;
+        Some((pos, &self.entries[pos].1))
+    }
+
+    /// Returns the position of the key.
+    pub fn get_index_of(&self, key: K) -> Option<usize> {
+        self.index.get(key).copied()
+    }
+
+    /// Returns the pair at the position.
+    pub fn get_index(&self, index: usize) -> Option<(K, &V)> {
+        self.entries.get(index).map(|(k, v)| (*k, v))
+    }
+
+    /// Returns the key and a mutable reference to the value at the position.
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(K, &mut V)> {
+        self.entries.get_mut(index).map(|(k, v)| (*k, v))
+    }
+
+    /// Returns the first pair.
+    pub fn first(&self) -> Option<(K, &V)> {
+        self.entries.first().map(|(k, v)| (*k, v))
+    }
+
+    /// Returns the last pair.
+    pub fn last(&self) -> Option<(K, &V)> {
+        self.entries.last().map(|(k, v)| (*k, v))
+    }
+
+    /// Returns true if the map contains the key.
+    pub fn contains_key(&self, key: K) -> bool {
+        self.index.contains_key(key)
+    }
+
+    /// Removes the key by replacing its pair with the last pair and returns its value.
+    ///
+    /// This changes the position of the last pair and takes O(1).
+    pub fn swap_remove(&mut self, key: K) -> Option<V> {
+        let pos = self.index.remove(key)?;
+        Some(self.swap_remove_at(pos).1)
+    }
+
+    /// Removes the pair at the position by replacing it with the last pair and returns it.
+    ///
+    /// This changes the position of the last pair and takes O(1).
+    pub fn swap_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        let key = self.entries.get(index)?.0;
+        self.index.remove(key);
+        Some(self.swap_remove_at(index))
+    }
+
+    /// Removes the key by shifting all following pairs and returns its value.
+    ///
+    /// This keeps the order of the remaining pairs and takes O(n).
+    pub fn shift_remove(&mut self, key: K) -> Option<V> {
+        let pos = self.index.remove(key)?;
+        Some(self.shift_remove_at(pos).1)
+    }
+
+    /// Removes the pair at the position by shifting all following pairs and returns it.
+    ///
+    /// This keeps the order of the remaining pairs and takes O(n).
+    pub fn shift_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        let key = self.entries.get(index)?.0;
+        self.index.remove(key);
+        Some(self.shift_remove_at(index))
+    }
+
+    /// Removes the last pair and returns it.
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        let (key, value) = self.entries.pop()?;
+        self.index.remove(key);
+        Some((key, value))
+    }
+
+    /// Retains only the pairs for which `f(key, value)` returns true, in their order.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(K, &mut V) -> bool,
+    {
+        // Decide first, so a panicking `f` leaves the map untouched apart from the values.
+        let keep: Vec<bool> = self.entries.iter_mut().map(|(k, v)| f(*k, v)).collect();
+        if keep.iter().all(|&keep| keep) {
+            return;
+        }
+
+        for ((key, _), _) in self.entries.iter().zip(&keep).filter(|(_, &keep)| !keep) {
+            self.index.remove(*key);
+        }
+        let mut keep = keep.into_iter();
+        self.entries.retain(|_| keep.next().unwrap_or(true));
+        self.update_positions(0..self.entries.len());
+    }
+
+    /// Sorts the pairs with the comparator `f(key_a, value_a, key_b, value_b)`.
+    ///
+    /// The sort is stable.
+    pub fn sort_by<F>(&mut self, mut f: F)
+    where
+        F: FnMut(K, &V, K, &V) -> Ordering,
+    {
+        let guard = Reindex(self);
+        guard.0.entries.sort_by(|a, b| f(a.0, &a.1, b.0, &b.1));
+    }
+
+    /// Sorts the pairs by their keys.
+    pub fn sort_keys(&mut self)
+    where
+        K: Ord,
+    {
+        let guard = Reindex(self);
+        guard.0.entries.sort_unstable_by_key(|kv| kv.0);
+    }
+
+    /// Reverses the order of the pairs.
+    pub fn reverse(&mut self) {
+        self.entries.reverse();
+        self.update_positions(0..self.entries.len());
+    }
+
+    /// Gets the [`Entry`] that corresponds to the given key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntIndexMap;
+    ///
+    /// let mut counters: IntIndexMap<u64, u64> = IntIndexMap::new();
+    /// for key in [7, 3, 7, 7, 3, 1] {
+    ///     *counters.entry(key).or_insert(0) += 1;
+    /// }
+    ///
+    /// assert_eq!(counters.iter().collect::<Vec<_>>(), [(7, &3), (3, &2), (1, &1)]);
+    /// ```
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        match self.index.get(key) {
+            Some(&index) => Entry::Occupied(OccupiedEntry { map: self, index }),
+            None => Entry::Vacant(VacantEntry { map: self, key }),
+        }
+    }
+
+    /// Returns the number of pairs.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the map contains no pairs.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all pairs, keeping the allocated memory.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.index.clear();
+    }
+
+    /// Returns all pairs in their order as a slice.
+    pub fn as_slice(&self) -> &[(K, V)] {
+        &self.entries
+    }
+
+    /// Returns an iterator over the pairs in their order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.entries.iter(),
+        }
+    }
+
+    /// Returns an iterator over the keys and mutable references to the values in their order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inner: self.entries.iter_mut(),
+        }
+    }
+
+    /// Returns an iterator over the keys in their order.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys {
+            inner: self.entries.iter(),
+        }
+    }
+
+    /// Returns an iterator over the values in their order.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values {
+            inner: self.entries.iter(),
+        }
+    }
+
+    /// Returns an iterator over mutable references to the values in their order.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            inner: self.entries.iter_mut(),
+        }
+    }
+
+    // Removes the pair at `pos` whose key was already removed from the index.
+    fn swap_remove_at(&mut self, pos: usize) -> (K, V) {
+        let kv = self.entries.swap_remove(pos);
+        if pos < self.entries.len() {
+            self.update_positions(pos..pos + 1);
+        }
+        kv
+    }
+
+    // Removes the pair at `pos` whose key was already removed from the index.
+    fn shift_remove_at(&mut self, pos: usize) -> (K, V) {
+        let kv = self.entries.remove(pos);
+        self.update_positions(pos..self.entries.len());
+        kv
+    }
+
+    // Stores the positions of the pairs in `range` in the index.
+    fn update_positions(&mut self, range: std::ops::Range<usize>) {
+        for (pos, (key, _)) in self.entries[range.clone()].iter().enumerate() {
+            self.index.insert(*key, range.start + pos);
+        }
+    }
+}
+
+// Updates all positions when dropped, so the index is also correct after a panicking comparator.
+struct Reindex<'a, K: IntKey, V>(&'a mut IntIndexMap<K, V>);
+
+impl<'a, K: IntKey, V> Drop for Reindex<'a, K, V> {
+    fn drop(&mut self) {
+        let len = self.0.entries.len();
+        self.0.update_positions(0..len);
+    }
+}
+
+impl<K, V> Default for IntIndexMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ***************** Equality *********************
+
+impl<K: IntKey, V: PartialEq> PartialEq for IntIndexMap<K, V> {
+    /// Compares the pairs regardless of their order, like [`IntMap`] does.
+    fn eq(&self, other: &IntIndexMap<K, V>) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+impl<K: IntKey, V: Eq> Eq for IntIndexMap<K, V> {}
+
+// ***************** Debug *********************
+
+impl<K, V> std::fmt::Debug for IntIndexMap<K, V>
+where
+    K: IntKey + std::fmt::Debug,
+    V: std::fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_map().entries(self.iter()).finish()
+    }
+}
+
+// ***************** Extend *********************
+
+impl<K: IntKey, V> Extend<(K, V)> for IntIndexMap<K, V> {
+    /// Inserts all pairs like [`IntIndexMap::insert`].
+    #[inline]
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+// ***************** FromIterator *********************
+
+impl<K: IntKey, V> FromIterator<(K, V)> for IntIndexMap<K, V> {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = IntIndexMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+// ***************** Entry *********************
+
+/// A view into a single entry in a [`IntIndexMap`], which may either be vacant or occupied.
+///
+/// The entry can be constructed by calling [`IntIndexMap::entry`] with a key.
+pub enum Entry<'a, K: IntKey, V> {
+    /// The entry is occupied.
+    Occupied(OccupiedEntry<'a, K, V>),
+    /// The entry is vacant.
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+impl<'a, K: IntKey, V> Entry<'a, K, V> {
+    /// Returns the key of the entry.
+    pub fn key(&self) -> K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Returns the position of the pair, or the position it will get when inserted.
+    pub fn index(&self) -> usize {
+        match self {
+            Entry::Occupied(entry) => entry.index(),
+            Entry::Vacant(entry) => entry.index(),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the provided value if empty, and returns
+    /// a mutable reference to the value in the entry.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the result of the provided function if empty,
+    /// and returns a mutable reference to the value in the entry.
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the result of the provided function if empty,
+    /// and returns a mutable reference to the value in the entry.
+    ///
+    /// The function is called with the key of the entry.
+    pub fn or_insert_with_key<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce(K) -> V,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let value = default(entry.key);
+                entry.insert(value)
+            }
+        }
+    }
+
+    /// Calls `f` with the value if the entry is occupied and returns the entry.
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K: IntKey, V: Default> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting the default value if empty, and returns
+    /// a mutable reference to the value in the entry.
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(Default::default)
+    }
+}
+
+/// A view into an occupied entry in a [`IntIndexMap`]. It is part of the [`Entry`] enum.
+pub struct OccupiedEntry<'a, K: IntKey, V> {
+    map: &'a mut IntIndexMap<K, V>,
+    index: usize,
+}
+
+impl<'a, K: IntKey, V> OccupiedEntry<'a, K, V> {
+    /// Returns the key of the entry.
+    pub fn key(&self) -> K {
+        self.map.entries[self.index].0
+    }
+
+    /// Returns the position of the pair.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Gets a reference to the value in the entry.
+    pub fn get(&self) -> &V {
+        &self.map.entries[self.index].1
+    }
+
+    /// Gets a mutable reference to the value in the entry.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.entries[self.index].1
+    }
+
+    /// Converts the entry into a mutable reference to the value in the entry with a
+    /// lifetime bound to the map itself.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.entries[self.index].1
+    }
+
+    /// Sets the value of the entry, and returns the entry's old value.
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the pair like [`IntIndexMap::swap_remove`] and returns its value.
+    pub fn swap_remove(self) -> V {
+        self.map
+            .swap_remove_index(self.index)
+            .map(|kv| kv.1)
+            .unwrap()
+    }
+
+    /// Removes the pair like [`IntIndexMap::shift_remove`] and returns its value.
+    pub fn shift_remove(self) -> V {
+        self.map
+            .shift_remove_index(self.index)
+            .map(|kv| kv.1)
+            .unwrap()
+    }
+}
+
+/// A view into a vacant entry in a [`IntIndexMap`]. It is part of the [`Entry`] enum.
+pub struct VacantEntry<'a, K: IntKey, V> {
+    map: &'a mut IntIndexMap<K, V>,
+    key: K,
+}
+
+impl<'a, K: IntKey, V> VacantEntry<'a, K, V> {
+    /// Returns the key of the entry.
+    pub fn key(&self) -> K {
+        self.key
+    }
+
+    /// Returns the position the pair will get when inserted.
+    pub fn index(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Appends the pair at the end and returns a mutable reference to its value.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let pos = self.map.entries.len();
+        self.map.index.insert(self.key, pos);
+        self.map.entries.push((self.key, value));
+        &mut self.map.entries[pos].1
+    }
+}
+
+// ***************** Iter *********************
+
+impl<'a, K: IntKey, V> IntoIterator for &'a IntIndexMap<K, V> {
+    type Item = (K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the pairs of a [`IntIndexMap`] in their order.
+///
+/// This struct is created by [`IntIndexMap::iter`].
+#[derive(Clone, Debug)]
+pub struct Iter<'a, K, V> {
+    inner: std::slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K: IntKey, V> Iterator for Iter<'a, K, V> {
+    type Item = (K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, &'a V)> {
+        self.inner.next().map(|(k, v)| (*k, v))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for Iter<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, &'a V)> {
+        self.inner.next_back().map(|(k, v)| (*k, v))
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for Iter<'a, K, V> {}
+impl<'a, K: IntKey, V> std::iter::FusedIterator for Iter<'a, K, V> {}
+
+// ***************** Iter Mut *********************
+
+impl<'a, K: IntKey, V> IntoIterator for &'a mut IntIndexMap<K, V> {
+    type Item = (K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// A mutable iterator over the pairs of a [`IntIndexMap`] in their order.
+///
+/// This struct is created by [`IntIndexMap::iter_mut`].
+#[derive(Debug)]
+pub struct IterMut<'a, K, V> {
+    inner: std::slice::IterMut<'a, (K, V)>,
+}
+
+impl<'a, K: IntKey, V> Iterator for IterMut<'a, K, V> {
+    type Item = (K, &'a mut V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, &'a mut V)> {
+        self.inner.next().map(|(k, v)| (*k, v))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for IterMut<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, &'a mut V)> {
+        self.inner.next_back().map(|(k, v)| (*k, v))
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for IterMut<'a, K, V> {}
+impl<'a, K: IntKey, V> std::iter::FusedIterator for IterMut<'a, K, V> {}
+
+// ***************** Keys Iter *********************
+
+/// An iterator over the keys of a [`IntIndexMap`] in their order.
+///
+/// This struct is created by [`IntIndexMap::keys`].
+#[derive(Clone, Debug)]
+pub struct Keys<'a, K, V> {
+    inner: std::slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K: IntKey, V> Iterator for Keys<'a, K, V> {
+    type Item = K;
+
+    #[inline]
+    fn next(&mut self) -> Option<K> {
+        self.inner.next().map(|kv| kv.0)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for Keys<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<K> {
+        self.inner.next_back().map(|kv| kv.0)
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for Keys<'a, K, V> {}
+impl<'a, K: IntKey, V> std::iter::FusedIterator for Keys<'a, K, V> {}
+
+// ***************** Values Iter *********************
+
+/// An iterator over the values of a [`IntIndexMap`] in their order.
+///
+/// This struct is created by [`IntIndexMap::values`].
+#[derive(Clone, Debug)]
+pub struct Values<'a, K, V> {
+    inner: std::slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a V> {
+        self.inner.next().map(|kv| &kv.1)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Values<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a V> {
+        self.inner.next_back().map(|kv| &kv.1)
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Values<'a, K, V> {}
+impl<'a, K, V> std::iter::FusedIterator for Values<'a, K, V> {}
+
+// ***************** Values Mut Iter *********************
+
+/// A mutable iterator over the values of a [`IntIndexMap`] in their order.
+///
+/// This struct is created by [`IntIndexMap::values_mut`].
+#[derive(Debug)]
+pub struct ValuesMut<'a, K, V> {
+    inner: std::slice::IterMut<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a mut V> {
+        self.inner.next().map(|kv| &mut kv.1)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for ValuesMut<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a mut V> {
+        self.inner.next_back().map(|kv| &mut kv.1)
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for ValuesMut<'a, K, V> {}
+impl<'a, K, V> std::iter::FusedIterator for ValuesMut<'a, K, V> {}
+
+// ***************** Into Iter *********************
+
+impl<K: IntKey, V> IntoIterator for IntIndexMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.entries.into_iter(),
+        }
+    }
+}
+
+/// An owning iterator over the pairs of a [`IntIndexMap`] in their order.
+///
+/// This struct is created by [`IntIndexMap::into_iter`].
+#[derive(Debug)]
+pub struct IntoIter<K, V> {
+    inner: std::vec::IntoIter<(K, V)>,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, V)> {
+        self.inner.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, V)> {
+        self.inner.next_back()
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+impl<K, V> std::iter::FusedIterator for IntoIter<K, V> {}
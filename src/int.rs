@@ -15,18 +15,67 @@ impl Int for u128 {}
 impl Int for usize {}
 
 pub trait SealedInt: Copy + PartialEq {
+    const BITS: u32;
+    const NAME: &'static str;
+
     fn calc_index(self, mod_mask: usize, prime: Self) -> usize;
+
+    /// Like `calc_index`, but uses the `size` highest bits of the product.
+    fn calc_index_shift(self, size: u32, multiplier: Self) -> usize;
+
+    /// Like `calc_index`, but maps the product to `0..len` with Lemire's multiply-shift range
+    /// reduction.
+    fn calc_index_range(self, len: usize, multiplier: Self) -> usize;
+
+    /// Truncates to the width of `Self`.
+    fn from_u128(value: u128) -> Self;
+
+    fn into_u128(self) -> u128;
 }
 
 macro_rules! impl_sealed_int_for_int {
     ($uint:ident) => {
         impl SealedInt for $uint {
+            const BITS: u32 = $uint::BITS;
+            const NAME: &'static str = stringify!($uint);
+
             #[inline(always)]
             fn calc_index(self, mod_mask: usize, prime: Self) -> usize {
                 let hash = prime.wrapping_mul(self);
                 // Faster modulus
                 (hash as usize) & mod_mask
             }
+
+            #[inline(always)]
+            fn calc_index_shift(self, size: u32, multiplier: Self) -> usize {
+                let hash = multiplier.wrapping_mul(self);
+                if size == 0 {
+                    0
+                } else if size >= Self::BITS {
+                    hash as usize
+                } else {
+                    (hash >> (Self::BITS - size)) as usize
+                }
+            }
+
+            #[inline(always)]
+            fn calc_index_range(self, len: usize, multiplier: Self) -> usize {
+                let hash = multiplier.wrapping_mul(self) as u128;
+                if Self::BITS == 128 {
+                    // Only the highest 64 bits are used to avoid a 256 bit product.
+                    (((hash >> 64) * len as u128) >> 64) as usize
+                } else {
+                    ((hash * len as u128) >> Self::BITS) as usize
+                }
+            }
+
+            fn from_u128(value: u128) -> Self {
+                value as $uint
+            }
+
+            fn into_u128(self) -> u128 {
+                self as u128
+            }
         }
     };
 }
@@ -22,7 +22,7 @@ use crate::Int;
 /// impl IntKey for MyKey {
 ///     type Int = u64;
 ///
-///     // You could also choose another prime number
+///     // You could also choose another prime number, see `intmap::tune`
 ///     const PRIME: Self::Int = u64::PRIME;
 ///
 ///     fn into_int(self) -> Self::Int {
@@ -43,6 +43,7 @@ pub trait IntKey: Copy {
     /// The prime number used for hashing.
     ///
     /// The choice might influence the number of key collisions which affects the performance.
+    /// Use [`tune`](crate::tune) to find a good one for a sample of keys.
     const PRIME: Self::Int;
 
     /// Converts the key into the underlying integer.
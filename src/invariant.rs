@@ -0,0 +1,213 @@
+use std::fmt;
+
+/// A violated internal invariant of an [`IntMap`], returned by [`IntMap::validate`].
+///
+/// Seeing this error means there is a bug in [`IntMap`] or in an [`IntKey`] implementation,
+/// e.g. [`IntKey::into_int`] returning different integers for the same key.
+///
+/// [`IntMap`]: crate::IntMap
+/// [`IntMap::validate`]: crate::IntMap::validate
+/// [`IntKey`]: crate::IntKey
+/// [`IntKey::into_int`]: crate::IntKey::into_int
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InvariantError {
+    /// The stored count differs from the actual number of key/value pairs.
+    CountMismatch {
+        /// The count returned by [`IntMap::len`](crate::IntMap::len).
+        count: usize,
+        /// The number of key/value pairs found in the slots.
+        actual: usize,
+    },
+    /// A key is stored in a slot that differs from the one its hash points to.
+    WrongSlot {
+        /// The slot the key is stored in.
+        slot: usize,
+        /// The position of the key within the slot.
+        position: usize,
+        /// The slot the hash of the key points to.
+        expected: usize,
+    },
+    /// A key is stored twice in the same slot.
+    DuplicateKey {
+        /// The slot both keys are stored in.
+        slot: usize,
+        /// The position of the first occurrence within the slot.
+        first: usize,
+        /// The position of the second occurrence within the slot.
+        second: usize,
+    },
+    /// The number of slots is neither zero nor a power of two, although the map uses
+    /// [`TableSizing::PowerOfTwo`].
+    ///
+    /// [`TableSizing::PowerOfTwo`]: crate::TableSizing::PowerOfTwo
+    CapacityNotPowerOfTwo {
+        /// The number of slots.
+        capacity: usize,
+    },
+    /// The number of slots differs from `2^size`.
+    CapacityMismatch {
+        /// The binary exponent of the expected number of slots.
+        size: u32,
+        /// The number of slots.
+        capacity: usize,
+    },
+    /// The bit mask for calculating slot indices is not `capacity - 1`.
+    ModMaskMismatch {
+        /// The current bit mask.
+        mod_mask: usize,
+        /// The number of slots.
+        capacity: usize,
+    },
+}
+
+impl fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::CountMismatch { count, actual } => {
+                write!(
+                    f,
+                    "count is {count} but {actual} key/value pairs are stored"
+                )
+            }
+            Self::WrongSlot {
+                slot,
+                position,
+                expected,
+            } => write!(
+                f,
+                "key at position {position} of slot {slot} belongs to slot {expected}"
+            ),
+            Self::DuplicateKey {
+                slot,
+                first,
+                second,
+            } => write!(
+                f,
+                "key at position {first} of slot {slot} is duplicated at position {second}"
+            ),
+            Self::CapacityNotPowerOfTwo { capacity } => {
+                write!(f, "capacity {capacity} is not a power of two")
+            }
+            Self::CapacityMismatch { size, capacity } => {
+                write!(f, "capacity {capacity} does not match size 2^{size}")
+            }
+            Self::ModMaskMismatch { mod_mask, capacity } => {
+                write!(
+                    f,
+                    "mod mask {mod_mask:#x} does not match capacity {capacity}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvariantError {}
+
+#[cfg(test)]
+mod tests {
+    use super::InvariantError;
+    use crate::IntMap;
+
+    #[test]
+    fn count_mismatch() {
+        let mut map: IntMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+        map.count += 1;
+
+        assert_eq!(
+            map.validate(),
+            Err(InvariantError::CountMismatch {
+                count: 101,
+                actual: 100,
+            })
+        );
+    }
+
+    #[test]
+    fn wrong_slot() {
+        let mut map: IntMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+        let slot = map.cache.iter().position(|vals| !vals.is_empty()).unwrap();
+        let target = (slot + 1) % map.cache.len();
+        let kv = map.cache[slot].pop().unwrap();
+        map.cache[target].push(kv);
+        let position = map.cache[target].len() - 1;
+
+        assert_eq!(
+            map.validate(),
+            Err(InvariantError::WrongSlot {
+                slot: target,
+                position,
+                expected: slot,
+            })
+        );
+    }
+
+    #[test]
+    fn duplicate_key() {
+        let mut map: IntMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+        let slot = map.cache.iter().position(|vals| !vals.is_empty()).unwrap();
+        let kv = map.cache[slot][0];
+        map.cache[slot].push(kv);
+        let second = map.cache[slot].len() - 1;
+
+        assert_eq!(
+            map.validate(),
+            Err(InvariantError::DuplicateKey {
+                slot,
+                first: 0,
+                second,
+            })
+        );
+    }
+
+    #[test]
+    fn capacity_not_power_of_two() {
+        let mut map: IntMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+        map.cache.push(Vec::new());
+        let capacity = map.cache.len();
+
+        assert_eq!(
+            map.validate(),
+            Err(InvariantError::CapacityNotPowerOfTwo { capacity })
+        );
+    }
+
+    #[test]
+    fn capacity_mismatch() {
+        let mut map: IntMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+        map.size += 1;
+        let capacity = map.cache.len();
+
+        assert_eq!(
+            map.validate(),
+            Err(InvariantError::CapacityMismatch {
+                size: map.size,
+                capacity,
+            })
+        );
+    }
+
+    #[test]
+    fn mod_mask_mismatch() {
+        let mut map: IntMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+        map.mod_mask = 0;
+        let capacity = map.cache.len();
+
+        assert_eq!(
+            map.validate(),
+            Err(InvariantError::ModMaskMismatch {
+                mod_mask: 0,
+                capacity,
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "debug-invariants")]
+    #[should_panic(expected = "IntMap invariant violated")]
+    fn debug_invariants_panic() {
+        let mut map: IntMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+        map.count += 1;
+        map.insert(1000, 1000);
+    }
+}
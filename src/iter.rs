@@ -1,8 +1,8 @@
-use std::iter::FlatMap as IterFlatMap;
+use std::fmt::{self, Debug, Formatter};
 use std::iter::Flatten as IterFlatten;
+use std::iter::FusedIterator;
 use std::slice::Iter as SliceIter;
 use std::slice::IterMut as SliceIterMut;
-use std::vec::Drain as VecDrain;
 use std::vec::IntoIter as VecIntoIter;
 
 use crate::IntKey;
@@ -15,7 +15,7 @@ impl<'a, K: IntKey, V> IntoIterator for &'a IntMap<K, V> {
     type IntoIter = Iter<'a, K, V>;
 
     fn into_iter(self) -> Self::IntoIter {
-        Iter::new(&self.cache)
+        Iter::new(&self.cache, self.count)
     }
 }
 
@@ -24,12 +24,15 @@ impl<'a, K: IntKey, V> IntoIterator for &'a IntMap<K, V> {
 /// This struct is created by [`IntMap::iter`].
 pub struct Iter<'a, K: IntKey, V> {
     inner: IterFlatten<SliceIter<'a, Vec<(K, V)>>>,
+    // The number of key/value pairs that were not yielded yet.
+    remaining: usize,
 }
 
 impl<'a, K: IntKey, V> Iter<'a, K, V> {
-    pub(crate) fn new(vec: &'a [Vec<(K, V)>]) -> Self {
+    pub(crate) fn new(vec: &'a [Vec<(K, V)>], len: usize) -> Self {
         Iter {
             inner: vec.iter().flatten(),
+            remaining: len,
         }
     }
 }
@@ -39,7 +42,46 @@ impl<'a, K: IntKey, V> Iterator for Iter<'a, K, V> {
 
     #[inline]
     fn next(&mut self) -> Option<(K, &'a V)> {
-        self.inner.next().map(|r| (r.0, &r.1))
+        let next = self.inner.next().map(|r| (r.0, &r.1));
+        self.remaining -= next.is_some() as usize;
+        next
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for Iter<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, &'a V)> {
+        let next = self.inner.next_back().map(|r| (r.0, &r.1));
+        self.remaining -= next.is_some() as usize;
+        next
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K: IntKey, V> FusedIterator for Iter<'a, K, V> {}
+
+impl<'a, K: IntKey, V> Clone for Iter<'a, K, V> {
+    fn clone(&self) -> Self {
+        Iter {
+            inner: self.inner.clone(),
+            remaining: self.remaining,
+        }
+    }
+}
+
+impl<'a, K, V> Debug for Iter<'a, K, V>
+where
+    K: IntKey + Debug,
+    V: Debug,
+{
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_list().entries(self.clone()).finish()
     }
 }
 
@@ -50,7 +92,7 @@ impl<'a, K: IntKey, V> IntoIterator for &'a mut IntMap<K, V> {
     type IntoIter = IterMut<'a, K, V>;
 
     fn into_iter(self) -> Self::IntoIter {
-        IterMut::new(&mut self.cache)
+        IterMut::new(&mut self.cache, self.count)
     }
 }
 
@@ -59,12 +101,15 @@ impl<'a, K: IntKey, V> IntoIterator for &'a mut IntMap<K, V> {
 /// This struct is created by [`IntMap::iter_mut`].
 pub struct IterMut<'a, K: IntKey, V> {
     inner: IterFlatten<SliceIterMut<'a, Vec<(K, V)>>>,
+    // The number of key/value pairs that were not yielded yet.
+    remaining: usize,
 }
 
 impl<'a, K: IntKey, V> IterMut<'a, K, V> {
-    pub(crate) fn new(vec: &'a mut [Vec<(K, V)>]) -> IterMut<'a, K, V> {
+    pub(crate) fn new(vec: &'a mut [Vec<(K, V)>], len: usize) -> IterMut<'a, K, V> {
         IterMut {
             inner: vec.iter_mut().flatten(),
+            remaining: len,
         }
     }
 }
@@ -74,7 +119,35 @@ impl<'a, K: IntKey, V> Iterator for IterMut<'a, K, V> {
 
     #[inline]
     fn next(&mut self) -> Option<(K, &'a mut V)> {
-        self.inner.next().map(|r| (r.0, &mut r.1))
+        let next = self.inner.next().map(|r| (r.0, &mut r.1));
+        self.remaining -= next.is_some() as usize;
+        next
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for IterMut<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, &'a mut V)> {
+        let next = self.inner.next_back().map(|r| (r.0, &mut r.1));
+        self.remaining -= next.is_some() as usize;
+        next
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for IterMut<'a, K, V> {}
+
+impl<'a, K: IntKey, V> FusedIterator for IterMut<'a, K, V> {}
+
+impl<'a, K: IntKey, V> Debug for IterMut<'a, K, V> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("IterMut")
+            .field("remaining", &self.remaining)
+            .finish_non_exhaustive()
     }
 }
 
@@ -100,6 +173,31 @@ impl<'a, K: IntKey, V> Iterator for Keys<'a, K, V> {
     }
 }
 
+impl<'a, K: IntKey, V> DoubleEndedIterator for Keys<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<K> {
+        self.inner.next_back().map(|kv| kv.0)
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for Keys<'a, K, V> {}
+
+impl<'a, K: IntKey, V> FusedIterator for Keys<'a, K, V> {}
+
+impl<'a, K: IntKey, V> Clone for Keys<'a, K, V> {
+    fn clone(&self) -> Self {
+        Keys {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, K: IntKey + Debug, V> Debug for Keys<'a, K, V> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_list().entries(self.clone()).finish()
+    }
+}
+
 // ***************** Values Iter *********************
 
 /// An iterator over the values of a [`IntMap`].
@@ -122,6 +220,31 @@ impl<'a, K: IntKey, V> Iterator for Values<'a, K, V> {
     }
 }
 
+impl<'a, K: IntKey, V> DoubleEndedIterator for Values<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a V> {
+        self.inner.next_back().map(|kv| kv.1)
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for Values<'a, K, V> {}
+
+impl<'a, K: IntKey, V> FusedIterator for Values<'a, K, V> {}
+
+impl<'a, K: IntKey, V> Clone for Values<'a, K, V> {
+    fn clone(&self) -> Self {
+        Values {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, K: IntKey, V: Debug> Debug for Values<'a, K, V> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_list().entries(self.clone()).finish()
+    }
+}
+
 // ***************** Values Mut *********************
 
 /// A mutable iterator over the values of a [`IntMap`].
@@ -145,6 +268,25 @@ impl<'a, K: IntKey, V> Iterator for ValuesMut<'a, K, V> {
     }
 }
 
+impl<'a, K: IntKey, V> DoubleEndedIterator for ValuesMut<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a mut V> {
+        self.inner.next_back().map(|kv| kv.1)
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for ValuesMut<'a, K, V> {}
+
+impl<'a, K: IntKey, V> FusedIterator for ValuesMut<'a, K, V> {}
+
+impl<'a, K: IntKey, V> Debug for ValuesMut<'a, K, V> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("ValuesMut")
+            .field("remaining", &self.inner.remaining)
+            .finish_non_exhaustive()
+    }
+}
+
 // ***************** Into Iter *********************
 
 impl<K: IntKey, V> IntoIterator for IntMap<K, V> {
@@ -152,7 +294,7 @@ impl<K: IntKey, V> IntoIterator for IntMap<K, V> {
     type IntoIter = IntoIter<K, V>;
 
     fn into_iter(self) -> Self::IntoIter {
-        IntoIter::new(self.cache)
+        IntoIter::new(self.cache, self.count)
     }
 }
 
@@ -161,12 +303,15 @@ impl<K: IntKey, V> IntoIterator for IntMap<K, V> {
 /// This struct is created by [`IntMap::into_iter`].
 pub struct IntoIter<K: IntKey, V> {
     inner: IterFlatten<VecIntoIter<Vec<(K, V)>>>,
+    // The number of key/value pairs that were not yielded yet.
+    remaining: usize,
 }
 
 impl<K: IntKey, V> IntoIter<K, V> {
-    pub(crate) fn new(vec: Vec<Vec<(K, V)>>) -> Self {
+    pub(crate) fn new(vec: Vec<Vec<(K, V)>>, len: usize) -> Self {
         IntoIter {
             inner: vec.into_iter().flatten(),
+            remaining: len,
         }
     }
 }
@@ -176,7 +321,35 @@ impl<K: IntKey, V> Iterator for IntoIter<K, V> {
 
     #[inline]
     fn next(&mut self) -> Option<(K, V)> {
-        self.inner.next()
+        let next = self.inner.next();
+        self.remaining -= next.is_some() as usize;
+        next
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K: IntKey, V> DoubleEndedIterator for IntoIter<K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, V)> {
+        let next = self.inner.next_back();
+        self.remaining -= next.is_some() as usize;
+        next
+    }
+}
+
+impl<K: IntKey, V> ExactSizeIterator for IntoIter<K, V> {}
+
+impl<K: IntKey, V> FusedIterator for IntoIter<K, V> {}
+
+impl<K: IntKey, V> Debug for IntoIter<K, V> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("IntoIter")
+            .field("remaining", &self.remaining)
+            .finish_non_exhaustive()
     }
 }
 
@@ -185,21 +358,23 @@ impl<K: IntKey, V> Iterator for IntoIter<K, V> {
 /// A draining iterator for [`IntMap`].
 ///
 /// This struct is created by [`IntMap::drain`].
-#[allow(clippy::type_complexity)]
 pub struct Drain<'a, K: IntKey, V> {
-    count: &'a mut usize,
-    inner: IterFlatMap<
-        SliceIterMut<'a, Vec<(K, V)>>,
-        VecDrain<'a, (K, V)>,
-        fn(&mut Vec<(K, V)>) -> VecDrain<(K, V)>,
-    >,
+    // The buckets that were not drained yet are `map.cache[front..back]`.
+    //
+    // The count of the map is also the number of key/value pairs that were not yielded yet. Every
+    // pair is removed from its bucket together with decrementing the count, so the map stays
+    // consistent if a value panics while being dropped.
+    map: &'a mut IntMap<K, V>,
+    front: usize,
+    back: usize,
 }
 
 impl<'a, K: IntKey, V> Drain<'a, K, V> {
-    pub(crate) fn new(vec: &'a mut [Vec<(K, V)>], count: &'a mut usize) -> Drain<'a, K, V> {
+    pub(crate) fn new(map: &'a mut IntMap<K, V>) -> Drain<'a, K, V> {
         Drain {
-            count,
-            inner: vec.iter_mut().flat_map(|v| v.drain(..)),
+            back: map.cache.len(),
+            front: 0,
+            map,
         }
     }
 }
@@ -209,11 +384,69 @@ impl<'a, K: IntKey, V> Iterator for Drain<'a, K, V> {
 
     #[inline]
     fn next(&mut self) -> Option<(K, V)> {
-        let next = self.inner.next();
-        if next.is_some() {
-            *self.count -= 1;
+        while self.front < self.back {
+            if let Some(kv) = self.map.cache[self.front].pop() {
+                self.map.count -= 1;
+                return Some(kv);
+            }
+            self.front += 1;
         }
-        next
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.map.count, Some(self.map.count))
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for Drain<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, V)> {
+        while self.front < self.back {
+            let vals = &mut self.map.cache[self.back - 1];
+            if !vals.is_empty() {
+                self.map.count -= 1;
+                // Mirrors `next`, which takes the pairs from the end of the bucket.
+                return Some(vals.remove(0));
+            }
+            self.back -= 1;
+        }
+        None
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for Drain<'a, K, V> {}
+
+impl<'a, K: IntKey, V> FusedIterator for Drain<'a, K, V> {}
+
+impl<'a, K: IntKey, V> Debug for Drain<'a, K, V> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("Drain")
+            .field("remaining", &self.map.count)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, K: IntKey, V> Drop for Drain<'a, K, V> {
+    fn drop(&mut self) {
+        // Continues removing the remaining pairs if dropping a value panics.
+        struct DropGuard<'r, 'a, K: IntKey, V>(&'r mut Drain<'a, K, V>);
+
+        impl<'r, 'a, K: IntKey, V> Drop for DropGuard<'r, 'a, K, V> {
+            fn drop(&mut self) {
+                self.0.for_each(drop);
+            }
+        }
+
+        // Remove the key/value pairs that were not yielded yet.
+        while let Some(kv) = self.next() {
+            let guard = DropGuard(self);
+            drop(kv);
+            std::mem::forget(guard);
+        }
+
+        self.map.debug_validate();
     }
 }
 
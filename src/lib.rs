@@ -8,21 +8,64 @@
 //! Be aware that no effort is made against DoS attacks.
 //! </div>
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+#[cfg(feature = "bincode")]
+mod bincode;
+#[cfg(feature = "borsh")]
+mod borsh;
+#[cfg(feature = "proptest")]
+pub mod proptest;
+#[cfg(feature = "quickcheck")]
+mod quickcheck;
 #[cfg(feature = "serde")]
 mod serde;
 
+mod batch;
+pub mod bi_map;
+mod builder;
+mod counter;
+pub mod cow;
+mod cursor;
 mod entry;
+pub mod index_map;
 mod int;
 mod int_key;
+mod invariant;
 mod iter;
-
+pub mod lru;
+pub mod multi_map;
+pub mod persistent;
+mod set_ops;
+pub mod small;
+pub mod soa;
+mod stats;
+pub mod swiss;
+pub mod ttl;
+pub mod tune;
+
+use builder::Hashing;
 use core::iter::{IntoIterator, Iterator};
-use int::SealedInt;
 
+pub use bi_map::IntBiMap;
+pub use builder::{ConfigError, IndexStrategy, IntMapBuilder, ShrinkPolicy, TableSizing};
+pub use counter::IntCounter;
+pub use cow::CowIntMap;
+pub use cursor::CursorMut;
 pub use entry::*;
+pub use index_map::IntIndexMap;
 pub use int::Int;
 pub use int_key::IntKey;
+pub use invariant::InvariantError;
 pub use iter::*;
+pub use lru::IntLruCache;
+pub use multi_map::IntMultiMap;
+pub use persistent::PersistentIntMap;
+pub use small::SmallIntMap;
+pub use soa::IntSoaMap;
+pub use stats::MapStats;
+pub use swiss::IntSwissMap;
+pub use ttl::IntTtlMap;
 
 // Test examples from the README.
 #[doc = include_str!("../README.md")]
@@ -38,9 +81,12 @@ pub struct IntMap<K, V> {
     // slot if they have a hash collision.
     // The size of `cache` as binary exponent. The actual size of `cache` is `2^size`.
     cache: Vec<Vec<(K, V)>>,
-    // The size of `cache` as binary exponent. The actual size of `cache` is `2^size`.
+    // How often `cache` has grown from zero slots. The actual size of `cache` is `2^size`, or
+    // `Hashing::table_len(size)` in general. The latter is O(size), so prefer `cache.len()`.
     size: u32,
     // A bit mask for calculating an index for `cache`. Must be recomputed if `size` changes.
+    //
+    // Always the size of `cache` minus one, even if that isn't a power of two.
     mod_mask: usize,
     // The number of stored key/value pairs.
     count: usize,
@@ -48,6 +94,10 @@ pub struct IntMap<K, V> {
     //
     // Multiplied by 1000, e.g. a load factor of 90.9% will result in the value 909.
     load_factor: usize,
+    // The ratio below which `cache` is shrunk after removals, multiplied by 1000. Zero means never.
+    shrink_load_factor: usize,
+    // How keys are mapped to slots.
+    hashing: Hashing,
 }
 
 impl<K, V> IntMap<K, V> {
@@ -74,6 +124,8 @@ impl<K, V> IntMap<K, V> {
             count: 0,
             mod_mask: 0,
             load_factor: 909, // 90.9%
+            shrink_load_factor: 0,
+            hashing: Hashing::Prime,
         }
     }
 }
@@ -110,9 +162,47 @@ impl<K: IntKey, V> IntMap<K, V> {
     /// let mut map: IntMap<u64, u64> = IntMap::with_capacity(20);
     /// map.set_load_factor(0.909); // Sets load factor to 90.9%
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the load factor is invalid, see [`IntMap::try_set_load_factor`].
     pub fn set_load_factor(&mut self, load_factor: f32) {
-        self.load_factor = (load_factor * 1000.) as usize;
+        if let Err(err) = self.try_set_load_factor(load_factor) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Like [`IntMap::set_load_factor`], but returns an error instead of panicking if the load
+    /// factor is invalid.
+    ///
+    /// The load factor must be a finite number of at least 0.001 and at least twice the load
+    /// factor of the [`ShrinkPolicy`]. The map is unchanged if an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::{ConfigError, IntMap};
+    ///
+    /// let mut map: IntMap<u64, u64> = IntMap::new();
+    /// assert_eq!(
+    ///     map.try_set_load_factor(0.0),
+    ///     Err(ConfigError::InvalidLoadFactor { load_factor: 0.0 })
+    /// );
+    /// assert_eq!(map.try_set_load_factor(2.0), Ok(()));
+    /// ```
+    pub fn try_set_load_factor(&mut self, load_factor: f32) -> Result<(), ConfigError> {
+        let int = builder::load_factor_to_int(load_factor)?;
+        if self.shrink_load_factor * 2 > int {
+            return Err(ConfigError::InvalidShrinkLoadFactor {
+                shrink_load_factor: self.shrink_load_factor as f32 / 1000.,
+                load_factor,
+            });
+        }
+
+        self.load_factor = int;
         self.increase_cache_if_needed();
+        self.debug_validate();
+        Ok(())
     }
 
     /// Returns the current load factor.
@@ -126,6 +216,43 @@ impl<K: IntKey, V> IntMap<K, V> {
         while self.lim() < capacity {
             self.increase_cache();
         }
+        self.debug_validate();
+    }
+
+    /// Shrinks the capacity as much as possible without exceeding the load factor.
+    ///
+    /// If the [`IntMap`] is empty, all memory is released.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntMap;
+    ///
+    /// let mut map: IntMap<u64, u64> = IntMap::with_capacity(1_000);
+    /// map.insert(1, 1);
+    /// map.shrink_to_fit();
+    /// assert_eq!(map.capacity(), 2);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        if self.count == 0 {
+            self.cache = Vec::new();
+            self.size = 0;
+            self.mod_mask = 0;
+        } else {
+            let mut size = self.size;
+            while size > 1
+                && (self.count * 1000) / self.hashing.table_len(size - 1) <= self.load_factor
+            {
+                size -= 1;
+            }
+
+            if size != self.size {
+                self.resize_cache(size);
+            }
+            self.cache.iter_mut().for_each(Vec::shrink_to_fit);
+        }
+
+        self.debug_validate();
     }
 
     /// Inserts a key/value pair into the [`IntMap`].
@@ -142,11 +269,12 @@ impl<K: IntKey, V> IntMap<K, V> {
     /// assert_eq!(map.insert(21, "Ay, caramba"), Some("Eat my shorts"));
     /// assert_eq!(map.get(21), Some(&"Ay, caramba"));
     /// ```
+    #[inline]
     pub fn insert(&mut self, key: K, value: V) -> Option<V> {
         self.increase_cache_if_needed();
 
         let k = key.into_int();
-        let ix = k.calc_index(self.mod_mask, K::PRIME);
+        let ix = self.calc_index(k);
 
         let vals = &mut self.cache[ix];
         let pos = vals.iter().position(|kv| kv.0.into_int() == k);
@@ -160,6 +288,7 @@ impl<K: IntKey, V> IntMap<K, V> {
         };
 
         vals.push((key, value));
+        self.debug_validate();
 
         old
     }
@@ -178,11 +307,12 @@ impl<K: IntKey, V> IntMap<K, V> {
     /// assert!(!map.insert_checked(21, "Ay, caramba"));
     /// assert_eq!(map.get(21), Some(&"Eat my shorts"));
     /// ```
+    #[inline]
     pub fn insert_checked(&mut self, key: K, value: V) -> bool {
         self.increase_cache_if_needed();
 
         let k = key.into_int();
-        let ix = k.calc_index(self.mod_mask, K::PRIME);
+        let ix = self.calc_index(k);
 
         let vals = &mut self.cache[ix];
         if vals.iter().any(|kv| kv.0.into_int() == k) {
@@ -191,6 +321,7 @@ impl<K: IntKey, V> IntMap<K, V> {
 
         self.count += 1;
         vals.push((key, value));
+        self.debug_validate();
 
         true
     }
@@ -215,7 +346,7 @@ impl<K: IntKey, V> IntMap<K, V> {
         }
 
         let k = key.into_int();
-        let ix = k.calc_index(self.mod_mask, K::PRIME);
+        let ix = self.calc_index(k);
 
         let vals = &self.cache[ix];
 
@@ -248,13 +379,12 @@ impl<K: IntKey, V> IntMap<K, V> {
         }
 
         let k = key.into_int();
-        let ix = k.calc_index(self.mod_mask, K::PRIME);
+        let ix = self.calc_index(k);
 
         let vals = &mut self.cache[ix];
 
-        return vals
-            .iter_mut()
-            .find_map(|kv| (kv.0.into_int() == k).then(move || &mut kv.1));
+        vals.iter_mut()
+            .find_map(|kv| (kv.0.into_int() == k).then(move || &mut kv.1))
     }
 
     /// Removes the value for given key from the [`IntMap`] and returns it.
@@ -277,7 +407,7 @@ impl<K: IntKey, V> IntMap<K, V> {
         }
 
         let k = key.into_int();
-        let ix = k.calc_index(self.mod_mask, K::PRIME);
+        let ix = self.calc_index(k);
 
         let vals = &mut self.cache[ix];
 
@@ -287,6 +417,8 @@ impl<K: IntKey, V> IntMap<K, V> {
             if peek.into_int() == k {
                 self.count -= 1;
                 let kv = vals.swap_remove(i);
+                self.decrease_cache_if_needed();
+                self.debug_validate();
                 return Some(kv.1);
             }
         }
@@ -323,10 +455,13 @@ impl<K: IntKey, V> IntMap<K, V> {
     /// ```
     pub fn clear(&mut self) {
         for vals in &mut self.cache {
+            // Updated first, because the bucket is empty afterwards even if dropping a value
+            // panics.
+            self.count -= vals.len();
             vals.clear();
         }
 
-        self.count = 0;
+        self.debug_validate();
     }
 
     /// Retains only the key/value pairs specified by the predicate.
@@ -350,22 +485,122 @@ impl<K: IntKey, V> IntMap<K, V> {
     /// assert!(map.contains_key(1));
     /// assert!(map.contains_key(4));
     /// ```
-    pub fn retain<F>(&mut self, mut f: F)
+    pub fn retain<F>(&mut self, f: F)
     where
         F: FnMut(K, &V) -> bool,
     {
-        let mut removed = 0;
+        self.retain_count(f);
+    }
+
+    /// Retains only the key/value pairs specified by the predicate and returns the number of
+    /// removed pairs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntMap;
+    ///
+    /// let mut map: IntMap<u64, u64> = (0..10).map(|i| (i, i)).collect();
+    /// assert_eq!(map.retain_count(|k, _| k < 3), 7);
+    /// assert_eq!(map.len(), 3);
+    /// ```
+    pub fn retain_count<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut(K, &V) -> bool,
+    {
+        self.retain_mut_count(|k, v| f(k, v))
+    }
+
+    /// Retains only the key/value pairs specified by the predicate, which can also modify the
+    /// values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntMap;
+    ///
+    /// let mut map: IntMap<u64, u64> = [(1, 1), (2, 5), (3, 9)].into_iter().collect();
+    ///
+    /// // Decrement all values and remove the ones that reach zero.
+    /// map.retain_mut(|_, v| {
+    ///     *v -= 1;
+    ///     *v > 0
+    /// });
+    ///
+    /// assert_eq!(map, [(2, 4), (3, 8)].into_iter().collect());
+    /// ```
+    pub fn retain_mut<F>(&mut self, f: F)
+    where
+        F: FnMut(K, &mut V) -> bool,
+    {
+        self.retain_mut_count(f);
+    }
+
+    /// Like [`IntMap::retain_mut`], but stops at the first error of the predicate and returns it.
+    ///
+    /// The pairs that were already removed stay removed, and the remaining pairs are left
+    /// untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntMap;
+    ///
+    /// let mut map: IntMap<u64, u64> = (0..10).map(|i| (i, i)).collect();
+    ///
+    /// let mut budget = 3;
+    /// let result = map.try_retain(|_, v| {
+    ///     if budget == 0 {
+    ///         return Err("out of budget");
+    ///     }
+    ///     budget -= 1;
+    ///     *v += 100;
+    ///     Ok(false)
+    /// });
+    ///
+    /// assert_eq!(result, Err("out of budget"));
+    /// assert_eq!(map.len(), 7);
+    /// assert!(map.values().all(|&v| v < 100));
+    /// ```
+    pub fn try_retain<E, F>(&mut self, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(K, &mut V) -> Result<bool, E>,
+    {
         for vals in &mut self.cache {
-            vals.retain(|(k, v)| {
-                let keep = (f)(*k, v);
-                if !keep {
-                    removed += 1;
+            let mut i = 0;
+            while i < vals.len() {
+                let (k, v) = &mut vals[i];
+                match f(*k, v) {
+                    Ok(true) => i += 1,
+                    Ok(false) => {
+                        // Updated first, so it stays in sync if dropping the value panics.
+                        self.count -= 1;
+                        vals.swap_remove(i);
+                    }
+                    Err(err) => {
+                        self.decrease_cache_if_needed();
+                        self.debug_validate();
+                        return Err(err);
+                    }
                 }
-                keep
-            });
+            }
         }
 
-        self.count -= removed;
+        self.decrease_cache_if_needed();
+        self.debug_validate();
+        Ok(())
+    }
+
+    fn retain_mut_count<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut(K, &mut V) -> bool,
+    {
+        let count = self.count;
+        let result: Result<(), std::convert::Infallible> = self.try_retain(|k, v| Ok(f(k, v)));
+        match result {
+            Ok(()) => count - self.count,
+            Err(never) => match never {},
+        }
     }
 
     /// Returns true if the [`IntMap`] is empty
@@ -388,27 +623,27 @@ impl<K: IntKey, V> IntMap<K, V> {
     //**** Iterators *****
 
     /// Returns an [`Iterator`] over all key/value pairs.
-    pub fn iter(&self) -> Iter<K, V> {
-        Iter::new(&self.cache)
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(&self.cache, self.count)
     }
 
     /// Returns an [`Iterator`] over all key/value pairs with mutable value.
-    pub fn iter_mut(&mut self) -> IterMut<K, V> {
-        IterMut::new(&mut self.cache)
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut::new(&mut self.cache, self.count)
     }
 
     /// Returns an [`Iterator`] over all keys.
-    pub fn keys(&self) -> Keys<K, V> {
+    pub fn keys(&self) -> Keys<'_, K, V> {
         Keys { inner: self.iter() }
     }
 
     /// Returns an [`Iterator`] over all values.
-    pub fn values(&self) -> Values<K, V> {
+    pub fn values(&self) -> Values<'_, K, V> {
         Values { inner: self.iter() }
     }
 
     /// Returns an [`Iterator`] over all mutable values.
-    pub fn values_mut(&mut self) -> ValuesMut<K, V> {
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
         ValuesMut {
             inner: self.iter_mut(),
         }
@@ -419,41 +654,70 @@ impl<K: IntKey, V> IntMap<K, V> {
     ///
     /// If the [`Iterator`] is droppend then all remaining key/value pairs will be removed from
     /// the [`IntMap`].
-    pub fn drain(&mut self) -> Drain<K, V> {
-        Drain::new(&mut self.cache, &mut self.count)
+    pub fn drain(&mut self) -> Drain<'_, K, V> {
+        Drain::new(self)
     }
 
     //**** Internal hash stuff *****
 
     #[inline(always)]
     fn lim(&self) -> usize {
-        if self.size == 0 {
-            0
-        } else {
-            2usize.pow(self.size)
-        }
+        self.cache.len()
+    }
+
+    #[inline(always)]
+    pub(crate) fn calc_index(&self, k: K::Int) -> usize {
+        self.hashing
+            .calc_index(k, K::PRIME, self.size, self.mod_mask)
     }
 
     fn increase_cache(&mut self) {
-        self.size += 1;
-        let new_lim = self.lim();
-        self.mod_mask = new_lim - 1;
+        self.resize_cache(self.size + 1);
+    }
+
+    #[inline]
+    fn decrease_cache_if_needed(&mut self) {
+        if self.shrink_load_factor == 0 {
+            return;
+        }
+
+        // Only compute smaller table lengths when we're about to rehash anyway.
+        let mut size = self.size;
+        let mut len = self.cache.len();
+        while size > 1 && (self.count * 1000) / len < self.shrink_load_factor {
+            size -= 1;
+            len = self.hashing.table_len(size);
+        }
+
+        if size != self.size {
+            self.resize_cache(size);
+        }
+    }
+
+    // Rehashes all pairs into a cache with `Hashing::table_len(size)` slots.
+    pub(crate) fn resize_cache(&mut self, size: u32) {
+        let new_lim = self.hashing.table_len(size);
+
+        let hashing = self.hashing;
+        let mod_mask = new_lim - 1;
+        let indices = precompute_hashes(self.cache.iter().flatten().map(|kv| kv.0), |key| {
+            hashing.calc_index(key.into_int(), K::PRIME, size, mod_mask)
+        });
 
         let mut vec: Vec<Vec<(K, V)>> = (0..new_lim).map(|_| Vec::new()).collect();
         std::mem::swap(&mut self.cache, &mut vec);
+        self.size = size;
+        self.mod_mask = mod_mask;
 
-        for key in vec.into_iter().flatten() {
-            let k = key.0.into_int();
-            let ix = k.calc_index(self.mod_mask, K::PRIME);
-
+        for (key, ix) in vec.into_iter().flatten().zip(indices) {
             let vals = &mut self.cache[ix];
             vals.push(key);
         }
 
         debug_assert!(
-            self.cache.len() == self.lim(),
+            self.cache.len() == new_lim,
             "cache vector the wrong length, lim: {:?} cache: {:?}",
-            self.lim(),
+            new_lim,
             self.cache.len()
         );
     }
@@ -462,13 +726,7 @@ impl<K: IntKey, V> IntMap<K, V> {
     fn increase_cache_if_needed(&mut self) -> bool {
         let initial_cache_len = self.cache.len();
 
-        // Handle empty cache to prevent division by zero.
-        if self.cache.is_empty() {
-            self.increase_cache();
-        }
-
-        // Tried using floats here but insert performance tanked.
-        while ((self.count * 1000) / self.cache.len()) > self.load_factor {
+        while exceeds_load_factor(self.count, self.cache.len(), self.load_factor) {
             self.increase_cache();
         }
 
@@ -513,8 +771,116 @@ impl<K: IntKey, V> IntMap<K, V> {
         self.cache.len()
     }
 
+    /// Returns statistics about the distribution of the keys over the slots.
+    ///
+    /// This inspects every slot, so it's an expensive operation for large maps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntMap;
+    ///
+    /// let mut map: IntMap<u64, u64> = IntMap::with_capacity(4);
+    /// map.set_load_factor(2.0);
+    /// for i in 0..8 {
+    ///     map.insert(i, i);
+    /// }
+    ///
+    /// let stats = map.stats();
+    /// assert_eq!(stats.len, 8);
+    /// assert_eq!(stats.capacity, 4);
+    /// assert_eq!(stats.histogram.iter().sum::<usize>(), 4);
+    /// assert!(stats.max_chain_len >= 2);
+    /// assert!(stats.estimated_lookup_cost(0.5) > 2.0);
+    /// ```
+    pub fn stats(&self) -> MapStats {
+        MapStats::new(self.cache.iter().map(|vals| vals.len()))
+    }
+
     //**** Testing methods *****
 
+    /// Checks the internal invariants of the [`IntMap`].
+    ///
+    /// This is an expensive operation that inspects every key/value pair. It's intended for
+    /// tests and debugging, see also the `debug-invariants` feature that runs it after every
+    /// mutating call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntMap;
+    ///
+    /// let mut map: IntMap<u64, u64> = IntMap::new();
+    /// map.insert(21, 42);
+    /// assert_eq!(map.validate(), Ok(()));
+    /// ```
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        let capacity = self.cache.len();
+
+        let power_of_two = !matches!(self.hashing, Hashing::MultiplyRange(_));
+        if power_of_two && capacity != 0 && !capacity.is_power_of_two() {
+            return Err(InvariantError::CapacityNotPowerOfTwo { capacity });
+        }
+
+        if capacity != self.hashing.table_len(self.size) {
+            return Err(InvariantError::CapacityMismatch {
+                size: self.size,
+                capacity,
+            });
+        }
+
+        if self.mod_mask != capacity.saturating_sub(1) {
+            return Err(InvariantError::ModMaskMismatch {
+                mod_mask: self.mod_mask,
+                capacity,
+            });
+        }
+
+        let mut actual = 0;
+        for (slot, vals) in self.cache.iter().enumerate() {
+            for (position, (key, _)) in vals.iter().enumerate() {
+                let k = key.into_int();
+
+                let expected = self.calc_index(k);
+                if expected != slot {
+                    return Err(InvariantError::WrongSlot {
+                        slot,
+                        position,
+                        expected,
+                    });
+                }
+
+                if let Some(first) = vals[..position].iter().position(|kv| kv.0.into_int() == k) {
+                    return Err(InvariantError::DuplicateKey {
+                        slot,
+                        first,
+                        second: position,
+                    });
+                }
+            }
+
+            actual += vals.len();
+        }
+
+        if self.count != actual {
+            return Err(InvariantError::CountMismatch {
+                count: self.count,
+                actual,
+            });
+        }
+
+        Ok(())
+    }
+
+    // Runs `validate` after mutating calls if the `debug-invariants` feature is enabled.
+    #[inline(always)]
+    pub(crate) fn debug_validate(&self) {
+        #[cfg(feature = "debug-invariants")]
+        if let Err(err) = self.validate() {
+            panic!("IntMap invariant violated: {err}");
+        }
+    }
+
     /// Checks whether the actual count of key/value pairs matches [`IntMap::count`].
     ///
     /// Only for testing.
@@ -527,7 +893,7 @@ impl<K: IntKey, V> IntMap<K, V> {
 
     /// Returns a new [`IntMap`] that contains only the collisions of the current [`IntMap`].
     ///
-    /// Only for testing.
+    /// Only for testing, see [`IntMap::stats`] for a documented alternative.
     #[doc(hidden)]
     pub fn collisions(&self) -> IntMap<u64, u64> {
         let mut map = IntMap::new();
@@ -573,9 +939,17 @@ impl<K: IntKey, V> IntMap<K, V> {
     /// assert_eq!(counters.get(50), Some(&3));
     /// assert_eq!(counters.get(60), Some(&1));
     /// ```
-    pub fn entry(&mut self, key: K) -> Entry<K, V> {
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
         Entry::new(key, self)
     }
+
+    /// Returns a cursor at the first key/value pair, which can modify, remove and insert pairs
+    /// while traversing the [`IntMap`].
+    ///
+    /// See [`CursorMut`] for an example.
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, K, V> {
+        CursorMut::new(self)
+    }
 }
 
 impl<K, V> Default for IntMap<K, V> {
@@ -584,6 +958,30 @@ impl<K, V> Default for IntMap<K, V> {
     }
 }
 
+// Returns the hash of every key for rehashing a map.
+//
+// Maps compute all hashes before they move any pair, so a panicking `IntKey::into_int` unwinds
+// before the map is changed and leaves it intact.
+pub(crate) fn precompute_hashes<K, T, I, F>(keys: I, hash: F) -> Vec<T>
+where
+    I: Iterator<Item = K>,
+    F: FnMut(K) -> T,
+{
+    keys.map(hash).collect()
+}
+
+// Returns true if `count` pairs in `slots` slots exceed the load factor, which is always the case
+// without slots. Tried using floats here but insert performance tanked.
+#[inline(always)]
+pub(crate) fn exceeds_load_factor(count: usize, slots: usize, load_factor: usize) -> bool {
+    slots == 0 || (count * 1000) / slots > load_factor
+}
+
+// The maximum number of pairs the binary decoders reserve space for before actually reading
+// them. Protects against huge allocations caused by a malicious length prefix.
+#[cfg(any(feature = "borsh", feature = "bincode"))]
+pub(crate) const MAX_PREALLOCATION: usize = 1024;
+
 // ***************** Equality *********************
 
 impl<K, V> PartialEq for IntMap<K, V>
@@ -0,0 +1,417 @@
+//! A least recently used cache for integer based keys, see [`IntLruCache`].
+
+use crate::{IntKey, IntMap};
+
+// Marks the absence of a node in the linked list.
+const NIL: usize = usize::MAX;
+
+/// A cache that evicts the least recently used key/value pairs once it's full.
+///
+/// The cache is either limited by the number of pairs ([`IntLruCache::new`]) or by the sum of
+/// their weights ([`IntLruCache::with_weigher`]). Looking up, inserting and evicting a pair are
+/// O(1).
+///
+/// # Examples
+///
+/// ```
+/// use intmap::IntLruCache;
+///
+/// let mut cache: IntLruCache<u64, &str> = IntLruCache::new(2);
+/// cache.put(1, "one");
+/// cache.put(2, "two");
+///
+/// // Touching 1 makes 2 the least recently used pair.
+/// assert_eq!(cache.get(1), Some(&"one"));
+/// let evicted: Vec<_> = cache.put(3, "three").into_iter().collect();
+/// assert_eq!(evicted, [(2, "two")]);
+///
+/// assert_eq!(cache.peek(2), None);
+/// let recency: Vec<_> = cache.iter().map(|(k, _)| k).collect();
+/// assert_eq!(recency, [3, 1]);
+/// ```
+#[derive(Clone)]
+pub struct IntLruCache<K, V> {
+    // Maps every key to the index of its node.
+    index: IntMap<K, usize>,
+    // The nodes of a doubly linked list, in no particular order.
+    nodes: Vec<Node<K, V>>,
+    // The most recently used node.
+    head: usize,
+    // The least recently used node.
+    tail: usize,
+    // The sum of the weights of all nodes.
+    weight: u64,
+    max_weight: u64,
+    // If `None`, every pair weighs 1.
+    weigher: Option<fn(K, &V) -> u64>,
+}
+
+#[derive(Clone)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    weight: u64,
+    // The next more recently used node.
+    prev: usize,
+    // The next less recently used node.
+    next: usize,
+}
+
+impl<K: IntKey, V> IntLruCache<K, V> {
+    /// Creates a new [`IntLruCache`] that holds at most `capacity` key/value pairs.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            index: IntMap::with_capacity(capacity),
+            nodes: Vec::with_capacity(capacity),
+            head: NIL,
+            tail: NIL,
+            weight: 0,
+            max_weight: capacity as u64,
+            weigher: None,
+        }
+    }
+
+    /// Creates a new [`IntLruCache`] where the weights of all key/value pairs sum up to at most
+    /// `max_weight`.
+    ///
+    /// The weight of a pair is computed once by `weigher` when it's inserted. A pair that alone
+    /// is heavier than `max_weight` is evicted right away.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntLruCache;
+    ///
+    /// let mut cache: IntLruCache<u64, String> =
+    ///     IntLruCache::with_weigher(10, |_, value| value.len() as u64);
+    /// cache.put(1, "abcd".to_string());
+    /// cache.put(2, "efgh".to_string());
+    /// assert_eq!(cache.weight(), 8);
+    ///
+    /// let evicted = cache.put(3, "ijklmnop".to_string());
+    /// assert_eq!(evicted.len(), 2);
+    /// assert_eq!(cache.len(), 1);
+    /// ```
+    pub fn with_weigher(max_weight: u64, weigher: fn(K, &V) -> u64) -> Self {
+        Self {
+            index: IntMap::new(),
+            nodes: Vec::new(),
+            head: NIL,
+            tail: NIL,
+            weight: 0,
+            max_weight,
+            weigher: Some(weigher),
+        }
+    }
+
+    /// Inserts a key/value pair as most recently used pair and evicts the least recently used
+    /// pairs until the cache is within its limit again.
+    ///
+    /// Returns the removed pairs: the previous pair of the key if it was present, followed by the
+    /// evicted pairs from the least to the most recently used one.
+    pub fn put(&mut self, key: K, value: V) -> Evicted<K, V> {
+        let mut evicted = Evicted::new();
+        let weight = self.weigh(key, &value);
+
+        match self.index.get(key) {
+            Some(&ix) => {
+                let node = &mut self.nodes[ix];
+                let old_value = std::mem::replace(&mut node.value, value);
+                let old_weight = std::mem::replace(&mut node.weight, weight);
+                self.weight = self.weight - old_weight + weight;
+                self.touch(ix);
+                evicted.push((key, old_value));
+            }
+            None => {
+                let ix = self.nodes.len();
+                self.nodes.push(Node {
+                    key,
+                    value,
+                    weight,
+                    prev: NIL,
+                    next: NIL,
+                });
+                self.index.insert(key, ix);
+                self.weight += weight;
+                self.link_front(ix);
+            }
+        }
+
+        while self.weight > self.max_weight {
+            match self.pop_lru() {
+                Some(pair) => evicted.push(pair),
+                None => break,
+            }
+        }
+
+        evicted
+    }
+
+    /// Returns the value of the key and marks it as most recently used.
+    pub fn get(&mut self, key: K) -> Option<&V> {
+        let ix = *self.index.get(key)?;
+        self.touch(ix);
+        Some(&self.nodes[ix].value)
+    }
+
+    /// Returns the value of the key and marks it as most recently used.
+    ///
+    /// The weight of the pair is not recomputed, use [`IntLruCache::put`] for that.
+    pub fn get_mut(&mut self, key: K) -> Option<&mut V> {
+        let ix = *self.index.get(key)?;
+        self.touch(ix);
+        Some(&mut self.nodes[ix].value)
+    }
+
+    /// Returns the value of the key without changing the recency.
+    pub fn peek(&self, key: K) -> Option<&V> {
+        let ix = *self.index.get(key)?;
+        Some(&self.nodes[ix].value)
+    }
+
+    /// Returns the least recently used pair without changing the recency.
+    pub fn peek_lru(&self) -> Option<(K, &V)> {
+        self.nodes
+            .get(self.tail)
+            .map(|node| (node.key, &node.value))
+    }
+
+    /// Returns true if the cache contains the key, without changing the recency.
+    pub fn contains_key(&self, key: K) -> bool {
+        self.index.contains_key(key)
+    }
+
+    /// Removes the key and returns its value.
+    pub fn remove(&mut self, key: K) -> Option<V> {
+        let ix = *self.index.get(key)?;
+        Some(self.remove_node(ix).value)
+    }
+
+    /// Removes the least recently used pair and returns it.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        if self.tail == NIL {
+            return None;
+        }
+        let node = self.remove_node(self.tail);
+        Some((node.key, node.value))
+    }
+
+    /// Removes all key/value pairs, but keeps the allocated memory for reuse.
+    pub fn clear(&mut self) {
+        self.index.clear();
+        self.nodes.clear();
+        self.head = NIL;
+        self.tail = NIL;
+        self.weight = 0;
+    }
+
+    /// Returns the number of key/value pairs.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if the cache contains no key/value pairs.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the sum of the weights of all key/value pairs, which is the number of pairs if
+    /// the cache has no weigher.
+    pub fn weight(&self) -> u64 {
+        self.weight
+    }
+
+    /// Returns the largest allowed sum of weights, which is the maximum number of pairs if the
+    /// cache has no weigher.
+    pub fn max_weight(&self) -> u64 {
+        self.max_weight
+    }
+
+    /// Returns an iterator over the key/value pairs from the most to the least recently used one,
+    /// without changing the recency.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            nodes: &self.nodes,
+            front: self.head,
+            back: self.tail,
+            len: self.nodes.len(),
+        }
+    }
+
+    fn weigh(&self, key: K, value: &V) -> u64 {
+        match self.weigher {
+            Some(weigher) => weigher(key, value),
+            None => 1,
+        }
+    }
+
+    fn touch(&mut self, ix: usize) {
+        if self.head != ix {
+            self.unlink(ix);
+            self.link_front(ix);
+        }
+    }
+
+    fn link_front(&mut self, ix: usize) {
+        self.nodes[ix].prev = NIL;
+        self.nodes[ix].next = self.head;
+        match self.nodes.get_mut(self.head) {
+            Some(head) => head.prev = ix,
+            None => self.tail = ix,
+        }
+        self.head = ix;
+    }
+
+    fn unlink(&mut self, ix: usize) {
+        let (prev, next) = (self.nodes[ix].prev, self.nodes[ix].next);
+        match self.nodes.get_mut(prev) {
+            Some(node) => node.next = next,
+            None => self.head = next,
+        }
+        match self.nodes.get_mut(next) {
+            Some(node) => node.prev = prev,
+            None => self.tail = prev,
+        }
+    }
+
+    fn remove_node(&mut self, ix: usize) -> Node<K, V> {
+        self.unlink(ix);
+        self.index.remove(self.nodes[ix].key);
+        let node = self.nodes.swap_remove(ix);
+        self.weight -= node.weight;
+
+        // The last node moved into the gap, so everything pointing to it must be updated.
+        let moved = self.nodes.len();
+        if ix < moved {
+            let (key, prev, next) = (self.nodes[ix].key, self.nodes[ix].prev, self.nodes[ix].next);
+            self.index.insert(key, ix);
+            match self.nodes.get_mut(prev) {
+                Some(node) => node.next = ix,
+                None => self.head = ix,
+            }
+            match self.nodes.get_mut(next) {
+                Some(node) => node.prev = ix,
+                None => self.tail = ix,
+            }
+        }
+
+        node
+    }
+}
+
+// ***************** Debug *********************
+
+impl<K, V> std::fmt::Debug for IntLruCache<K, V>
+where
+    K: IntKey + std::fmt::Debug,
+    V: std::fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_map().entries(self.iter()).finish()
+    }
+}
+
+// ***************** Evicted *********************
+
+/// The key/value pairs removed by [`IntLruCache::put`].
+///
+/// Doesn't allocate unless more than one pair was removed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Evicted<K, V> {
+    first: Option<(K, V)>,
+    rest: Vec<(K, V)>,
+}
+
+impl<K, V> Evicted<K, V> {
+    fn new() -> Self {
+        Self {
+            first: None,
+            rest: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, pair: (K, V)) {
+        if self.first.is_none() {
+            self.first = Some(pair);
+        } else {
+            self.rest.push(pair);
+        }
+    }
+
+    /// Returns the number of removed pairs.
+    pub fn len(&self) -> usize {
+        usize::from(self.first.is_some()) + self.rest.len()
+    }
+
+    /// Returns true if no pair was removed.
+    pub fn is_empty(&self) -> bool {
+        self.first.is_none()
+    }
+}
+
+impl<K, V> IntoIterator for Evicted<K, V> {
+    type Item = (K, V);
+    type IntoIter = std::iter::Chain<std::option::IntoIter<(K, V)>, std::vec::IntoIter<(K, V)>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.first.into_iter().chain(self.rest)
+    }
+}
+
+// ***************** Iter *********************
+
+impl<'a, K: IntKey, V> IntoIterator for &'a IntLruCache<K, V> {
+    type Item = (K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the key/value pairs of a [`IntLruCache`] from the most to the least recently
+/// used one.
+///
+/// This struct is created by [`IntLruCache::iter`].
+pub struct Iter<'a, K, V> {
+    nodes: &'a [Node<K, V>],
+    front: usize,
+    back: usize,
+    // Stops the iterator once front and back meet.
+    len: usize,
+}
+
+impl<'a, K: IntKey, V> Iterator for Iter<'a, K, V> {
+    type Item = (K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, &'a V)> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = &self.nodes[self.front];
+        self.front = node.next;
+        self.len -= 1;
+        Some((node.key, &node.value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for Iter<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, &'a V)> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = &self.nodes[self.back];
+        self.back = node.prev;
+        self.len -= 1;
+        Some((node.key, &node.value))
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for Iter<'a, K, V> {}
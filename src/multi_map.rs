@@ -0,0 +1,634 @@
+//! A map from integer based keys to multiple values, see [`IntMultiMap`].
+
+use std::iter::FromIterator;
+use std::slice::Iter as SliceIter;
+use std::slice::IterMut as SliceIterMut;
+use std::vec::IntoIter as VecIntoIter;
+
+use crate::{IntKey, IntMap};
+
+/// A map that maps an integer based `K` to one or more `V`, e.g. for one-to-many indexes.
+///
+/// The values of a key are kept in insertion order. A key with a single value doesn't allocate
+/// besides its slot in the underlying [`IntMap`].
+///
+/// # Examples
+///
+/// ```
+/// use intmap::IntMultiMap;
+///
+/// let mut sessions: IntMultiMap<u64, &str> = IntMultiMap::new();
+/// sessions.insert(1, "laptop");
+/// sessions.insert(1, "phone");
+/// sessions.insert(2, "tablet");
+///
+/// assert_eq!(sessions.get_all(1), ["laptop", "phone"]);
+/// assert_eq!(sessions.len_of(2), 1);
+/// assert_eq!(sessions.len(), 3);
+/// assert_eq!(sessions.keys_len(), 2);
+///
+/// assert_eq!(sessions.remove_one(1, &"laptop"), Some("laptop"));
+/// assert_eq!(sessions.remove_all(2), ["tablet"]);
+/// assert_eq!(sessions.get_all(1), ["phone"]);
+/// assert!(sessions.get_all(2).is_empty());
+/// ```
+#[derive(Clone)]
+pub struct IntMultiMap<K, V> {
+    map: IntMap<K, OneOrMany<V>>,
+    // The number of values of all keys.
+    count: usize,
+}
+
+impl<K, V> IntMultiMap<K, V> {
+    /// Creates a new [`IntMultiMap`].
+    ///
+    /// The [`IntMultiMap`] is initially created with a capacity of 0, so it will not allocate
+    /// until it is first inserted into.
+    pub const fn new() -> Self {
+        Self {
+            map: IntMap::new(),
+            count: 0,
+        }
+    }
+}
+
+impl<K: IntKey, V> IntMultiMap<K, V> {
+    /// Creates a new [`IntMultiMap`] with space for at least the given number of keys.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            map: IntMap::with_capacity(capacity),
+            count: 0,
+        }
+    }
+
+    /// Appends a value to the values of the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntMultiMap;
+    ///
+    /// let mut map: IntMultiMap<u64, _> = IntMultiMap::new();
+    /// map.insert(21, "Eat my shorts");
+    /// map.insert(21, "Ay, caramba");
+    /// assert_eq!(map.get_all(21), ["Eat my shorts", "Ay, caramba"]);
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        self.entry(key).push(value);
+    }
+
+    /// Returns the values of the key in insertion order, or an empty slice if the key is absent.
+    pub fn get_all(&self, key: K) -> &[V] {
+        match self.map.get(key) {
+            Some(values) => values.as_slice(),
+            None => &[],
+        }
+    }
+
+    /// Returns the values of the key in insertion order, or an empty slice if the key is absent.
+    pub fn get_all_mut(&mut self, key: K) -> &mut [V] {
+        match self.map.get_mut(key) {
+            Some(values) => values.as_mut_slice(),
+            None => &mut [],
+        }
+    }
+
+    /// Returns the number of values of the key.
+    pub fn len_of(&self, key: K) -> usize {
+        self.get_all(key).len()
+    }
+
+    /// Returns true if the key has at least one value.
+    pub fn contains_key(&self, key: K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Removes the first value of the key that is equal to `value` and returns it.
+    ///
+    /// The order of the remaining values is preserved. The key is removed together with its last
+    /// value.
+    pub fn remove_one(&mut self, key: K, value: &V) -> Option<V>
+    where
+        V: PartialEq,
+    {
+        let values = self.map.get_mut(key)?;
+        let ix = values.as_slice().iter().position(|v| v == value)?;
+
+        self.count -= 1;
+        if values.len() == 1 {
+            self.map
+                .remove(key)
+                .and_then(|values| values.into_iter().next())
+        } else {
+            Some(values.remove(ix))
+        }
+    }
+
+    /// Removes the key and returns all of its values in insertion order.
+    pub fn remove_all(&mut self, key: K) -> Vec<V> {
+        match self.map.remove(key) {
+            Some(values) => {
+                self.count -= values.len();
+                values.into_vec()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Removes all keys and values, but keeps the allocated memory for reuse.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.count = 0;
+    }
+
+    /// Returns the number of values of all keys.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the number of keys.
+    pub fn keys_len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the map contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns an iterator over all key/value pairs, yielding a key once for every value.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.map.iter(),
+            current: None,
+        }
+    }
+
+    /// Returns an iterator over all key/value pairs with mutable values, yielding a key once for
+    /// every value.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inner: self.map.iter_mut(),
+            current: None,
+        }
+    }
+
+    /// Returns an iterator over the keys and all of their values.
+    pub fn iter_all(&self) -> IterAll<'_, K, V> {
+        IterAll {
+            inner: self.map.iter(),
+        }
+    }
+
+    /// Returns an iterator over the keys, yielding each key once.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys {
+            inner: self.map.keys(),
+        }
+    }
+
+    /// Gets the entry of the key for in-place manipulation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::multi_map::Entry;
+    /// use intmap::IntMultiMap;
+    ///
+    /// let mut map: IntMultiMap<u64, u64> = IntMultiMap::new();
+    /// *map.entry(1).push(10) += 1;
+    /// map.entry(1).or_insert(20);
+    /// map.entry(2).or_insert(20);
+    ///
+    /// match map.entry(1) {
+    ///     Entry::Occupied(mut entry) => entry.get_all_mut().reverse(),
+    ///     Entry::Vacant(_) => unreachable!(),
+    /// }
+    ///
+    /// assert_eq!(map.get_all(1), [11]);
+    /// assert_eq!(map.get_all(2), [20]);
+    /// ```
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        match self.map.entry(key) {
+            crate::Entry::Occupied(inner) => Entry::Occupied(OccupiedEntry {
+                inner,
+                count: &mut self.count,
+            }),
+            crate::Entry::Vacant(inner) => Entry::Vacant(VacantEntry {
+                inner,
+                count: &mut self.count,
+            }),
+        }
+    }
+}
+
+impl<K, V> Default for IntMultiMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ***************** Equality *********************
+
+impl<K, V> PartialEq for IntMultiMap<K, V>
+where
+    K: IntKey,
+    V: PartialEq,
+{
+    /// Two maps are equal if they have the same keys with the same values in the same order.
+    fn eq(&self, other: &IntMultiMap<K, V>) -> bool {
+        self.count == other.count && self.map == other.map
+    }
+}
+impl<K: IntKey, V: Eq> Eq for IntMultiMap<K, V> {}
+
+// ***************** Debug *********************
+
+impl<K, V> std::fmt::Debug for IntMultiMap<K, V>
+where
+    K: IntKey + std::fmt::Debug,
+    V: std::fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_map().entries(self.iter_all()).finish()
+    }
+}
+
+// ***************** Extend *********************
+
+impl<K: IntKey, V> Extend<(K, V)> for IntMultiMap<K, V> {
+    #[inline]
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+// ***************** FromIterator *********************
+
+impl<K: IntKey, V> FromIterator<(K, V)> for IntMultiMap<K, V> {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = IntMultiMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+// ***************** Values *********************
+
+// The values of a single key. `Many` always contains at least two values, so that a single value
+// doesn't allocate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum OneOrMany<V> {
+    One(V),
+    Many(Vec<V>),
+}
+
+impl<V> OneOrMany<V> {
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn as_slice(&self) -> &[V] {
+        match self {
+            Self::One(value) => std::slice::from_ref(value),
+            Self::Many(values) => values,
+        }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [V] {
+        match self {
+            Self::One(value) => std::slice::from_mut(value),
+            Self::Many(values) => values,
+        }
+    }
+
+    fn push(&mut self, value: V) -> &mut V {
+        if let Self::One(_) = self {
+            let values = Self::Many(Vec::with_capacity(2));
+            if let Self::One(first) = std::mem::replace(self, values) {
+                self.as_vec_mut().push(first);
+            }
+        }
+
+        let values = self.as_vec_mut();
+        values.push(value);
+        values.last_mut().unwrap()
+    }
+
+    // Must only be called with at least two values.
+    fn remove(&mut self, ix: usize) -> V {
+        let values = self.as_vec_mut();
+        let value = values.remove(ix);
+        if values.len() == 1 {
+            *self = Self::One(values.pop().unwrap());
+        }
+        value
+    }
+
+    fn as_vec_mut(&mut self) -> &mut Vec<V> {
+        match self {
+            Self::One(_) => unreachable!("single value has no vec"),
+            Self::Many(values) => values,
+        }
+    }
+
+    fn into_vec(self) -> Vec<V> {
+        match self {
+            Self::One(value) => vec![value],
+            Self::Many(values) => values,
+        }
+    }
+}
+
+impl<V> IntoIterator for OneOrMany<V> {
+    type Item = V;
+    type IntoIter = OneOrManyIntoIter<V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            Self::One(value) => OneOrManyIntoIter::One(Some(value)),
+            Self::Many(values) => OneOrManyIntoIter::Many(values.into_iter()),
+        }
+    }
+}
+
+enum OneOrManyIntoIter<V> {
+    One(Option<V>),
+    Many(VecIntoIter<V>),
+}
+
+impl<V> Iterator for OneOrManyIntoIter<V> {
+    type Item = V;
+
+    #[inline]
+    fn next(&mut self) -> Option<V> {
+        match self {
+            Self::One(value) => value.take(),
+            Self::Many(values) => values.next(),
+        }
+    }
+}
+
+// ***************** Entry *********************
+
+/// A view into the values of a single key in a [`IntMultiMap`], which may either be vacant or
+/// occupied.
+///
+/// The entry can be constructed by calling [`IntMultiMap::entry`] with a key.
+pub enum Entry<'a, K: IntKey, V: 'a> {
+    /// The key has at least one value.
+    Occupied(OccupiedEntry<'a, K, V>),
+    /// The key has no values.
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+impl<'a, K: IntKey, V> Entry<'a, K, V> {
+    /// Appends a value to the values of the key and returns a mutable reference to it.
+    pub fn push(self, value: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_vec_push(value),
+            Entry::Vacant(entry) => entry.insert(value),
+        }
+    }
+
+    /// Inserts the value if the key has no values, and returns a mutable reference to all values
+    /// of the key.
+    pub fn or_insert(self, default: V) -> &'a mut [V] {
+        self.or_insert_with(|| default)
+    }
+
+    /// Inserts the result of the provided function if the key has no values, and returns a
+    /// mutable reference to all values of the key.
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut [V]
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => std::slice::from_mut(entry.insert(default())),
+        }
+    }
+}
+
+/// A view into a key with values in a [`IntMultiMap`]. It is part of the [`Entry`] enum.
+pub struct OccupiedEntry<'a, K: IntKey, V: 'a> {
+    inner: crate::OccupiedEntry<'a, K, OneOrMany<V>>,
+    // IntMultiMap::count
+    count: &'a mut usize,
+}
+
+impl<'a, K: IntKey, V> OccupiedEntry<'a, K, V> {
+    /// Gets the values of the key in insertion order.
+    pub fn get_all(&self) -> &[V] {
+        self.inner.get().as_slice()
+    }
+
+    /// Gets the values of the key in insertion order.
+    pub fn get_all_mut(&mut self) -> &mut [V] {
+        self.inner.get_mut().as_mut_slice()
+    }
+
+    /// Converts the entry into a mutable reference to the values of the key with a lifetime bound
+    /// to the [`IntMultiMap`] itself.
+    pub fn into_mut(self) -> &'a mut [V] {
+        self.inner.into_mut().as_mut_slice()
+    }
+
+    /// Returns the number of values of the key.
+    pub fn len(&self) -> usize {
+        self.inner.get().len()
+    }
+
+    /// Always returns false, since an occupied entry has at least one value.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Appends a value to the values of the key and returns a mutable reference to it.
+    pub fn push(&mut self, value: V) -> &mut V {
+        *self.count += 1;
+        self.inner.get_mut().push(value)
+    }
+
+    /// Removes the key and returns all of its values in insertion order.
+    pub fn remove_all(self) -> Vec<V> {
+        let values = self.inner.remove();
+        *self.count -= values.len();
+        values.into_vec()
+    }
+
+    fn into_vec_push(self, value: V) -> &'a mut V {
+        *self.count += 1;
+        self.inner.into_mut().push(value)
+    }
+}
+
+/// A view into a key without values in a [`IntMultiMap`]. It is part of the [`Entry`] enum.
+pub struct VacantEntry<'a, K: IntKey, V: 'a> {
+    inner: crate::VacantEntry<'a, K, OneOrMany<V>>,
+    // IntMultiMap::count
+    count: &'a mut usize,
+}
+
+impl<'a, K: IntKey, V: 'a> VacantEntry<'a, K, V> {
+    /// Inserts the first value of the key and returns a mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        *self.count += 1;
+        match self.inner.insert(OneOrMany::One(value)) {
+            OneOrMany::One(value) => value,
+            OneOrMany::Many(_) => unreachable!("inserted a single value"),
+        }
+    }
+}
+
+// ***************** Iter *********************
+
+impl<'a, K: IntKey, V> IntoIterator for &'a IntMultiMap<K, V> {
+    type Item = (K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the key/value pairs of a [`IntMultiMap`].
+///
+/// This struct is created by [`IntMultiMap::iter`].
+pub struct Iter<'a, K: IntKey, V> {
+    inner: crate::Iter<'a, K, OneOrMany<V>>,
+    current: Option<(K, SliceIter<'a, V>)>,
+}
+
+impl<'a, K: IntKey, V> Iterator for Iter<'a, K, V> {
+    type Item = (K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, &'a V)> {
+        loop {
+            if let Some((key, values)) = &mut self.current {
+                if let Some(value) = values.next() {
+                    return Some((*key, value));
+                }
+            }
+            let (key, values) = self.inner.next()?;
+            self.current = Some((key, values.as_slice().iter()));
+        }
+    }
+}
+
+// ***************** Iter Mut *********************
+
+impl<'a, K: IntKey, V> IntoIterator for &'a mut IntMultiMap<K, V> {
+    type Item = (K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// A mutable iterator over the key/value pairs of a [`IntMultiMap`].
+///
+/// This struct is created by [`IntMultiMap::iter_mut`].
+pub struct IterMut<'a, K: IntKey, V> {
+    inner: crate::IterMut<'a, K, OneOrMany<V>>,
+    current: Option<(K, SliceIterMut<'a, V>)>,
+}
+
+impl<'a, K: IntKey, V> Iterator for IterMut<'a, K, V> {
+    type Item = (K, &'a mut V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, &'a mut V)> {
+        loop {
+            if let Some((key, values)) = &mut self.current {
+                if let Some(value) = values.next() {
+                    return Some((*key, value));
+                }
+            }
+            let (key, values) = self.inner.next()?;
+            self.current = Some((key, values.as_mut_slice().iter_mut()));
+        }
+    }
+}
+
+// ***************** Iter All *********************
+
+/// An iterator over the keys of a [`IntMultiMap`] and all of their values.
+///
+/// This struct is created by [`IntMultiMap::iter_all`].
+pub struct IterAll<'a, K: IntKey, V> {
+    inner: crate::Iter<'a, K, OneOrMany<V>>,
+}
+
+impl<'a, K: IntKey, V> Iterator for IterAll<'a, K, V> {
+    type Item = (K, &'a [V]);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, &'a [V])> {
+        self.inner
+            .next()
+            .map(|(key, values)| (key, values.as_slice()))
+    }
+}
+
+// ***************** Keys Iter *********************
+
+/// An iterator over the keys of a [`IntMultiMap`].
+///
+/// This struct is created by [`IntMultiMap::keys`].
+pub struct Keys<'a, K: IntKey, V> {
+    inner: crate::Keys<'a, K, OneOrMany<V>>,
+}
+
+impl<'a, K: IntKey, V> Iterator for Keys<'a, K, V> {
+    type Item = K;
+
+    #[inline]
+    fn next(&mut self) -> Option<K> {
+        self.inner.next()
+    }
+}
+
+// ***************** Into Iter *********************
+
+impl<K: IntKey, V> IntoIterator for IntMultiMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.map.into_iter(),
+            current: None,
+        }
+    }
+}
+
+/// An owning iterator over the key/value pairs of a [`IntMultiMap`].
+///
+/// This struct is created by [`IntMultiMap::into_iter`].
+pub struct IntoIter<K: IntKey, V> {
+    inner: crate::IntoIter<K, OneOrMany<V>>,
+    current: Option<(K, OneOrManyIntoIter<V>)>,
+}
+
+impl<K: IntKey, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, V)> {
+        loop {
+            if let Some((key, values)) = &mut self.current {
+                if let Some(value) = values.next() {
+                    return Some((*key, value));
+                }
+            }
+            let (key, values) = self.inner.next()?;
+            self.current = Some((key, values.into_iter()));
+        }
+    }
+}
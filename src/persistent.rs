@@ -0,0 +1,415 @@
+//! A persistent map for integer based keys, see [`PersistentIntMap`].
+
+use std::iter::FromIterator;
+use std::sync::Arc;
+
+use crate::{int::SealedInt, IntKey, IntMap};
+
+// The number of key bits consumed by each level of the trie.
+const LEVEL_BITS: u32 = 5;
+const LEVEL_MASK: u128 = (1 << LEVEL_BITS) - 1;
+
+/// A persistent map that maps an integer based `K` to `V`.
+///
+/// Every version of the map is immutable and shares all unchanged parts with the other versions,
+/// so cloning is O(1) and [`PersistentIntMap::insert`] and [`PersistentIntMap::remove`] only copy
+/// O(log n) nodes. This makes it cheap to hand out snapshots to readers while a writer keeps
+/// applying updates. The nodes are shared with [`Arc`], so the versions can be sent to other
+/// threads.
+///
+/// Internally it's a trie with 32 children per node over the bits of [`IntKey::into_int`].
+///
+/// # Examples
+///
+/// ```
+/// use intmap::PersistentIntMap;
+///
+/// let v1: PersistentIntMap<u64, &str> = PersistentIntMap::new().insert(1, "a").insert(2, "b");
+/// let v2 = v1.insert(3, "c").remove(1);
+///
+/// assert_eq!(v1.get(1), Some(&"a"));
+/// assert_eq!(v1.get(3), None);
+/// assert_eq!(v2.get(1), None);
+/// assert_eq!(v2.get(3), Some(&"c"));
+/// assert_eq!(v2.len(), 2);
+/// ```
+pub struct PersistentIntMap<K, V> {
+    root: Option<Arc<Branch<K, V>>>,
+    // The number of key/value pairs.
+    count: usize,
+}
+
+struct Branch<K, V> {
+    // Bit `i` is set if the child for the key bits `i` exists.
+    bitmap: u32,
+    // The children ordered by their key bits.
+    children: Vec<Child<K, V>>,
+}
+
+enum Child<K, V> {
+    Leaf(Arc<(K, V)>),
+    Branch(Arc<Branch<K, V>>),
+}
+
+// Nodes are immutable once shared, so a clone is just another reference to them. Derived impls
+// would add needless `K: Clone` and `V: Clone` bounds.
+impl<K, V> Clone for Branch<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            bitmap: self.bitmap,
+            children: self.children.clone(),
+        }
+    }
+}
+
+impl<K, V> Clone for Child<K, V> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Leaf(leaf) => Self::Leaf(Arc::clone(leaf)),
+            Self::Branch(branch) => Self::Branch(Arc::clone(branch)),
+        }
+    }
+}
+
+impl<K, V> Clone for PersistentIntMap<K, V> {
+    /// Returns the same version of the map in O(1).
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            count: self.count,
+        }
+    }
+}
+
+impl<K, V> PersistentIntMap<K, V> {
+    /// Creates a new empty [`PersistentIntMap`].
+    pub const fn new() -> Self {
+        Self {
+            root: None,
+            count: 0,
+        }
+    }
+
+    /// Returns the number of key/value pairs.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns true if the map contains no key/value pairs.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns true if both maps are the same version, i.e. they share their root node.
+    ///
+    /// Two maps with equal contents that were built independently are not the same version.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.root, &other.root) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns an iterator over all key/value pairs.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut stack = Vec::new();
+        if let Some(root) = &self.root {
+            stack.push(root.children.iter());
+        }
+        Iter {
+            stack,
+            remaining: self.count,
+        }
+    }
+}
+
+impl<K: IntKey, V> PersistentIntMap<K, V> {
+    /// Returns the value of the key.
+    pub fn get(&self, key: K) -> Option<&V> {
+        let bits = key.into_int().into_u128();
+        let mut branch = self.root.as_deref()?;
+        let mut shift = 0;
+
+        loop {
+            match branch.child(bits, shift)? {
+                Child::Leaf(leaf) => {
+                    return if leaf.0.into_int() == key.into_int() {
+                        Some(&leaf.1)
+                    } else {
+                        None
+                    };
+                }
+                Child::Branch(next) => {
+                    branch = next;
+                    shift += LEVEL_BITS;
+                }
+            }
+        }
+    }
+
+    /// Returns true if the map contains the key.
+    pub fn contains_key(&self, key: K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns a new version of the map that contains the key/value pair.
+    ///
+    /// The previous value of the key is replaced in the new version. `self` is not changed.
+    pub fn insert(&self, key: K, value: V) -> Self {
+        let mut map = self.clone();
+        map.insert_mut(key, value);
+        map
+    }
+
+    /// Returns a new version of the map without the key.
+    ///
+    /// `self` is not changed.
+    pub fn remove(&self, key: K) -> Self {
+        let mut map = self.clone();
+        map.remove_mut(key);
+        map
+    }
+
+    /// Inserts the key/value pair into this version of the map and returns true if the key was
+    /// absent.
+    ///
+    /// Only the nodes that are shared with other versions are copied, so this is cheaper than
+    /// [`PersistentIntMap::insert`] when building a map.
+    pub fn insert_mut(&mut self, key: K, value: V) -> bool {
+        let root = self.root.get_or_insert_with(|| {
+            Arc::new(Branch {
+                bitmap: 0,
+                children: Vec::new(),
+            })
+        });
+
+        let added = Arc::make_mut(root).insert(Arc::new((key, value)), 0);
+        if added {
+            self.count += 1;
+        }
+        added
+    }
+
+    /// Removes the key from this version of the map and returns true if it was present.
+    ///
+    /// Only the nodes that are shared with other versions are copied.
+    pub fn remove_mut(&mut self, key: K) -> bool {
+        // Checking first avoids copying shared nodes for absent keys.
+        if !self.contains_key(key) {
+            return false;
+        }
+
+        if let Some(root) = &mut self.root {
+            Arc::make_mut(root).remove(key, 0);
+        }
+        self.count -= 1;
+        if self.count == 0 {
+            self.root = None;
+        }
+        true
+    }
+
+    /// Converts this version of the map into an [`IntMap`].
+    pub fn to_int_map(&self) -> IntMap<K, V>
+    where
+        V: Clone,
+    {
+        let mut map = IntMap::with_capacity(self.count);
+        for (key, value) in self.iter() {
+            map.insert(key, value.clone());
+        }
+        map
+    }
+}
+
+impl<K: IntKey, V> Branch<K, V> {
+    fn child(&self, bits: u128, shift: u32) -> Option<&Child<K, V>> {
+        let bit = 1 << ((bits >> shift) & LEVEL_MASK);
+        if self.bitmap & bit == 0 {
+            None
+        } else {
+            Some(&self.children[self.position(bit)])
+        }
+    }
+
+    // The index in `children` of the child with the given bitmap bit.
+    fn position(&self, bit: u32) -> usize {
+        (self.bitmap & (bit - 1)).count_ones() as usize
+    }
+
+    // Returns true if the key was absent.
+    fn insert(&mut self, leaf: Arc<(K, V)>, shift: u32) -> bool {
+        let bits = leaf.0.into_int().into_u128();
+        let bit = 1 << ((bits >> shift) & LEVEL_MASK);
+        let pos = self.position(bit);
+
+        if self.bitmap & bit == 0 {
+            self.bitmap |= bit;
+            self.children.insert(pos, Child::Leaf(leaf));
+            return true;
+        }
+
+        let existing = match &mut self.children[pos] {
+            Child::Branch(branch) => return Arc::make_mut(branch).insert(leaf, shift + LEVEL_BITS),
+            Child::Leaf(existing) if existing.0.into_int() == leaf.0.into_int() => {
+                *existing = leaf;
+                return false;
+            }
+            Child::Leaf(existing) => Arc::clone(existing),
+        };
+
+        // Both keys share the bits so far, so they move down into a new branch.
+        let mut branch = Branch {
+            bitmap: 0,
+            children: Vec::with_capacity(2),
+        };
+        branch.insert(existing, shift + LEVEL_BITS);
+        branch.insert(leaf, shift + LEVEL_BITS);
+        self.children[pos] = Child::Branch(Arc::new(branch));
+        true
+    }
+
+    // Must only be called if the key is present.
+    fn remove(&mut self, key: K, shift: u32) {
+        let bits = key.into_int().into_u128();
+        let bit = 1 << ((bits >> shift) & LEVEL_MASK);
+        let pos = self.position(bit);
+
+        let collapsed = match &mut self.children[pos] {
+            Child::Leaf(_) => {
+                self.bitmap &= !bit;
+                self.children.remove(pos);
+                return;
+            }
+            Child::Branch(branch) => {
+                let branch = Arc::make_mut(branch);
+                branch.remove(key, shift + LEVEL_BITS);
+                // A branch with a single leaf is replaced by the leaf, so every branch below the
+                // root contains at least two key/value pairs.
+                match branch.children.as_slice() {
+                    [Child::Leaf(leaf)] => Arc::clone(leaf),
+                    _ => return,
+                }
+            }
+        };
+        self.children[pos] = Child::Leaf(collapsed);
+    }
+}
+
+impl<K, V> Default for PersistentIntMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ***************** Conversion *********************
+
+impl<K: IntKey, V> From<IntMap<K, V>> for PersistentIntMap<K, V> {
+    fn from(map: IntMap<K, V>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<K: IntKey, V: Clone> From<&PersistentIntMap<K, V>> for IntMap<K, V> {
+    fn from(map: &PersistentIntMap<K, V>) -> Self {
+        map.to_int_map()
+    }
+}
+
+// ***************** Equality *********************
+
+impl<K, V> PartialEq for PersistentIntMap<K, V>
+where
+    K: IntKey,
+    V: PartialEq,
+{
+    fn eq(&self, other: &PersistentIntMap<K, V>) -> bool {
+        self.ptr_eq(other)
+            || (self.count == other.count && self.iter().all(|(k, a)| other.get(k) == Some(a)))
+    }
+}
+impl<K: IntKey, V: Eq> Eq for PersistentIntMap<K, V> {}
+
+// ***************** Debug *********************
+
+impl<K, V> std::fmt::Debug for PersistentIntMap<K, V>
+where
+    K: IntKey + std::fmt::Debug,
+    V: std::fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_map().entries(self.iter()).finish()
+    }
+}
+
+// ***************** Extend *********************
+
+impl<K: IntKey, V> Extend<(K, V)> for PersistentIntMap<K, V> {
+    #[inline]
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert_mut(key, value);
+        }
+    }
+}
+
+// ***************** FromIterator *********************
+
+impl<K: IntKey, V> FromIterator<(K, V)> for PersistentIntMap<K, V> {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = PersistentIntMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+// ***************** Iter *********************
+
+impl<'a, K: IntKey, V> IntoIterator for &'a PersistentIntMap<K, V> {
+    type Item = (K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the key/value pairs of a [`PersistentIntMap`], ordered by the reversed bits
+/// of the keys.
+///
+/// This struct is created by [`PersistentIntMap::iter`].
+pub struct Iter<'a, K, V> {
+    // The children of the branches on the path to the current leaf that weren't visited yet.
+    stack: Vec<std::slice::Iter<'a, Child<K, V>>>,
+    remaining: usize,
+}
+
+impl<'a, K: Copy, V> Iterator for Iter<'a, K, V> {
+    type Item = (K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, &'a V)> {
+        loop {
+            let children = self.stack.last_mut()?;
+            match children.next() {
+                Some(Child::Leaf(leaf)) => {
+                    self.remaining -= 1;
+                    return Some((leaf.0, &leaf.1));
+                }
+                Some(Child::Branch(branch)) => self.stack.push(branch.children.iter()),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K: Copy, V> ExactSizeIterator for Iter<'a, K, V> {}
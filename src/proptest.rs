@@ -0,0 +1,135 @@
+//! [`proptest`] strategies for generating [`IntMap`]s.
+//!
+//! Enabled with the `proptest` feature. Besides the [`Arbitrary`] implementation for [`IntMap`]
+//! this module exposes the building blocks, so property tests can control keys, values and the
+//! internal layout of the generated maps.
+//!
+//! # Examples
+//!
+//! ```
+//! use intmap::IntMap;
+//! use proptest::prelude::*;
+//!
+//! proptest!(|(map in any::<IntMap<u64, u8>>())| {
+//!     prop_assert_eq!(map.len(), map.iter().count());
+//! });
+//!
+//! proptest!(|(map in intmap::proptest::colliding_int_map(0u32..1000, any::<u8>(), 0..50))| {
+//!     prop_assert!(map.keys().all(|k| k < 1000));
+//! });
+//! ```
+
+use std::fmt::Debug;
+
+use proptest::arbitrary::{any, any_with, Arbitrary};
+use proptest::collection::{vec, SizeRange};
+use proptest::sample::Index;
+use proptest::strategy::{BoxedStrategy, Just, Strategy};
+
+use crate::{IntKey, IntMap};
+
+/// The largest capacity generated by [`capacity`].
+pub const MAX_CAPACITY: usize = 100;
+
+/// Returns a strategy for the capacity passed to [`IntMap::with_capacity`].
+pub fn capacity() -> impl Strategy<Value = usize> {
+    0usize..=MAX_CAPACITY
+}
+
+/// Returns a strategy for the load factor passed to [`IntMap::set_load_factor`].
+pub fn load_factor() -> impl Strategy<Value = f32> {
+    0.1f32..=10.0f32
+}
+
+/// Returns a strategy for [`IntMap`]s with keys from `key` and values from `value`.
+///
+/// `size` is the number of generated pairs, the resulting map may be smaller if `key` produces
+/// duplicates. The maps are created with an arbitrary [`capacity`] and [`load_factor`] so the
+/// internal layout varies as well.
+pub fn int_map<K, V>(
+    key: K,
+    value: V,
+    size: impl Into<SizeRange>,
+) -> impl Strategy<Value = IntMap<K::Value, V::Value>>
+where
+    K: Strategy,
+    K::Value: IntKey,
+    V: Strategy,
+{
+    pairs_with_layout(key, value, size.into(), Just(false))
+}
+
+/// Returns a strategy for [`IntMap`]s whose keys are picked from a small pool of 1 to 10 keys
+/// generated by `key`.
+///
+/// The pairs have a high probability of sharing keys and slots, which exercises replacement and
+/// collision handling. Otherwise it behaves like [`int_map`].
+pub fn colliding_int_map<K, V>(
+    key: K,
+    value: V,
+    size: impl Into<SizeRange>,
+) -> impl Strategy<Value = IntMap<K::Value, V::Value>>
+where
+    K: Strategy,
+    K::Value: IntKey,
+    V: Strategy,
+{
+    pairs_with_layout(key, value, size.into(), Just(true))
+}
+
+fn pairs_with_layout<K, V>(
+    key: K,
+    value: V,
+    size: SizeRange,
+    colliding: impl Strategy<Value = bool>,
+) -> impl Strategy<Value = IntMap<K::Value, V::Value>>
+where
+    K: Strategy,
+    K::Value: IntKey,
+    V: Strategy,
+{
+    (
+        vec((key, any::<Index>(), value), size),
+        colliding,
+        1usize..=10,
+        capacity(),
+        load_factor(),
+    )
+        .prop_map(|(pairs, colliding, pool_len, capacity, load_factor)| {
+            // The pool consists of the first generated keys, so shrinking the keys still works.
+            let pool: Vec<_> = if colliding {
+                pairs.iter().take(pool_len).map(|(k, _, _)| *k).collect()
+            } else {
+                Vec::new()
+            };
+
+            let mut map = IntMap::with_capacity(capacity);
+            map.set_load_factor(load_factor);
+            for (k, ix, v) in pairs {
+                let k = if pool.is_empty() { k } else { *ix.get(&pool) };
+                map.insert(k, v);
+            }
+            map
+        })
+}
+
+impl<K, V> Arbitrary for IntMap<K, V>
+where
+    K: IntKey + Arbitrary + Debug + 'static,
+    V: Arbitrary + 'static,
+{
+    type Parameters = (SizeRange, K::Parameters, V::Parameters);
+    type Strategy = BoxedStrategy<Self>;
+
+    /// Generates maps with arbitrary keys and maps with colliding keys at equal rates.
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        let (size, key, value) = args;
+        pairs_with_layout(
+            any_with::<K>(key),
+            any_with::<V>(value),
+            size,
+            any::<bool>(),
+        )
+        .boxed()
+    }
+}
@@ -0,0 +1,41 @@
+use crate::{IntKey, IntMap};
+use quickcheck::{Arbitrary, Gen};
+
+impl<K, V> Arbitrary for IntMap<K, V>
+where
+    K: IntKey + Arbitrary,
+    V: Arbitrary,
+{
+    /// Generates maps with arbitrary keys and maps whose keys are picked from a small pool of
+    /// 1 to 10 keys at equal rates. The latter exercises replacement and collision handling.
+    fn arbitrary(g: &mut Gen) -> Self {
+        let len = usize::arbitrary(g) % (g.size() + 1);
+        let colliding = bool::arbitrary(g);
+
+        let pool: Vec<K> = if colliding {
+            (0..=usize::arbitrary(g) % 10)
+                .map(|_| K::arbitrary(g))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let mut map = IntMap::with_capacity(usize::arbitrary(g) % 101);
+        map.set_load_factor(f32::from(u16::arbitrary(g) % 100 + 1) / 10.0);
+
+        for _ in 0..len {
+            let key = match g.choose(&pool) {
+                Some(key) => *key,
+                None => K::arbitrary(g),
+            };
+            map.insert(key, V::arbitrary(g));
+        }
+
+        map
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let pairs: Vec<(K, V)> = self.clone().into_iter().collect();
+        Box::new(pairs.shrink().map(|pairs| pairs.into_iter().collect()))
+    }
+}
@@ -1,6 +1,6 @@
-use crate::{IntKey, IntMap};
+use crate::{IntBiMap, IntIndexMap, IntKey, IntMap};
 use serde::{
-    de::{Deserializer, MapAccess, Visitor},
+    de::{Deserializer, Error, MapAccess, Visitor},
     ser::SerializeMap,
     Deserialize, Serialize, Serializer,
 };
@@ -71,3 +71,146 @@ where
         Ok(map)
     }
 }
+
+impl<L, R> Serialize for IntBiMap<L, R>
+where
+    L: IntKey + Serialize,
+    R: IntKey + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (l, r) in self.iter() {
+            map.serialize_entry(&l, &r)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de, L, R> Deserialize<'de> for IntBiMap<L, R>
+where
+    L: IntKey + Deserialize<'de>,
+    R: IntKey + Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(IntBiMapVisitor::new())
+    }
+}
+
+struct IntBiMapVisitor<L: IntKey, R: IntKey> {
+    marker: std::marker::PhantomData<fn() -> IntBiMap<L, R>>,
+}
+
+impl<L: IntKey, R: IntKey> IntBiMapVisitor<L, R> {
+    fn new() -> Self {
+        IntBiMapVisitor {
+            marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<'de, L, R> Visitor<'de> for IntBiMapVisitor<L, R>
+where
+    L: IntKey + Deserialize<'de>,
+    R: IntKey + Deserialize<'de>,
+{
+    type Value = IntBiMap<L, R>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "IntBiMap<{}, {}> without duplicates",
+            std::any::type_name::<L>(),
+            std::any::type_name::<R>()
+        )
+    }
+
+    fn visit_map<M>(self, mut access: M) -> Result<Self::Value, M::Error>
+    where
+        M: MapAccess<'de>,
+    {
+        let mut map = IntBiMap::with_capacity(access.size_hint().unwrap_or(0));
+
+        while let Some((left, right)) = access.next_entry()? {
+            // Silently dropping pairs would lose data, so duplicates are rejected.
+            if map.insert_no_overwrite(left, right).is_err() {
+                return Err(M::Error::custom("duplicate value in IntBiMap"));
+            }
+        }
+
+        Ok(map)
+    }
+}
+
+impl<K, V> Serialize for IntIndexMap<K, V>
+where
+    K: IntKey + Serialize,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // The pairs are serialized in their order, so the output is deterministic.
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (k, v) in self.iter() {
+            map.serialize_entry(&k, v)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de, K, V> Deserialize<'de> for IntIndexMap<K, V>
+where
+    K: IntKey + Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(IntIndexMapVisitor::new())
+    }
+}
+
+struct IntIndexMapVisitor<K: IntKey, V> {
+    marker: std::marker::PhantomData<fn() -> IntIndexMap<K, V>>,
+}
+
+impl<K: IntKey, V> IntIndexMapVisitor<K, V> {
+    fn new() -> Self {
+        IntIndexMapVisitor {
+            marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<'de, K, V> Visitor<'de> for IntIndexMapVisitor<K, V>
+where
+    K: IntKey + Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = IntIndexMap<K, V>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "IntIndexMap<{}>", std::any::type_name::<V>())
+    }
+
+    fn visit_map<M>(self, mut access: M) -> Result<Self::Value, M::Error>
+    where
+        M: MapAccess<'de>,
+    {
+        let mut map = IntIndexMap::with_capacity(access.size_hint().unwrap_or(0));
+
+        while let Some((key, value)) = access.next_entry()? {
+            map.insert(key, value);
+        }
+
+        Ok(map)
+    }
+}
@@ -0,0 +1,259 @@
+use crate::{IntKey, IntMap};
+
+impl<K: IntKey, V> IntMap<K, V> {
+    /// Moves all pairs of `other` into `self` and combines the values of keys that are present in
+    /// both maps with `f(key, value_of_self, value_of_other)`.
+    ///
+    /// If both maps have the same capacity and index strategy, the pairs are merged bucket by
+    /// bucket without rehashing. A smaller `self` is grown to the capacity of `other` first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntMap;
+    ///
+    /// let mut a: IntMap<u64, u64> = [(1, 10), (2, 20)].into_iter().collect();
+    /// let b: IntMap<u64, u64> = [(2, 2), (3, 3)].into_iter().collect();
+    /// a.merge_with(b, |_, a, b| a + b);
+    ///
+    /// assert_eq!(a, [(1, 10), (2, 22), (3, 3)].into_iter().collect());
+    /// ```
+    pub fn merge_with<F>(&mut self, other: IntMap<K, V>, mut f: F)
+    where
+        F: FnMut(K, V, V) -> V,
+    {
+        self.absorb(other, |vals, count, key, value| {
+            let k = key.into_int();
+            match vals.iter().position(|kv| kv.0.into_int() == k) {
+                Some(pos) => {
+                    // The pair is missing while `f` runs, so the count must not include it.
+                    let (key, old) = vals.swap_remove(pos);
+                    *count -= 1;
+                    let value = f(key, old, value);
+                    vals.push((key, value));
+                    *count += 1;
+                }
+                None => {
+                    vals.push((key, value));
+                    *count += 1;
+                }
+            }
+        });
+    }
+
+    /// Moves the pairs of `other` whose keys are absent in `self` into `self`.
+    ///
+    /// The values of `self` are kept for keys that are present in both maps. See
+    /// [`IntMap::merge_with`] for how the pairs are moved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntMap;
+    ///
+    /// let mut a: IntMap<u64, &str> = [(1, "a"), (2, "b")].into_iter().collect();
+    /// a.union([(2, "x"), (3, "c")].into_iter().collect());
+    ///
+    /// assert_eq!(a, [(1, "a"), (2, "b"), (3, "c")].into_iter().collect());
+    /// ```
+    pub fn union(&mut self, other: IntMap<K, V>) {
+        self.absorb(other, |vals, count, key, value| {
+            let k = key.into_int();
+            if !vals.iter().any(|kv| kv.0.into_int() == k) {
+                vals.push((key, value));
+                *count += 1;
+            }
+        });
+    }
+
+    /// Removes the keys that are present in both maps and moves the remaining pairs of `other`
+    /// into `self`.
+    ///
+    /// See [`IntMap::merge_with`] for how the pairs are moved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntMap;
+    ///
+    /// let mut a: IntMap<u64, &str> = [(1, "a"), (2, "b")].into_iter().collect();
+    /// a.symmetric_difference([(2, "x"), (3, "c")].into_iter().collect());
+    ///
+    /// assert_eq!(a, [(1, "a"), (3, "c")].into_iter().collect());
+    /// ```
+    pub fn symmetric_difference(&mut self, other: IntMap<K, V>) {
+        self.absorb(other, |vals, count, key, value| {
+            let k = key.into_int();
+            match vals.iter().position(|kv| kv.0.into_int() == k) {
+                Some(pos) => {
+                    *count -= 1;
+                    vals.swap_remove(pos);
+                }
+                None => {
+                    vals.push((key, value));
+                    *count += 1;
+                }
+            }
+        });
+    }
+
+    /// Retains only the keys that are present in `other` and updates their values with
+    /// `f(key, value_of_self, value_of_other)`.
+    ///
+    /// If both maps have the same capacity and index strategy, the keys are looked up bucket by
+    /// bucket without rehashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntMap;
+    ///
+    /// let mut a: IntMap<u64, u64> = [(1, 10), (2, 20)].into_iter().collect();
+    /// let b: IntMap<u64, u8> = [(2, 2), (3, 3)].into_iter().collect();
+    /// a.intersection_with(&b, |_, a, &b| *a += b as u64);
+    ///
+    /// assert_eq!(a, [(2, 22)].into_iter().collect());
+    /// ```
+    pub fn intersection_with<W, F>(&mut self, other: &IntMap<K, W>, mut f: F)
+    where
+        F: FnMut(K, &mut V, &W),
+    {
+        self.retain_matching(other, |key, value, found| match found {
+            Some(other_value) => {
+                f(key, value, other_value);
+                true
+            }
+            None => false,
+        });
+    }
+
+    /// Retains only the keys that are present in `other`, e.g. an `IntMap<K, ()>` used as set.
+    ///
+    /// See [`IntMap::intersection_with`] for how the keys are looked up.
+    pub fn retain_keys_in<W>(&mut self, other: &IntMap<K, W>) {
+        self.retain_matching(other, |_, _, found| found.is_some());
+    }
+
+    /// Removes the keys that are present in `other`.
+    ///
+    /// See [`IntMap::intersection_with`] for how the keys are looked up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntMap;
+    ///
+    /// let mut a: IntMap<u64, &str> = [(1, "a"), (2, "b")].into_iter().collect();
+    /// let b: IntMap<u64, ()> = [(2, ())].into_iter().collect();
+    /// a.difference(&b);
+    ///
+    /// assert_eq!(a, [(1, "a")].into_iter().collect());
+    /// ```
+    pub fn difference<W>(&mut self, other: &IntMap<K, W>) {
+        self.retain_matching(other, |_, _, found| found.is_none());
+    }
+
+    /// Returns true if every pair of `self` is also present in `other` with an equal value.
+    ///
+    /// See [`IntMap::intersection_with`] for how the keys are looked up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntMap;
+    ///
+    /// let a: IntMap<u64, u64> = [(1, 1)].into_iter().collect();
+    /// let b: IntMap<u64, u64> = [(1, 1), (2, 2)].into_iter().collect();
+    /// assert!(a.is_submap_of(&b));
+    /// assert!(!b.is_submap_of(&a));
+    /// ```
+    pub fn is_submap_of(&self, other: &IntMap<K, V>) -> bool
+    where
+        V: PartialEq,
+    {
+        if self.count > other.count {
+            return false;
+        }
+
+        let aligned = self.cache.len() == other.cache.len() && self.hashing == other.hashing;
+        self.cache.iter().enumerate().all(|(i, vals)| {
+            vals.iter().all(|(key, value)| {
+                let found = if aligned {
+                    find(&other.cache[i], *key)
+                } else {
+                    other.get(*key)
+                };
+                found == Some(value)
+            })
+        })
+    }
+
+    // Moves all pairs of `other` into the matching buckets of `self` with `f`, which also updates
+    // the count together with the bucket.
+    fn absorb<F>(&mut self, other: IntMap<K, V>, mut f: F)
+    where
+        F: FnMut(&mut Vec<(K, V)>, &mut usize, K, V),
+    {
+        if other.is_empty() {
+            return;
+        }
+
+        // With equal capacities and hashing the bucket of a key is the same in both maps.
+        while self.cache.len() < other.cache.len() {
+            self.increase_cache();
+        }
+        let aligned = self.cache.len() == other.cache.len() && self.hashing == other.hashing;
+
+        for (i, vals) in other.cache.into_iter().enumerate() {
+            for (key, value) in vals {
+                let ix = if aligned {
+                    i
+                } else {
+                    self.calc_index(key.into_int())
+                };
+
+                f(&mut self.cache[ix], &mut self.count, key, value);
+            }
+        }
+
+        self.increase_cache_if_needed();
+        self.decrease_cache_if_needed();
+        self.debug_validate();
+    }
+
+    // Retains the pairs for which `f(key, value, value_in_other)` returns true.
+    fn retain_matching<W, F>(&mut self, other: &IntMap<K, W>, mut f: F)
+    where
+        F: FnMut(K, &mut V, Option<&W>) -> bool,
+    {
+        let aligned = self.cache.len() == other.cache.len() && self.hashing == other.hashing;
+
+        for (i, vals) in self.cache.iter_mut().enumerate() {
+            let mut j = 0;
+            while j < vals.len() {
+                let (key, value) = &mut vals[j];
+                let found = if aligned {
+                    find(&other.cache[i], *key)
+                } else {
+                    other.get(*key)
+                };
+
+                if f(*key, value, found) {
+                    j += 1;
+                } else {
+                    self.count -= 1;
+                    vals.swap_remove(j);
+                }
+            }
+        }
+
+        self.decrease_cache_if_needed();
+        self.debug_validate();
+    }
+}
+
+fn find<K: IntKey, V>(vals: &[(K, V)], key: K) -> Option<&V> {
+    let k = key.into_int();
+    vals.iter()
+        .find_map(|kv| (kv.0.into_int() == k).then(|| &kv.1))
+}
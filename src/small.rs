@@ -0,0 +1,1175 @@
+//! A map with integer based keys that stores a few pairs inline, see [`SmallIntMap`].
+
+use std::fmt::{self, Debug, Formatter};
+use std::iter::{FromIterator, FusedIterator};
+
+use crate::{builder, ConfigError, IntKey, IntMap};
+
+/// A map with integer based keys that stores up to `N` pairs inline without allocating.
+///
+/// The pairs are kept in an array and found by a linear scan, which is faster than hashing for
+/// a handful of pairs. Inserting more than `N` pairs moves all of them into an [`IntMap`], which
+/// is used from then on. [`SmallIntMap::shrink_to_fit`] moves them back into the array once they
+/// fit again.
+///
+/// The API follows the one of [`IntMap`], including the set operations. The load factor only
+/// applies to the [`IntMap`] of a spilled map, and the set operations are delegated to it if both
+/// maps are spilled.
+///
+/// # Examples
+///
+/// ```
+/// use intmap::SmallIntMap;
+///
+/// let mut map: SmallIntMap<u64, &str, 2> = SmallIntMap::new();
+/// map.insert(1, "a");
+/// map.insert(2, "b");
+/// assert!(!map.spilled());
+///
+/// map.insert(3, "c");
+/// assert!(map.spilled());
+/// assert_eq!(map.get(1), Some(&"a"));
+///
+/// map.remove(3);
+/// map.shrink_to_fit();
+/// assert!(!map.spilled());
+/// assert_eq!(map.len(), 2);
+/// ```
+#[derive(Clone)]
+pub struct SmallIntMap<K, V, const N: usize> {
+    storage: Storage<K, V, N>,
+    // The load factor of the `IntMap` the pairs spill into, multiplied by 1000.
+    load_factor: usize,
+}
+
+#[derive(Clone)]
+enum Storage<K, V, const N: usize> {
+    // The first `len` slots are occupied, the others are `None`.
+    Inline {
+        pairs: [Option<(K, V)>; N],
+        len: usize,
+    },
+    Heap(IntMap<K, V>),
+}
+
+impl<K, V, const N: usize> SmallIntMap<K, V, N> {
+    /// Creates a new [`SmallIntMap`] that stores its pairs inline.
+    pub fn new() -> Self {
+        Self {
+            storage: Storage::Inline {
+                pairs: empty_pairs(),
+                len: 0,
+            },
+            load_factor: 909, // 90.9%
+        }
+    }
+
+    /// Returns true if the pairs were moved into an [`IntMap`].
+    pub fn spilled(&self) -> bool {
+        matches!(self.storage, Storage::Heap(_))
+    }
+}
+
+impl<K: IntKey, V, const N: usize> SmallIntMap<K, V, N> {
+    /// Creates a new [`SmallIntMap`] with space for at least the given number of pairs.
+    ///
+    /// A capacity above `N` allocates an [`IntMap`] right away.
+    pub fn with_capacity(capacity: usize) -> Self {
+        if capacity <= N {
+            Self::new()
+        } else {
+            IntMap::with_capacity(capacity).into()
+        }
+    }
+
+    /// Sets the load factor of the [`IntMap`] the pairs spill into, see
+    /// [`IntMap::set_load_factor`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::SmallIntMap;
+    ///
+    /// let mut map: SmallIntMap<u64, u64, 2> = SmallIntMap::new();
+    /// map.set_load_factor(0.5);
+    /// map.extend([(1, 1), (2, 2), (3, 3), (4, 4)]);
+    /// assert!(map.spilled());
+    /// assert_eq!(map.capacity(), 8);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the load factor is invalid, see [`SmallIntMap::try_set_load_factor`].
+    pub fn set_load_factor(&mut self, load_factor: f32) {
+        if let Err(err) = self.try_set_load_factor(load_factor) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Like [`SmallIntMap::set_load_factor`], but returns an error instead of panicking if the
+    /// load factor is invalid, see [`IntMap::try_set_load_factor`].
+    pub fn try_set_load_factor(&mut self, load_factor: f32) -> Result<(), ConfigError> {
+        match &mut self.storage {
+            Storage::Inline { .. } => self.load_factor = builder::load_factor_to_int(load_factor)?,
+            Storage::Heap(map) => {
+                map.try_set_load_factor(load_factor)?;
+                self.load_factor = map.load_factor;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the current load factor.
+    pub fn get_load_factor(&self) -> f32 {
+        self.load_factor as f32 / 1000.
+    }
+
+    /// Ensures that the map has space for at least `additional` more pairs.
+    pub fn reserve(&mut self, additional: usize) {
+        match &mut self.storage {
+            Storage::Inline { len, .. } if *len + additional <= N => {}
+            Storage::Inline { .. } => self.spill(additional),
+            Storage::Heap(map) => map.reserve(additional),
+        }
+    }
+
+    /// Inserts a key/value pair and returns the previous value if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::SmallIntMap;
+    ///
+    /// let mut map: SmallIntMap<u64, _, 4> = SmallIntMap::new();
+    /// assert_eq!(map.insert(21, "Eat my shorts"), None);
+    /// assert_eq!(map.insert(21, "Ay, caramba"), Some("Eat my shorts"));
+    /// assert_eq!(map.get(21), Some(&"Ay, caramba"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(pos) = self.position(key) {
+            let slot = &mut self.inline_pairs()[pos];
+            return slot.replace((key, value)).map(|kv| kv.1);
+        }
+
+        match &mut self.storage {
+            Storage::Heap(map) => map.insert(key, value),
+            Storage::Inline { pairs, len } if *len < N => {
+                pairs[*len] = Some((key, value));
+                *len += 1;
+                None
+            }
+            Storage::Inline { .. } => {
+                self.spill(1);
+                self.insert(key, value)
+            }
+        }
+    }
+
+    /// Inserts a key/value pair if the key is not yet inserted.
+    ///
+    /// This function returns true if key/value were inserted and false otherwise.
+    pub fn insert_checked(&mut self, key: K, value: V) -> bool {
+        if self.contains_key(key) {
+            return false;
+        }
+
+        self.insert(key, value);
+        true
+    }
+
+    /// Returns a reference to the value of the key if any.
+    pub fn get(&self, key: K) -> Option<&V> {
+        match &self.storage {
+            Storage::Inline { pairs, len } => {
+                let k = key.into_int();
+                pairs[..*len]
+                    .iter()
+                    .flatten()
+                    .find(|kv| kv.0.into_int() == k)
+                    .map(|kv| &kv.1)
+            }
+            Storage::Heap(map) => map.get(key),
+        }
+    }
+
+    /// Returns a mutable reference to the value of the key if any.
+    pub fn get_mut(&mut self, key: K) -> Option<&mut V> {
+        match &mut self.storage {
+            Storage::Inline { pairs, len } => {
+                let k = key.into_int();
+                pairs[..*len]
+                    .iter_mut()
+                    .flatten()
+                    .find(|kv| kv.0.into_int() == k)
+                    .map(|kv| &mut kv.1)
+            }
+            Storage::Heap(map) => map.get_mut(key),
+        }
+    }
+
+    /// Removes the key and returns its value if any.
+    ///
+    /// The map stays spilled, see [`SmallIntMap::shrink_to_fit`].
+    pub fn remove(&mut self, key: K) -> Option<V> {
+        match &mut self.storage {
+            Storage::Inline { pairs, len } => {
+                let k = key.into_int();
+                let pos = pairs[..*len]
+                    .iter()
+                    .position(|kv| matches!(kv, Some((key, _)) if key.into_int() == k))?;
+                *len -= 1;
+                pairs.swap(pos, *len);
+                pairs[*len].take().map(|kv| kv.1)
+            }
+            Storage::Heap(map) => map.remove(key),
+        }
+    }
+
+    /// Returns true if the key is present.
+    pub fn contains_key(&self, key: K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Retains only the key/value pairs specified by the predicate.
+    ///
+    /// In other words, remove all elements such that `f(key, &value)` returns false.
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(K, &V) -> bool,
+    {
+        self.retain_count(f);
+    }
+
+    /// Retains only the key/value pairs specified by the predicate and returns the number of
+    /// removed pairs.
+    pub fn retain_count<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut(K, &V) -> bool,
+    {
+        let len = self.len();
+        self.retain_mut(|k, v| f(k, v));
+        len - self.len()
+    }
+
+    /// Retains only the key/value pairs specified by the predicate, which can also modify the
+    /// values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::SmallIntMap;
+    ///
+    /// let mut map: SmallIntMap<u64, u64, 4> = [(1, 1), (2, 5), (3, 9)].into_iter().collect();
+    ///
+    /// // Decrement all values and remove the ones that reach zero.
+    /// map.retain_mut(|_, v| {
+    ///     *v -= 1;
+    ///     *v > 0
+    /// });
+    ///
+    /// assert_eq!(map, [(2, 4), (3, 8)].into_iter().collect());
+    /// ```
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(K, &mut V) -> bool,
+    {
+        let result: Result<(), std::convert::Infallible> = self.try_retain(|k, v| Ok(f(k, v)));
+        if let Err(never) = result {
+            match never {}
+        }
+    }
+
+    /// Like [`SmallIntMap::retain_mut`], but stops at the first error of the predicate and
+    /// returns it.
+    ///
+    /// The pairs that were already removed stay removed, and the remaining pairs are left
+    /// untouched.
+    pub fn try_retain<E, F>(&mut self, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(K, &mut V) -> Result<bool, E>,
+    {
+        match &mut self.storage {
+            Storage::Inline { pairs, len } => {
+                let mut pos = 0;
+                while pos < *len {
+                    let keep = match &mut pairs[pos] {
+                        Some((k, v)) => f(*k, v)?,
+                        None => true,
+                    };
+                    if keep {
+                        pos += 1;
+                    } else {
+                        *len -= 1;
+                        pairs.swap(pos, *len);
+                        pairs[*len] = None;
+                    }
+                }
+                Ok(())
+            }
+            Storage::Heap(map) => map.try_retain(f),
+        }
+    }
+
+    /// Gets the [`Entry`] that corresponds to the given key.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, N> {
+        if self.contains_key(key) {
+            Entry::Occupied(OccupiedEntry { map: self, key })
+        } else {
+            Entry::Vacant(VacantEntry { map: self, key })
+        }
+    }
+
+    /// Returns the number of pairs.
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            Storage::Inline { len, .. } => *len,
+            Storage::Heap(map) => map.len(),
+        }
+    }
+
+    /// Returns true if the map contains no pairs.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all pairs.
+    ///
+    /// A spilled map keeps its [`IntMap`] and the allocated memory.
+    pub fn clear(&mut self) {
+        match &mut self.storage {
+            Storage::Inline { pairs, len } => {
+                // Updated first, so the map is empty even if dropping a value panics.
+                let old_len = std::mem::take(len);
+                pairs[..old_len].iter_mut().for_each(|kv| *kv = None);
+            }
+            Storage::Heap(map) => map.clear(),
+        }
+    }
+
+    /// Returns the number of pairs the map can hold without allocating.
+    pub fn capacity(&self) -> usize {
+        match &self.storage {
+            Storage::Inline { .. } => N,
+            Storage::Heap(map) => map.capacity(),
+        }
+    }
+
+    /// Moves the pairs of a spilled map back inline if they fit, otherwise shrinks the
+    /// [`IntMap`].
+    pub fn shrink_to_fit(&mut self) {
+        match &mut self.storage {
+            Storage::Heap(map) if map.len() <= N => {
+                let mut pairs = empty_pairs();
+                let mut len = 0;
+                for (slot, kv) in pairs.iter_mut().zip(map.drain()) {
+                    *slot = Some(kv);
+                    len += 1;
+                }
+                self.storage = Storage::Inline { pairs, len };
+            }
+            Storage::Heap(map) => map.shrink_to_fit(),
+            Storage::Inline { .. } => {}
+        }
+    }
+
+    /// Returns an iterator over the pairs.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let inner = match &self.storage {
+            Storage::Inline { pairs, len } => IterInner::Inline(pairs[..*len].iter()),
+            Storage::Heap(map) => IterInner::Heap(map.iter()),
+        };
+        Iter { inner }
+    }
+
+    /// Returns an iterator over the pairs with mutable references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let inner = match &mut self.storage {
+            Storage::Inline { pairs, len } => IterMutInner::Inline(pairs[..*len].iter_mut()),
+            Storage::Heap(map) => IterMutInner::Heap(map.iter_mut()),
+        };
+        IterMut { inner }
+    }
+
+    /// Returns an iterator over the keys.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.iter() }
+    }
+
+    /// Returns an iterator over the values.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+
+    /// Returns an iterator over mutable references to the values.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            inner: self.iter_mut(),
+        }
+    }
+
+    /// Returns an iterator over all pairs that removes the pairs from the [`SmallIntMap`] during
+    /// iteration.
+    ///
+    /// If the iterator is dropped then all remaining pairs will be removed from the
+    /// [`SmallIntMap`]. A spilled map keeps its [`IntMap`] and the allocated memory.
+    pub fn drain(&mut self) -> Drain<'_, K, V, N> {
+        let inner = match &mut self.storage {
+            Storage::Inline { pairs, len } => {
+                // The pairs are moved out at once, so the map is empty even if the iterator is
+                // leaked.
+                let len = std::mem::take(len);
+                let pairs = std::mem::replace(pairs, empty_pairs());
+                DrainInner::Inline(pairs.into_iter().take(len))
+            }
+            Storage::Heap(map) => DrainInner::Heap(map.drain()),
+        };
+        Drain { inner }
+    }
+
+    // Returns the position of the key if it's stored inline.
+    fn position(&self, key: K) -> Option<usize> {
+        match &self.storage {
+            Storage::Inline { pairs, len } => {
+                let k = key.into_int();
+                pairs[..*len]
+                    .iter()
+                    .position(|kv| matches!(kv, Some((key, _)) if key.into_int() == k))
+            }
+            Storage::Heap(_) => None,
+        }
+    }
+
+    fn inline_pairs(&mut self) -> &mut [Option<(K, V)>] {
+        match &mut self.storage {
+            Storage::Inline { pairs, len } => &mut pairs[..*len],
+            Storage::Heap(_) => &mut [],
+        }
+    }
+
+    // Moves the inline pairs into an `IntMap` with space for `additional` more pairs.
+    fn spill(&mut self, additional: usize) {
+        if let Storage::Inline { pairs, len } = &mut self.storage {
+            let mut map = IntMap::new();
+            map.load_factor = self.load_factor;
+            map.reserve(*len + additional);
+            // Taken first, so the map is empty instead of inconsistent if inserting panics.
+            *len = 0;
+            let pairs = std::mem::replace(pairs, empty_pairs());
+            map.extend(pairs.into_iter().flatten());
+            self.storage = Storage::Heap(map);
+        }
+    }
+}
+
+// ***************** Set operations *********************
+
+impl<K: IntKey, V, const N: usize> SmallIntMap<K, V, N> {
+    /// Moves all pairs of `other` into `self` and combines the values of keys that are present in
+    /// both maps with `f(key, value_of_self, value_of_other)`, see [`IntMap::merge_with`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::SmallIntMap;
+    ///
+    /// let mut a: SmallIntMap<u64, u64, 4> = [(1, 10), (2, 20)].into_iter().collect();
+    /// let b: SmallIntMap<u64, u64, 4> = [(2, 2), (3, 3)].into_iter().collect();
+    /// a.merge_with(b, |_, a, b| a + b);
+    ///
+    /// assert_eq!(a, [(1, 10), (2, 22), (3, 3)].into_iter().collect());
+    /// ```
+    pub fn merge_with<F>(&mut self, other: SmallIntMap<K, V, N>, mut f: F)
+    where
+        F: FnMut(K, V, V) -> V,
+    {
+        match (&mut self.storage, other.spilled()) {
+            // Moves the `IntMap` out of `other` without rehashing.
+            (Storage::Heap(map), true) => map.merge_with(other.into(), f),
+            _ => {
+                for (key, value) in other {
+                    let value = match self.remove(key) {
+                        Some(old) => f(key, old, value),
+                        None => value,
+                    };
+                    self.insert(key, value);
+                }
+            }
+        }
+    }
+
+    /// Moves the pairs of `other` whose keys are absent in `self` into `self`, see
+    /// [`IntMap::union`].
+    pub fn union(&mut self, other: SmallIntMap<K, V, N>) {
+        match (&mut self.storage, other.spilled()) {
+            (Storage::Heap(map), true) => map.union(other.into()),
+            _ => {
+                for (key, value) in other {
+                    self.insert_checked(key, value);
+                }
+            }
+        }
+    }
+
+    /// Removes the keys that are present in both maps and moves the remaining pairs of `other`
+    /// into `self`, see [`IntMap::symmetric_difference`].
+    pub fn symmetric_difference(&mut self, other: SmallIntMap<K, V, N>) {
+        match (&mut self.storage, other.spilled()) {
+            (Storage::Heap(map), true) => map.symmetric_difference(other.into()),
+            _ => {
+                for (key, value) in other {
+                    if self.remove(key).is_none() {
+                        self.insert(key, value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Retains only the keys that are present in `other` and updates their values with
+    /// `f(key, value_of_self, value_of_other)`, see [`IntMap::intersection_with`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::SmallIntMap;
+    ///
+    /// let mut a: SmallIntMap<u64, u64, 4> = [(1, 10), (2, 20)].into_iter().collect();
+    /// let b: SmallIntMap<u64, u8, 4> = [(2, 2), (3, 3)].into_iter().collect();
+    /// a.intersection_with(&b, |_, a, &b| *a += b as u64);
+    ///
+    /// assert_eq!(a, [(2, 22)].into_iter().collect());
+    /// ```
+    pub fn intersection_with<W, F>(&mut self, other: &SmallIntMap<K, W, N>, mut f: F)
+    where
+        F: FnMut(K, &mut V, &W),
+    {
+        match (&mut self.storage, &other.storage) {
+            (Storage::Heap(map), Storage::Heap(other)) => map.intersection_with(other, f),
+            _ => self.retain_mut(|key, value| match other.get(key) {
+                Some(other_value) => {
+                    f(key, value, other_value);
+                    true
+                }
+                None => false,
+            }),
+        }
+    }
+
+    /// Retains only the keys that are present in `other`, see [`IntMap::retain_keys_in`].
+    pub fn retain_keys_in<W>(&mut self, other: &SmallIntMap<K, W, N>) {
+        match (&mut self.storage, &other.storage) {
+            (Storage::Heap(map), Storage::Heap(other)) => map.retain_keys_in(other),
+            _ => self.retain(|key, _| other.contains_key(key)),
+        }
+    }
+
+    /// Removes the keys that are present in `other`, see [`IntMap::difference`].
+    pub fn difference<W>(&mut self, other: &SmallIntMap<K, W, N>) {
+        match (&mut self.storage, &other.storage) {
+            (Storage::Heap(map), Storage::Heap(other)) => map.difference(other),
+            _ => self.retain(|key, _| !other.contains_key(key)),
+        }
+    }
+
+    /// Returns true if every pair of `self` is also present in `other` with an equal value, see
+    /// [`IntMap::is_submap_of`].
+    pub fn is_submap_of(&self, other: &SmallIntMap<K, V, N>) -> bool
+    where
+        V: PartialEq,
+    {
+        match (&self.storage, &other.storage) {
+            (Storage::Heap(map), Storage::Heap(other)) => map.is_submap_of(other),
+            _ => {
+                self.len() <= other.len()
+                    && self
+                        .iter()
+                        .all(|(key, value)| other.get(key) == Some(value))
+            }
+        }
+    }
+}
+
+fn empty_pairs<K, V, const N: usize>() -> [Option<(K, V)>; N] {
+    [(); N].map(|_| None)
+}
+
+impl<K, V, const N: usize> Default for SmallIntMap<K, V, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ***************** Equality *********************
+
+impl<K: IntKey, V: PartialEq, const N: usize> PartialEq for SmallIntMap<K, V, N> {
+    /// Compares the pairs regardless of their order and whether the maps are spilled.
+    fn eq(&self, other: &SmallIntMap<K, V, N>) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+impl<K: IntKey, V: Eq, const N: usize> Eq for SmallIntMap<K, V, N> {}
+
+// ***************** Debug *********************
+
+impl<K, V, const N: usize> Debug for SmallIntMap<K, V, N>
+where
+    K: IntKey + Debug,
+    V: Debug,
+{
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_map().entries(self.iter()).finish()
+    }
+}
+
+// ***************** Extend *********************
+
+impl<K: IntKey, V, const N: usize> Extend<(K, V)> for SmallIntMap<K, V, N> {
+    #[inline]
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+// ***************** FromIterator *********************
+
+impl<K: IntKey, V, const N: usize> FromIterator<(K, V)> for SmallIntMap<K, V, N> {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = SmallIntMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+// ***************** Conversion *********************
+
+impl<K: IntKey, V, const N: usize> From<IntMap<K, V>> for SmallIntMap<K, V, N> {
+    /// Keeps the [`IntMap`] without rehashing, even if its pairs would fit inline.
+    fn from(map: IntMap<K, V>) -> Self {
+        Self {
+            load_factor: map.load_factor,
+            storage: Storage::Heap(map),
+        }
+    }
+}
+
+impl<K: IntKey, V, const N: usize> From<SmallIntMap<K, V, N>> for IntMap<K, V> {
+    fn from(map: SmallIntMap<K, V, N>) -> Self {
+        match map.storage {
+            Storage::Inline { pairs, len } => {
+                let mut int_map = IntMap::with_capacity(len);
+                int_map.extend(pairs.into_iter().flatten());
+                int_map
+            }
+            Storage::Heap(map) => map,
+        }
+    }
+}
+
+// ***************** Entry *********************
+
+/// A view into a single entry in a [`SmallIntMap`], which may either be vacant or occupied.
+///
+/// The entry can be constructed by calling [`SmallIntMap::entry`] with a key.
+pub enum Entry<'a, K: IntKey, V, const N: usize> {
+    /// The entry is occupied.
+    Occupied(OccupiedEntry<'a, K, V, N>),
+    /// The entry is vacant.
+    Vacant(VacantEntry<'a, K, V, N>),
+}
+
+impl<'a, K: IntKey, V, const N: usize> Entry<'a, K, V, N> {
+    /// Ensures a value is in the entry by inserting the provided value if empty, and returns
+    /// a mutable reference to the value in the entry.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the result of the provided function if empty,
+    /// and returns a mutable reference to the value in the entry.
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Provides in-place mutable access to an occupied entry before any potential inserts.
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K: IntKey, V: Default, const N: usize> Entry<'a, K, V, N> {
+    /// Ensures a value is in the entry by inserting the default value if empty, and returns a
+    /// mutable reference to the value in the entry.
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(Default::default)
+    }
+}
+
+/// A view into an occupied entry in a [`SmallIntMap`]. It is part of the [`Entry`] enum.
+pub struct OccupiedEntry<'a, K: IntKey, V, const N: usize> {
+    map: &'a mut SmallIntMap<K, V, N>,
+    key: K,
+}
+
+impl<'a, K: IntKey, V, const N: usize> OccupiedEntry<'a, K, V, N> {
+    /// Gets a reference to the value in the entry.
+    pub fn get(&self) -> &V {
+        self.map.get(self.key).expect("occupied entry")
+    }
+
+    /// Gets a mutable reference to the value in the entry.
+    pub fn get_mut(&mut self) -> &mut V {
+        self.map.get_mut(self.key).expect("occupied entry")
+    }
+
+    /// Converts the entry into a mutable reference to its value.
+    pub fn into_mut(self) -> &'a mut V {
+        self.map.get_mut(self.key).expect("occupied entry")
+    }
+
+    /// Sets the value of the entry and returns the old value.
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the value out of the entry.
+    pub fn remove(self) -> V {
+        self.map.remove(self.key).expect("occupied entry")
+    }
+}
+
+/// A view into a vacant entry in a [`SmallIntMap`]. It is part of the [`Entry`] enum.
+pub struct VacantEntry<'a, K: IntKey, V, const N: usize> {
+    map: &'a mut SmallIntMap<K, V, N>,
+    key: K,
+}
+
+impl<'a, K: IntKey, V, const N: usize> VacantEntry<'a, K, V, N> {
+    /// Inserts the value and returns a mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.map.insert(self.key, value);
+        self.map.get_mut(self.key).expect("the pair was inserted")
+    }
+}
+
+// ***************** Iter *********************
+
+impl<'a, K: IntKey, V, const N: usize> IntoIterator for &'a SmallIntMap<K, V, N> {
+    type Item = (K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the pairs of a [`SmallIntMap`].
+///
+/// This struct is created by [`SmallIntMap::iter`].
+pub struct Iter<'a, K: IntKey, V> {
+    inner: IterInner<'a, K, V>,
+}
+
+enum IterInner<'a, K: IntKey, V> {
+    Inline(std::slice::Iter<'a, Option<(K, V)>>),
+    Heap(crate::Iter<'a, K, V>),
+}
+
+impl<'a, K: IntKey, V> Iterator for Iter<'a, K, V> {
+    type Item = (K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, &'a V)> {
+        match &mut self.inner {
+            IterInner::Inline(iter) => iter.next()?.as_ref().map(|(k, v)| (*k, v)),
+            IterInner::Heap(iter) => iter.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            IterInner::Inline(iter) => iter.size_hint(),
+            IterInner::Heap(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for Iter<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, &'a V)> {
+        match &mut self.inner {
+            IterInner::Inline(iter) => iter.next_back()?.as_ref().map(|(k, v)| (*k, v)),
+            IterInner::Heap(iter) => iter.next_back(),
+        }
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K: IntKey, V> FusedIterator for Iter<'a, K, V> {}
+
+impl<'a, K: IntKey, V> Clone for Iter<'a, K, V> {
+    fn clone(&self) -> Self {
+        let inner = match &self.inner {
+            IterInner::Inline(iter) => IterInner::Inline(iter.clone()),
+            IterInner::Heap(iter) => IterInner::Heap(iter.clone()),
+        };
+        Iter { inner }
+    }
+}
+
+impl<'a, K, V> Debug for Iter<'a, K, V>
+where
+    K: IntKey + Debug,
+    V: Debug,
+{
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_list().entries(self.clone()).finish()
+    }
+}
+
+// ***************** Iter Mut *********************
+
+impl<'a, K: IntKey, V, const N: usize> IntoIterator for &'a mut SmallIntMap<K, V, N> {
+    type Item = (K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// A mutable iterator over the pairs of a [`SmallIntMap`].
+///
+/// This struct is created by [`SmallIntMap::iter_mut`].
+pub struct IterMut<'a, K: IntKey, V> {
+    inner: IterMutInner<'a, K, V>,
+}
+
+enum IterMutInner<'a, K: IntKey, V> {
+    Inline(std::slice::IterMut<'a, Option<(K, V)>>),
+    Heap(crate::IterMut<'a, K, V>),
+}
+
+impl<'a, K: IntKey, V> Iterator for IterMut<'a, K, V> {
+    type Item = (K, &'a mut V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, &'a mut V)> {
+        match &mut self.inner {
+            IterMutInner::Inline(iter) => iter.next()?.as_mut().map(|(k, v)| (*k, v)),
+            IterMutInner::Heap(iter) => iter.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            IterMutInner::Inline(iter) => iter.size_hint(),
+            IterMutInner::Heap(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for IterMut<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, &'a mut V)> {
+        match &mut self.inner {
+            IterMutInner::Inline(iter) => iter.next_back()?.as_mut().map(|(k, v)| (*k, v)),
+            IterMutInner::Heap(iter) => iter.next_back(),
+        }
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for IterMut<'a, K, V> {}
+
+impl<'a, K: IntKey, V> FusedIterator for IterMut<'a, K, V> {}
+
+impl<'a, K: IntKey, V> Debug for IterMut<'a, K, V> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("IterMut")
+            .field("remaining", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+// ***************** Keys Iter *********************
+
+/// An iterator over the keys of a [`SmallIntMap`].
+///
+/// This struct is created by [`SmallIntMap::keys`].
+pub struct Keys<'a, K: IntKey, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K: IntKey, V> Iterator for Keys<'a, K, V> {
+    type Item = K;
+
+    #[inline]
+    fn next(&mut self) -> Option<K> {
+        self.inner.next().map(|kv| kv.0)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for Keys<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<K> {
+        self.inner.next_back().map(|kv| kv.0)
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for Keys<'a, K, V> {}
+
+impl<'a, K: IntKey, V> FusedIterator for Keys<'a, K, V> {}
+
+impl<'a, K: IntKey, V> Clone for Keys<'a, K, V> {
+    fn clone(&self) -> Self {
+        Keys {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, K: IntKey + Debug, V> Debug for Keys<'a, K, V> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_list().entries(self.clone()).finish()
+    }
+}
+
+// ***************** Values Iter *********************
+
+/// An iterator over the values of a [`SmallIntMap`].
+///
+/// This struct is created by [`SmallIntMap::values`].
+pub struct Values<'a, K: IntKey, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K: IntKey, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a V> {
+        self.inner.next().map(|kv| kv.1)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for Values<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a V> {
+        self.inner.next_back().map(|kv| kv.1)
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for Values<'a, K, V> {}
+
+impl<'a, K: IntKey, V> FusedIterator for Values<'a, K, V> {}
+
+impl<'a, K: IntKey, V> Clone for Values<'a, K, V> {
+    fn clone(&self) -> Self {
+        Values {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, K: IntKey, V: Debug> Debug for Values<'a, K, V> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_list().entries(self.clone()).finish()
+    }
+}
+
+// ***************** Values Mut Iter *********************
+
+/// A mutable iterator over the values of a [`SmallIntMap`].
+///
+/// This struct is created by [`SmallIntMap::values_mut`].
+pub struct ValuesMut<'a, K: IntKey, V> {
+    inner: IterMut<'a, K, V>,
+}
+
+impl<'a, K: IntKey, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a mut V> {
+        self.inner.next().map(|kv| kv.1)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for ValuesMut<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a mut V> {
+        self.inner.next_back().map(|kv| kv.1)
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for ValuesMut<'a, K, V> {}
+
+impl<'a, K: IntKey, V> FusedIterator for ValuesMut<'a, K, V> {}
+
+impl<'a, K: IntKey, V> Debug for ValuesMut<'a, K, V> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("ValuesMut")
+            .field("remaining", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+// ***************** Into Iter *********************
+
+impl<K: IntKey, V, const N: usize> IntoIterator for SmallIntMap<K, V, N> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let inner = match self.storage {
+            Storage::Inline { pairs, len } => IntoIterInner::Inline(pairs.into_iter().take(len)),
+            Storage::Heap(map) => IntoIterInner::Heap(map.into_iter()),
+        };
+        IntoIter { inner }
+    }
+}
+
+/// An owning iterator over the pairs of a [`SmallIntMap`].
+///
+/// This struct is created by [`SmallIntMap::into_iter`].
+pub struct IntoIter<K: IntKey, V, const N: usize> {
+    inner: IntoIterInner<K, V, N>,
+}
+
+enum IntoIterInner<K: IntKey, V, const N: usize> {
+    Inline(std::iter::Take<std::array::IntoIter<Option<(K, V)>, N>>),
+    Heap(crate::IntoIter<K, V>),
+}
+
+impl<K: IntKey, V, const N: usize> Iterator for IntoIter<K, V, N> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, V)> {
+        match &mut self.inner {
+            IntoIterInner::Inline(iter) => iter.next()?,
+            IntoIterInner::Heap(iter) => iter.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            IntoIterInner::Inline(iter) => iter.size_hint(),
+            IntoIterInner::Heap(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<K: IntKey, V, const N: usize> DoubleEndedIterator for IntoIter<K, V, N> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, V)> {
+        match &mut self.inner {
+            IntoIterInner::Inline(iter) => iter.next_back()?,
+            IntoIterInner::Heap(iter) => iter.next_back(),
+        }
+    }
+}
+
+impl<K: IntKey, V, const N: usize> ExactSizeIterator for IntoIter<K, V, N> {}
+
+impl<K: IntKey, V, const N: usize> FusedIterator for IntoIter<K, V, N> {}
+
+impl<K: IntKey, V, const N: usize> Debug for IntoIter<K, V, N> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("IntoIter")
+            .field("remaining", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+// ***************** Drain Iter *********************
+
+/// A draining iterator for [`SmallIntMap`].
+///
+/// This struct is created by [`SmallIntMap::drain`].
+pub struct Drain<'a, K: IntKey, V, const N: usize> {
+    inner: DrainInner<'a, K, V, N>,
+}
+
+enum DrainInner<'a, K: IntKey, V, const N: usize> {
+    Inline(std::iter::Take<std::array::IntoIter<Option<(K, V)>, N>>),
+    Heap(crate::Drain<'a, K, V>),
+}
+
+impl<'a, K: IntKey, V, const N: usize> Iterator for Drain<'a, K, V, N> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, V)> {
+        match &mut self.inner {
+            DrainInner::Inline(iter) => iter.next()?,
+            DrainInner::Heap(iter) => iter.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            DrainInner::Inline(iter) => iter.size_hint(),
+            DrainInner::Heap(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<'a, K: IntKey, V, const N: usize> DoubleEndedIterator for Drain<'a, K, V, N> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, V)> {
+        match &mut self.inner {
+            DrainInner::Inline(iter) => iter.next_back()?,
+            DrainInner::Heap(iter) => iter.next_back(),
+        }
+    }
+}
+
+impl<'a, K: IntKey, V, const N: usize> ExactSizeIterator for Drain<'a, K, V, N> {}
+
+impl<'a, K: IntKey, V, const N: usize> FusedIterator for Drain<'a, K, V, N> {}
+
+impl<'a, K: IntKey, V, const N: usize> Debug for Drain<'a, K, V, N> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("Drain")
+            .field("remaining", &self.len())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,858 @@
+//! A map with integer based keys that stores keys and values in separate arrays, see
+//! [`IntSoaMap`].
+
+use std::iter::FromIterator;
+use std::slice::Iter as SliceIter;
+use std::slice::IterMut as SliceIterMut;
+use std::vec::IntoIter as VecIntoIter;
+
+use crate::builder::{self, Hashing};
+use crate::{ConfigError, IntKey, IntMap};
+
+// Marks the end of a chain in `IntSoaMap::heads` and `IntSoaMap::next`.
+const NONE: u32 = u32::MAX;
+
+/// A map with integer based keys that stores its keys and values in separate arrays.
+///
+/// [`IntMap`] stores `(K, V)` tuples in its slots, so every key scan also pulls the values into
+/// the CPU caches, which gets expensive when `V` is large. This map stores all keys in one array
+/// and all values at the same positions in a second array. Every slot only holds the position
+/// of its first pair, and every pair the position of the next pair in the same slot.
+/// [`IntSoaMap::contains_key`], [`IntSoaMap::keys`] and [`IntSoaMap::retain_keys`] never touch
+/// the values, while [`IntSoaMap::get`] still returns `&V`. Growing the map only rebuilds the
+/// positions, the pairs are never moved.
+///
+/// The slots are computed like in [`IntMap`], and [`IntMapBuilder::build_soa`] configures the
+/// map like an [`IntMap`]. The map holds at most `u32::MAX - 1` pairs, and the order of the pairs
+/// is unspecified.
+///
+/// [`IntMapBuilder::build_soa`]: crate::IntMapBuilder::build_soa
+///
+/// # Examples
+///
+/// ```
+/// use intmap::IntSoaMap;
+///
+/// let mut map: IntSoaMap<u64, [u8; 256]> = IntSoaMap::new();
+/// map.insert(1, [1; 256]);
+/// map.insert(2, [2; 256]);
+/// map.insert(3, [3; 256]);
+///
+/// assert_eq!(map.get(2), Some(&[2; 256]));
+/// assert_eq!(map.keys().sum::<u64>(), 6);
+///
+/// map.retain_keys(|k| k % 2 == 1);
+/// assert_eq!(map.len(), 2);
+/// assert!(!map.contains_key(2));
+/// ```
+#[derive(Clone)]
+pub struct IntSoaMap<K, V> {
+    // The keys of all pairs, at the same positions as their values in `values`.
+    keys: Vec<K>,
+    // The values of all pairs, at the same positions as their keys in `keys`.
+    values: Vec<V>,
+    // For every pair the position of the next pair in the same slot, or `NONE`.
+    next: Vec<u32>,
+    // For every slot the position of its first pair, or `NONE`.
+    heads: Vec<u32>,
+    // The number of times the slots grew from zero, see `Hashing::table_len`.
+    size: u32,
+    // The number of slots minus one, see `Hashing::calc_index`.
+    mod_mask: usize,
+    // The load factor, multiplied by 1000.
+    pub(crate) load_factor: usize,
+    // The load factor below which the slots shrink, multiplied by 1000, or 0 to never shrink.
+    pub(crate) shrink_load_factor: usize,
+    pub(crate) hashing: Hashing,
+}
+
+impl<K, V> IntSoaMap<K, V> {
+    /// Creates a new [`IntSoaMap`].
+    ///
+    /// The [`IntSoaMap`] is initially created with a capacity of 0, so it will not allocate
+    /// until it is first inserted into.
+    pub const fn new() -> Self {
+        Self {
+            keys: Vec::new(),
+            values: Vec::new(),
+            next: Vec::new(),
+            heads: Vec::new(),
+            size: 0,
+            mod_mask: 0,
+            load_factor: 909, // 90.9%
+            shrink_load_factor: 0,
+            hashing: Hashing::Prime,
+        }
+    }
+}
+
+impl<K: IntKey, V> IntSoaMap<K, V> {
+    /// Creates a new [`IntSoaMap`] with at least the given capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut map = Self::new();
+        map.reserve(capacity);
+        map
+    }
+
+    /// Sets the load factor of the [`IntSoaMap`], see [`IntMap::set_load_factor`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the load factor is invalid, see [`IntSoaMap::try_set_load_factor`].
+    pub fn set_load_factor(&mut self, load_factor: f32) {
+        if let Err(err) = self.try_set_load_factor(load_factor) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Like [`IntSoaMap::set_load_factor`], but returns an error instead of panicking if the load
+    /// factor is invalid, see [`IntMap::try_set_load_factor`].
+    pub fn try_set_load_factor(&mut self, load_factor: f32) -> Result<(), ConfigError> {
+        let int = builder::load_factor_to_int(load_factor)?;
+        if self.shrink_load_factor * 2 > int {
+            return Err(ConfigError::InvalidShrinkLoadFactor {
+                shrink_load_factor: self.shrink_load_factor as f32 / 1000.,
+                load_factor,
+            });
+        }
+
+        self.load_factor = int;
+        if !self.heads.is_empty() {
+            self.increase_slots_if_needed();
+        }
+        Ok(())
+    }
+
+    /// Returns the current load factor.
+    pub fn get_load_factor(&self) -> f32 {
+        self.load_factor as f32 / 1000.
+    }
+
+    /// Ensures that the map has at least `additional` more slots and space for as many pairs.
+    pub fn reserve(&mut self, additional: usize) {
+        let capacity = self.len() + additional;
+        let mut size = self.size;
+        while self.hashing.table_len(size) < capacity {
+            size += 1;
+        }
+        if size != self.size {
+            self.resize(size);
+        }
+
+        self.keys.reserve(additional);
+        self.values.reserve(additional);
+        self.next.reserve(additional);
+    }
+
+    /// Shrinks the capacity as much as possible without exceeding the load factor.
+    ///
+    /// If the [`IntSoaMap`] is empty, all memory is released.
+    pub fn shrink_to_fit(&mut self) {
+        if self.is_empty() {
+            self.keys = Vec::new();
+            self.values = Vec::new();
+            self.next = Vec::new();
+            self.heads = Vec::new();
+            self.size = 0;
+            self.mod_mask = 0;
+            return;
+        }
+
+        let mut size = self.size;
+        while size > 1 && (self.len() * 1000) / self.hashing.table_len(size - 1) <= self.load_factor
+        {
+            size -= 1;
+        }
+        if size != self.size {
+            self.resize(size);
+        }
+
+        self.keys.shrink_to_fit();
+        self.values.shrink_to_fit();
+        self.next.shrink_to_fit();
+    }
+
+    /// Inserts a key/value pair and returns the previous value if any.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.find(key) {
+            Some(pos) => Some(std::mem::replace(&mut self.values[pos], value)),
+            None => {
+                self.push(key, value);
+                None
+            }
+        }
+    }
+
+    /// Inserts a key/value pair if the key is not yet inserted.
+    ///
+    /// This function returns true if key/value were inserted and false otherwise.
+    pub fn insert_checked(&mut self, key: K, value: V) -> bool {
+        if self.find(key).is_some() {
+            return false;
+        }
+
+        self.push(key, value);
+        true
+    }
+
+    /// Returns the value of the key.
+    pub fn get(&self, key: K) -> Option<&V> {
+        let pos = self.find(key)?;
+        Some(&self.values[pos])
+    }
+
+    /// Returns a mutable reference to the value of the key.
+    pub fn get_mut(&mut self, key: K) -> Option<&mut V> {
+        let pos = self.find(key)?;
+        Some(&mut self.values[pos])
+    }
+
+    /// Returns true if the map contains the key.
+    ///
+    /// This doesn't touch the values.
+    pub fn contains_key(&self, key: K) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Removes the key and returns its value.
+    pub fn remove(&mut self, key: K) -> Option<V> {
+        let pos = self.find(key)?;
+        let value = self.remove_at(pos);
+        self.decrease_slots_if_needed();
+        Some(value)
+    }
+
+    /// Retains only the pairs for which `f(key, value)` returns true.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(K, &V) -> bool,
+    {
+        let mut pos = 0;
+        while pos < self.len() {
+            if f(self.keys[pos], &self.values[pos]) {
+                pos += 1;
+            } else {
+                // Moves the last pair to `pos`, which is checked next.
+                self.remove_at(pos);
+            }
+        }
+
+        self.decrease_slots_if_needed();
+    }
+
+    /// Retains only the pairs for which `f(key)` returns true.
+    ///
+    /// Unlike [`IntSoaMap::retain`], this only reads the keys.
+    pub fn retain_keys<F>(&mut self, mut f: F)
+    where
+        F: FnMut(K) -> bool,
+    {
+        let mut pos = 0;
+        while pos < self.len() {
+            if f(self.keys[pos]) {
+                pos += 1;
+            } else {
+                self.remove_at(pos);
+            }
+        }
+
+        self.decrease_slots_if_needed();
+    }
+
+    /// Gets the [`Entry`] that corresponds to the given key.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        match self.find(key) {
+            Some(pos) => Entry::Occupied(OccupiedEntry { map: self, pos }),
+            None => Entry::Vacant(VacantEntry { map: self, key }),
+        }
+    }
+
+    /// Returns the number of pairs.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns true if the map contains no pairs.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Removes all pairs, keeping the allocated memory.
+    pub fn clear(&mut self) {
+        self.heads.iter_mut().for_each(|head| *head = NONE);
+        self.keys.clear();
+        self.next.clear();
+        // Cleared last, `Vec::clear` empties the values even if dropping one panics.
+        self.values.clear();
+    }
+
+    /// Returns the number of slots.
+    pub fn capacity(&self) -> usize {
+        self.heads.len()
+    }
+
+    /// Returns an iterator over the pairs.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            keys: self.keys.iter(),
+            values: self.values.iter(),
+        }
+    }
+
+    /// Returns an iterator over the keys and mutable references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            keys: self.keys.iter(),
+            values: self.values.iter_mut(),
+        }
+    }
+
+    /// Returns an iterator over the keys.
+    ///
+    /// This doesn't touch the values.
+    pub fn keys(&self) -> Keys<'_, K> {
+        Keys {
+            inner: self.keys.iter(),
+        }
+    }
+
+    /// Returns an iterator over the values.
+    pub fn values(&self) -> Values<'_, V> {
+        Values {
+            inner: self.values.iter(),
+        }
+    }
+
+    /// Returns an iterator over mutable references to the values.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, V> {
+        ValuesMut {
+            inner: self.values.iter_mut(),
+        }
+    }
+
+    //**** Internal hash stuff *****
+
+    #[inline(always)]
+    fn calc_index(&self, k: K::Int) -> usize {
+        self.hashing
+            .calc_index(k, K::PRIME, self.size, self.mod_mask)
+    }
+
+    // Returns the position of the key in `keys` and `values`.
+    #[inline]
+    fn find(&self, key: K) -> Option<usize> {
+        if self.heads.is_empty() {
+            return None;
+        }
+
+        let k = key.into_int();
+        let mut pos = self.heads[self.calc_index(k)];
+        while pos != NONE {
+            if self.keys[pos as usize].into_int() == k {
+                return Some(pos as usize);
+            }
+            pos = self.next[pos as usize];
+        }
+        None
+    }
+
+    // Inserts a pair whose key is missing and returns its position.
+    fn push(&mut self, key: K, value: V) -> usize {
+        let pos = self.len();
+        assert!(pos < NONE as usize, "capacity overflow");
+        self.increase_slots_if_needed();
+
+        let ix = self.calc_index(key.into_int());
+        self.keys.push(key);
+        self.values.push(value);
+        self.next.push(self.heads[ix]);
+        self.heads[ix] = pos as u32;
+        pos
+    }
+
+    // Removes the pair at `pos` and moves the last pair into its place.
+    fn remove_at(&mut self, pos: usize) -> V {
+        let next = self.next[pos];
+        *self.link_to(pos) = next;
+
+        let last = self.len() - 1;
+        if pos != last {
+            *self.link_to(last) = pos as u32;
+        }
+
+        self.keys.swap_remove(pos);
+        self.next.swap_remove(pos);
+        self.values.swap_remove(pos)
+    }
+
+    // Returns the head or `next` entry that holds `pos`.
+    fn link_to(&mut self, pos: usize) -> &mut u32 {
+        let ix = self.calc_index(self.keys[pos].into_int());
+        if self.heads[ix] as usize == pos {
+            return &mut self.heads[ix];
+        }
+
+        let mut prev = self.heads[ix] as usize;
+        while self.next[prev] as usize != pos {
+            prev = self.next[prev] as usize;
+        }
+        &mut self.next[prev]
+    }
+
+    fn increase_slots_if_needed(&mut self) {
+        let mut size = self.size;
+        while crate::exceeds_load_factor(self.len(), self.hashing.table_len(size), self.load_factor)
+        {
+            size += 1;
+        }
+        if size != self.size {
+            self.resize(size);
+        }
+    }
+
+    fn decrease_slots_if_needed(&mut self) {
+        if self.shrink_load_factor == 0 {
+            return;
+        }
+
+        let mut size = self.size;
+        let mut len = self.heads.len();
+        while size > 1 && (self.len() * 1000) / len < self.shrink_load_factor {
+            size -= 1;
+            len = self.hashing.table_len(size);
+        }
+        if size != self.size {
+            self.resize(size);
+        }
+    }
+
+    // Rebuilds the chains for `Hashing::table_len(size)` slots. The pairs stay in place.
+    pub(crate) fn resize(&mut self, size: u32) {
+        let len = self.hashing.table_len(size);
+        let mod_mask = len - 1;
+
+        // Built aside, so the map is unchanged if `IntKey::into_int` panics.
+        let mut heads = vec![NONE; len];
+        let mut next = Vec::with_capacity(self.next.capacity());
+        for (pos, key) in self.keys.iter().enumerate() {
+            let ix = self
+                .hashing
+                .calc_index(key.into_int(), K::PRIME, size, mod_mask);
+            next.push(heads[ix]);
+            heads[ix] = pos as u32;
+        }
+
+        self.heads = heads;
+        self.next = next;
+        self.size = size;
+        self.mod_mask = mod_mask;
+    }
+}
+
+impl<K, V> Default for IntSoaMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ***************** Equality *********************
+
+impl<K: IntKey, V: PartialEq> PartialEq for IntSoaMap<K, V> {
+    /// Compares the pairs regardless of their order, like [`IntMap`] does.
+    fn eq(&self, other: &IntSoaMap<K, V>) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+impl<K: IntKey, V: Eq> Eq for IntSoaMap<K, V> {}
+
+// ***************** Debug *********************
+
+impl<K, V> std::fmt::Debug for IntSoaMap<K, V>
+where
+    K: IntKey + std::fmt::Debug,
+    V: std::fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_map().entries(self.iter()).finish()
+    }
+}
+
+// ***************** Extend *********************
+
+impl<K: IntKey, V> Extend<(K, V)> for IntSoaMap<K, V> {
+    #[inline]
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+// ***************** FromIterator *********************
+
+impl<K: IntKey, V> FromIterator<(K, V)> for IntSoaMap<K, V> {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = IntSoaMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+// ***************** Conversion *********************
+
+impl<K: IntKey, V> From<IntMap<K, V>> for IntSoaMap<K, V> {
+    fn from(map: IntMap<K, V>) -> Self {
+        let mut soa_map = IntSoaMap::with_capacity(map.len());
+        soa_map.extend(map);
+        soa_map
+    }
+}
+
+impl<K: IntKey, V> From<IntSoaMap<K, V>> for IntMap<K, V> {
+    fn from(map: IntSoaMap<K, V>) -> Self {
+        let mut int_map = IntMap::with_capacity(map.len());
+        int_map.extend(map);
+        int_map
+    }
+}
+
+// ***************** Entry *********************
+
+/// A view into a single entry in a [`IntSoaMap`], which may either be vacant or occupied.
+///
+/// The entry can be constructed by calling [`IntSoaMap::entry`] with a key.
+pub enum Entry<'a, K: IntKey, V> {
+    /// The entry is occupied.
+    Occupied(OccupiedEntry<'a, K, V>),
+    /// The entry is vacant.
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+impl<'a, K: IntKey, V> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting the provided value if empty, and returns
+    /// a mutable reference to the value in the entry.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the result of the provided function if empty,
+    /// and returns a mutable reference to the value in the entry.
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Provides in-place mutable access to an occupied entry before any potential inserts.
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K: IntKey, V: Default> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting the default value if empty, and returns a
+    /// mutable reference to the value in the entry.
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(Default::default)
+    }
+}
+
+/// A view into an occupied entry in a [`IntSoaMap`]. It is part of the [`Entry`] enum.
+pub struct OccupiedEntry<'a, K: IntKey, V> {
+    map: &'a mut IntSoaMap<K, V>,
+    pos: usize,
+}
+
+impl<'a, K: IntKey, V> OccupiedEntry<'a, K, V> {
+    /// Gets a reference to the value in the entry.
+    pub fn get(&self) -> &V {
+        &self.map.values[self.pos]
+    }
+
+    /// Gets a mutable reference to the value in the entry.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.values[self.pos]
+    }
+
+    /// Converts the entry into a mutable reference to its value.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.values[self.pos]
+    }
+
+    /// Sets the value of the entry and returns the old value.
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the value out of the entry.
+    pub fn remove(self) -> V {
+        let value = self.map.remove_at(self.pos);
+        self.map.decrease_slots_if_needed();
+        value
+    }
+}
+
+/// A view into a vacant entry in a [`IntSoaMap`]. It is part of the [`Entry`] enum.
+pub struct VacantEntry<'a, K: IntKey, V> {
+    map: &'a mut IntSoaMap<K, V>,
+    key: K,
+}
+
+impl<'a, K: IntKey, V> VacantEntry<'a, K, V> {
+    /// Inserts the value and returns a mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let pos = self.map.push(self.key, value);
+        &mut self.map.values[pos]
+    }
+}
+
+// ***************** Iter *********************
+
+impl<'a, K: IntKey, V> IntoIterator for &'a IntSoaMap<K, V> {
+    type Item = (K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the pairs of a [`IntSoaMap`].
+///
+/// This struct is created by [`IntSoaMap::iter`].
+#[derive(Clone, Debug)]
+pub struct Iter<'a, K, V> {
+    keys: SliceIter<'a, K>,
+    values: SliceIter<'a, V>,
+}
+
+impl<'a, K: IntKey, V> Iterator for Iter<'a, K, V> {
+    type Item = (K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, &'a V)> {
+        self.keys.next().copied().zip(self.values.next())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for Iter<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, &'a V)> {
+        self.keys.next_back().copied().zip(self.values.next_back())
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for Iter<'a, K, V> {}
+impl<'a, K: IntKey, V> std::iter::FusedIterator for Iter<'a, K, V> {}
+
+// ***************** Iter Mut *********************
+
+impl<'a, K: IntKey, V> IntoIterator for &'a mut IntSoaMap<K, V> {
+    type Item = (K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// A mutable iterator over the pairs of a [`IntSoaMap`].
+///
+/// This struct is created by [`IntSoaMap::iter_mut`].
+#[derive(Debug)]
+pub struct IterMut<'a, K, V> {
+    keys: SliceIter<'a, K>,
+    values: SliceIterMut<'a, V>,
+}
+
+impl<'a, K: IntKey, V> Iterator for IterMut<'a, K, V> {
+    type Item = (K, &'a mut V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, &'a mut V)> {
+        self.keys.next().copied().zip(self.values.next())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for IterMut<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, &'a mut V)> {
+        self.keys.next_back().copied().zip(self.values.next_back())
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for IterMut<'a, K, V> {}
+impl<'a, K: IntKey, V> std::iter::FusedIterator for IterMut<'a, K, V> {}
+
+// ***************** Keys Iter *********************
+
+/// An iterator over the keys of a [`IntSoaMap`], which doesn't touch the values.
+///
+/// This struct is created by [`IntSoaMap::keys`].
+#[derive(Clone, Debug)]
+pub struct Keys<'a, K> {
+    inner: SliceIter<'a, K>,
+}
+
+impl<'a, K: IntKey> Iterator for Keys<'a, K> {
+    type Item = K;
+
+    #[inline]
+    fn next(&mut self) -> Option<K> {
+        self.inner.next().copied()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K: IntKey> DoubleEndedIterator for Keys<'a, K> {
+    #[inline]
+    fn next_back(&mut self) -> Option<K> {
+        self.inner.next_back().copied()
+    }
+}
+
+impl<'a, K: IntKey> ExactSizeIterator for Keys<'a, K> {}
+impl<'a, K: IntKey> std::iter::FusedIterator for Keys<'a, K> {}
+
+// ***************** Values Iter *********************
+
+/// An iterator over the values of a [`IntSoaMap`].
+///
+/// This struct is created by [`IntSoaMap::values`].
+#[derive(Clone, Debug)]
+pub struct Values<'a, V> {
+    inner: SliceIter<'a, V>,
+}
+
+impl<'a, V> Iterator for Values<'a, V> {
+    type Item = &'a V;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a V> {
+        self.inner.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, V> DoubleEndedIterator for Values<'a, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a V> {
+        self.inner.next_back()
+    }
+}
+
+impl<'a, V> ExactSizeIterator for Values<'a, V> {}
+impl<'a, V> std::iter::FusedIterator for Values<'a, V> {}
+
+// ***************** Values Mut Iter *********************
+
+/// A mutable iterator over the values of a [`IntSoaMap`].
+///
+/// This struct is created by [`IntSoaMap::values_mut`].
+#[derive(Debug)]
+pub struct ValuesMut<'a, V> {
+    inner: SliceIterMut<'a, V>,
+}
+
+impl<'a, V> Iterator for ValuesMut<'a, V> {
+    type Item = &'a mut V;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a mut V> {
+        self.inner.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, V> DoubleEndedIterator for ValuesMut<'a, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a mut V> {
+        self.inner.next_back()
+    }
+}
+
+impl<'a, V> ExactSizeIterator for ValuesMut<'a, V> {}
+impl<'a, V> std::iter::FusedIterator for ValuesMut<'a, V> {}
+
+// ***************** Into Iter *********************
+
+impl<K: IntKey, V> IntoIterator for IntSoaMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            keys: self.keys.into_iter(),
+            values: self.values.into_iter(),
+        }
+    }
+}
+
+/// An owning iterator over the pairs of a [`IntSoaMap`].
+///
+/// This struct is created by [`IntSoaMap::into_iter`].
+#[derive(Debug)]
+pub struct IntoIter<K, V> {
+    keys: VecIntoIter<K>,
+    values: VecIntoIter<V>,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, V)> {
+        self.keys.next().zip(self.values.next())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, V)> {
+        self.keys.next_back().zip(self.values.next_back())
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+impl<K, V> std::iter::FusedIterator for IntoIter<K, V> {}
@@ -0,0 +1,86 @@
+/// Statistics about the distribution of keys over the slots of an [`IntMap`].
+///
+/// Created by [`IntMap::stats`]. Useful for choosing [`IntKey::PRIME`] and the load factor for a
+/// given key distribution.
+///
+/// The probe lengths count the key comparisons of a lookup. For misses it's assumed that the
+/// missing keys are distributed uniformly over all slots.
+///
+/// [`IntMap`]: crate::IntMap
+/// [`IntMap::stats`]: crate::IntMap::stats
+/// [`IntKey::PRIME`]: crate::IntKey::PRIME
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct MapStats {
+    /// The number of key/value pairs.
+    pub len: usize,
+    /// The number of slots.
+    pub capacity: usize,
+    /// The number of slots by the number of key/value pairs they contain, e.g. `histogram[2]` is
+    /// the number of slots with exactly two pairs.
+    pub histogram: Vec<usize>,
+    /// The largest number of key/value pairs in a single slot.
+    pub max_chain_len: usize,
+    /// The average number of key comparisons for looking up a present key.
+    pub avg_probe_hit: f64,
+    /// The average number of key comparisons for looking up a missing key.
+    pub avg_probe_miss: f64,
+    /// The ratio between empty slots and all slots.
+    pub empty_slot_ratio: f64,
+}
+
+impl MapStats {
+    pub(crate) fn new(chain_lens: impl Iterator<Item = usize>) -> Self {
+        let mut histogram = Vec::new();
+        let mut len = 0;
+        let mut capacity = 0;
+        let mut hit_probes = 0;
+
+        for chain_len in chain_lens {
+            if histogram.len() <= chain_len {
+                histogram.resize(chain_len + 1, 0);
+            }
+            histogram[chain_len] += 1;
+
+            len += chain_len;
+            capacity += 1;
+            // The i-th key of a slot needs i comparisons.
+            hit_probes += chain_len * (chain_len + 1) / 2;
+        }
+
+        let ratio = |a: usize, b: usize| if b == 0 { 0.0 } else { a as f64 / b as f64 };
+
+        Self {
+            len,
+            capacity,
+            max_chain_len: histogram.len().saturating_sub(1),
+            avg_probe_hit: ratio(hit_probes, len),
+            avg_probe_miss: ratio(len, capacity),
+            empty_slot_ratio: ratio(histogram.first().copied().unwrap_or(0), capacity),
+            histogram,
+        }
+    }
+
+    /// Returns the number of slots that contain more than one key/value pair.
+    pub fn colliding_slots(&self) -> usize {
+        self.histogram.iter().skip(2).sum()
+    }
+
+    /// Estimates the average cost of a lookup where `hit_ratio` is the share of lookups for
+    /// present keys, between 0.0 and 1.0.
+    ///
+    /// The cost is the number of memory indirections plus key comparisons: finding the slot costs
+    /// one, looking into a non-empty slot costs another one, and every comparison costs one.
+    pub fn estimated_lookup_cost(&self, hit_ratio: f64) -> f64 {
+        if self.capacity == 0 {
+            return 0.0;
+        }
+
+        let non_empty_ratio = 1.0 - self.empty_slot_ratio;
+        let hit_cost = 2.0 + self.avg_probe_hit;
+        let miss_cost = 1.0 + non_empty_ratio + self.avg_probe_miss;
+
+        let hit_ratio = hit_ratio.clamp(0.0, 1.0);
+        hit_ratio * hit_cost + (1.0 - hit_ratio) * miss_cost
+    }
+}
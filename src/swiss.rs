@@ -0,0 +1,1290 @@
+//! A map with integer based keys and SwissTable-style control bytes, see [`IntSwissMap`].
+
+use std::iter::{Flatten, FromIterator};
+
+use crate::int::SealedInt;
+use crate::{ConfigError, IntKey, IntMap};
+
+// The number of slots described by one word of control bytes.
+const GROUP: usize = 8;
+// Control byte of a slot that was never used since the last rebuild.
+const EMPTY: u8 = 0xFF;
+// Control byte of a slot whose pair was removed. Lookups must probe past it.
+const DELETED: u8 = 0x80;
+const LSB: u64 = 0x0101_0101_0101_0101;
+const MSB: u64 = 0x8080_8080_8080_8080;
+// 2^64 divided by the golden ratio.
+const GOLDEN: u64 = 0x9E37_79B9_7F4A_7C15;
+// Leaves at least one in eight slots `EMPTY`, multiplied by 1000 like `IntMap::load_factor`.
+const MAX_LOAD_FACTOR: usize = 875;
+
+/// A hashmap with integer based keys that is optimized for looking up missing keys.
+///
+/// Unlike [`IntMap`], which chains colliding pairs in a `Vec` per slot, the pairs are stored in a
+/// single open addressed table. Every slot has a control byte with the 7 highest bits of the
+/// hash of its key, and lookups compare 8 control bytes at once within a `u64`. A missing key is
+/// usually rejected after reading a single word, without touching any keys.
+///
+/// The API follows the one of [`IntMap`], including the set operations. Only the statistics,
+/// the cursor and the shrink policy are missing. The load factor is capped at 0.875, so every
+/// lookup finds an `EMPTY` slot eventually.
+///
+/// # Examples
+///
+/// ```
+/// use intmap::IntSwissMap;
+///
+/// let mut map: IntSwissMap<u64, &str> = IntSwissMap::new();
+/// map.insert(21, "Eat my shorts");
+/// map.insert(42, "Ay, caramba");
+///
+/// assert_eq!(map.get(21), Some(&"Eat my shorts"));
+/// assert!(!map.contains_key(7));
+/// assert_eq!(map.remove(42), Some("Ay, caramba"));
+/// assert_eq!(map.len(), 1);
+/// ```
+#[derive(Clone)]
+pub struct IntSwissMap<K, V> {
+    // One word per group of `GROUP` slots. Byte `i` of a word describes slot `i` of the group
+    // and is `EMPTY`, `DELETED` or the 7 highest bits of the hash of the key in that slot.
+    ctrl: Vec<u64>,
+    // The slots, `GROUP` for every word in `ctrl`.
+    slots: Vec<Option<(K, V)>>,
+    // The number of groups as binary exponent.
+    group_bits: u32,
+    // The number of stored key/value pairs.
+    count: usize,
+    // How many `EMPTY` slots can still be filled before the table must be rebuilt.
+    growth_left: usize,
+    // The maximum share of full and `DELETED` slots, multiplied by 1000.
+    load_factor: usize,
+}
+
+impl<K, V> IntSwissMap<K, V> {
+    /// Creates a new [`IntSwissMap`].
+    ///
+    /// The [`IntSwissMap`] is initially created with a capacity of 0, so it will not allocate
+    /// until it is first inserted into.
+    pub const fn new() -> Self {
+        Self {
+            ctrl: Vec::new(),
+            slots: Vec::new(),
+            group_bits: 0,
+            count: 0,
+            growth_left: 0,
+            load_factor: MAX_LOAD_FACTOR,
+        }
+    }
+}
+
+impl<K: IntKey, V> IntSwissMap<K, V> {
+    /// Creates a new [`IntSwissMap`] with space for at least the given number of pairs.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut map = Self::new();
+        map.reserve(capacity);
+        map
+    }
+
+    /// Sets the load factor of the [`IntSwissMap`].
+    ///
+    /// A lower load factor makes the probe sequences shorter but uses more space. Load factors
+    /// above 0.875 are lowered to 0.875.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntSwissMap;
+    ///
+    /// let mut map: IntSwissMap<u64, u64> = IntSwissMap::new();
+    /// map.set_load_factor(0.5);
+    /// assert_eq!(map.get_load_factor(), 0.5);
+    /// map.set_load_factor(1.0);
+    /// assert_eq!(map.get_load_factor(), 0.875);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the load factor is invalid, see [`IntSwissMap::try_set_load_factor`].
+    pub fn set_load_factor(&mut self, load_factor: f32) {
+        if let Err(err) = self.try_set_load_factor(load_factor) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Like [`IntSwissMap::set_load_factor`], but returns an error instead of panicking if the
+    /// load factor is invalid.
+    ///
+    /// The load factor must be a finite number of at least 0.001. The map is unchanged if an
+    /// error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::{ConfigError, IntSwissMap};
+    ///
+    /// let mut map: IntSwissMap<u64, u64> = IntSwissMap::new();
+    /// assert_eq!(
+    ///     map.try_set_load_factor(0.0),
+    ///     Err(ConfigError::InvalidLoadFactor { load_factor: 0.0 })
+    /// );
+    /// assert_eq!(map.try_set_load_factor(0.5), Ok(()));
+    /// ```
+    pub fn try_set_load_factor(&mut self, load_factor: f32) -> Result<(), ConfigError> {
+        let int = crate::builder::load_factor_to_int(load_factor)?;
+        self.load_factor = int.min(MAX_LOAD_FACTOR);
+
+        // `growth_left` depends on the load factor, so the table is rebuilt.
+        if !self.ctrl.is_empty() {
+            let groups = self.groups_for(self.count).max(self.ctrl.len());
+            self.resize(groups);
+        }
+        Ok(())
+    }
+
+    /// Returns the current load factor.
+    pub fn get_load_factor(&self) -> f32 {
+        self.load_factor as f32 / 1000.
+    }
+
+    /// Ensures that the map has space for at least `additional` more pairs.
+    pub fn reserve(&mut self, additional: usize) {
+        if additional > self.growth_left {
+            self.reserve_rehash(additional);
+        }
+    }
+
+    /// Shrinks the capacity as much as possible without exceeding the load factor.
+    ///
+    /// If the [`IntSwissMap`] is empty, all memory is released.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntSwissMap;
+    ///
+    /// let mut map: IntSwissMap<u64, u64> = IntSwissMap::with_capacity(1_000);
+    /// map.insert(1, 1);
+    /// map.shrink_to_fit();
+    /// assert_eq!(map.capacity(), 7);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        if self.count == 0 {
+            self.ctrl = Vec::new();
+            self.slots = Vec::new();
+            self.group_bits = 0;
+            self.growth_left = 0;
+        } else {
+            let groups = self.groups_for(self.count);
+            if groups < self.ctrl.len() {
+                self.resize(groups);
+            }
+        }
+    }
+
+    /// Inserts a key/value pair and returns the previous value if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntSwissMap;
+    ///
+    /// let mut map: IntSwissMap<u64, _> = IntSwissMap::new();
+    /// assert_eq!(map.insert(21, "Eat my shorts"), None);
+    /// assert_eq!(map.insert(21, "Ay, caramba"), Some("Eat my shorts"));
+    /// assert_eq!(map.get(21), Some(&"Ay, caramba"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let hash = hash(key);
+        match self.find(key, hash) {
+            Some(slot) => self.slots[slot].replace((key, value)).map(|kv| kv.1),
+            None => {
+                self.insert_new(hash, key, value);
+                None
+            }
+        }
+    }
+
+    /// Inserts a key/value pair if the key is not yet inserted.
+    ///
+    /// This function returns true if key/value were inserted and false otherwise.
+    pub fn insert_checked(&mut self, key: K, value: V) -> bool {
+        let hash = hash(key);
+        if self.find(key, hash).is_some() {
+            return false;
+        }
+
+        self.insert_new(hash, key, value);
+        true
+    }
+
+    /// Returns a reference to the value of the key if any.
+    pub fn get(&self, key: K) -> Option<&V> {
+        let slot = self.find(key, hash(key))?;
+        self.slots[slot].as_ref().map(|kv| &kv.1)
+    }
+
+    /// Returns a mutable reference to the value of the key if any.
+    pub fn get_mut(&mut self, key: K) -> Option<&mut V> {
+        let slot = self.find(key, hash(key))?;
+        self.slots[slot].as_mut().map(|kv| &mut kv.1)
+    }
+
+    /// Removes the key and returns its value if any.
+    pub fn remove(&mut self, key: K) -> Option<V> {
+        let slot = self.find(key, hash(key))?;
+        self.remove_at(slot).map(|kv| kv.1)
+    }
+
+    /// Returns true if the key is present.
+    pub fn contains_key(&self, key: K) -> bool {
+        self.find(key, hash(key)).is_some()
+    }
+
+    /// Retains only the key/value pairs specified by the predicate.
+    ///
+    /// In other words, remove all elements such that `f(key, &value)` returns false.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntSwissMap;
+    ///
+    /// let mut map: IntSwissMap<u64, u64> = [(1, 11), (2, 12), (4, 13)].into_iter().collect();
+    ///
+    /// // retain only the odd values
+    /// map.retain(|k, v| *v % 2 == 1);
+    ///
+    /// assert_eq!(map.len(), 2);
+    /// assert!(map.contains_key(1));
+    /// assert!(map.contains_key(4));
+    /// ```
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(K, &V) -> bool,
+    {
+        self.retain_count(f);
+    }
+
+    /// Retains only the key/value pairs specified by the predicate and returns the number of
+    /// removed pairs.
+    pub fn retain_count<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut(K, &V) -> bool,
+    {
+        let count = self.count;
+        self.retain_mut(|k, v| f(k, v));
+        count - self.count
+    }
+
+    /// Retains only the key/value pairs specified by the predicate, which can also modify the
+    /// values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntSwissMap;
+    ///
+    /// let mut map: IntSwissMap<u64, u64> = [(1, 1), (2, 5), (3, 9)].into_iter().collect();
+    ///
+    /// // Decrement all values and remove the ones that reach zero.
+    /// map.retain_mut(|_, v| {
+    ///     *v -= 1;
+    ///     *v > 0
+    /// });
+    ///
+    /// assert_eq!(map, [(2, 4), (3, 8)].into_iter().collect());
+    /// ```
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(K, &mut V) -> bool,
+    {
+        let result: Result<(), std::convert::Infallible> = self.try_retain(|k, v| Ok(f(k, v)));
+        if let Err(never) = result {
+            match never {}
+        }
+    }
+
+    /// Like [`IntSwissMap::retain_mut`], but stops at the first error of the predicate and
+    /// returns it.
+    ///
+    /// The pairs that were already removed stay removed, and the remaining pairs are left
+    /// untouched.
+    pub fn try_retain<E, F>(&mut self, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(K, &mut V) -> Result<bool, E>,
+    {
+        for slot in 0..self.slots.len() {
+            if let Some((k, v)) = &mut self.slots[slot] {
+                if !f(*k, v)? {
+                    self.remove_at(slot);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the [`Entry`] that corresponds to the given key.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        let hash = hash(key);
+        match self.find(key, hash) {
+            Some(slot) => Entry::Occupied(OccupiedEntry { map: self, slot }),
+            None => Entry::Vacant(VacantEntry {
+                map: self,
+                key,
+                hash,
+            }),
+        }
+    }
+
+    /// Returns the number of pairs.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns true if the map contains no pairs.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Removes all pairs but keeps the allocated memory.
+    pub fn clear(&mut self) {
+        // Removed one by one, so the map stays consistent even if dropping a value panics.
+        for slot in 0..self.slots.len() {
+            if self.slots[slot].is_some() {
+                self.remove_at(slot);
+            }
+        }
+
+        self.reset_ctrl();
+    }
+
+    /// Returns the number of pairs the map can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.capacity_of(self.ctrl.len())
+    }
+
+    /// Returns an iterator over the pairs.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.slots.iter().flatten(),
+            remaining: self.count,
+        }
+    }
+
+    /// Returns an iterator over the pairs with mutable references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inner: self.slots.iter_mut().flatten(),
+            remaining: self.count,
+        }
+    }
+
+    /// Returns an iterator over the keys.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.iter() }
+    }
+
+    /// Returns an iterator over the values.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+
+    /// Returns an iterator over mutable references to the values.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            inner: self.iter_mut(),
+        }
+    }
+
+    /// Returns an iterator over all pairs that removes the pairs from the [`IntSwissMap`]
+    /// during iteration.
+    ///
+    /// If the iterator is dropped then all remaining pairs will be removed from the
+    /// [`IntSwissMap`].
+    pub fn drain(&mut self) -> Drain<'_, K, V> {
+        Drain {
+            back: self.slots.len(),
+            front: 0,
+            map: self,
+        }
+    }
+
+    //**** Internal hash stuff *****
+
+    // Returns the slot of the key.
+    #[inline]
+    fn find(&self, key: K, hash: u64) -> Option<usize> {
+        if self.ctrl.is_empty() {
+            return None;
+        }
+
+        let k = key.into_int();
+        let h2 = h2(hash);
+        let mut probe = self.probe(hash);
+        loop {
+            let group = self.ctrl[probe.group];
+            for offset in BitMask(match_byte(group, h2)) {
+                let slot = probe.group * GROUP + offset;
+                if let Some((key, _)) = &self.slots[slot] {
+                    if key.into_int() == k {
+                        return Some(slot);
+                    }
+                }
+            }
+
+            // A key is never placed behind an `EMPTY` slot of its probe sequence.
+            if match_empty(group) != 0 {
+                return None;
+            }
+            probe.next();
+        }
+    }
+
+    // Returns the first `EMPTY` or `DELETED` slot in the probe sequence of the hash.
+    fn find_insert_slot(&self, hash: u64) -> usize {
+        let mut probe = self.probe(hash);
+        loop {
+            if let Some(offset) = BitMask(match_empty_or_deleted(self.ctrl[probe.group])).next() {
+                return probe.group * GROUP + offset;
+            }
+            probe.next();
+        }
+    }
+
+    fn probe(&self, hash: u64) -> Probe {
+        let mask = self.ctrl.len() - 1;
+        // Uses the bits right below the ones of `h2`, so both are independent.
+        let group = (hash.rotate_left(7 + self.group_bits) as usize) & mask;
+        Probe {
+            group,
+            stride: 0,
+            mask,
+        }
+    }
+
+    fn insert_new(&mut self, hash: u64, key: K, value: V) {
+        let mut slot = self.find_first_slot(hash);
+        if self.growth_left == 0 && slot.map_or(true, |slot| self.ctrl_byte(slot) == EMPTY) {
+            self.reserve_rehash(1);
+            slot = self.find_first_slot(hash);
+        }
+
+        let slot = slot.expect("the table has free slots");
+        if self.ctrl_byte(slot) == EMPTY {
+            self.growth_left -= 1;
+        }
+        self.set_ctrl_byte(slot, h2(hash));
+        self.slots[slot] = Some((key, value));
+        self.count += 1;
+    }
+
+    fn find_first_slot(&self, hash: u64) -> Option<usize> {
+        if self.ctrl.is_empty() {
+            None
+        } else {
+            Some(self.find_insert_slot(hash))
+        }
+    }
+
+    fn remove_at(&mut self, slot: usize) -> Option<(K, V)> {
+        // If the group has an `EMPTY` slot, no probe sequence continues behind it, so the slot
+        // can become `EMPTY` again.
+        let byte = if match_empty(self.ctrl[slot / GROUP]) != 0 {
+            self.growth_left += 1;
+            EMPTY
+        } else {
+            DELETED
+        };
+        self.set_ctrl_byte(slot, byte);
+        self.count -= 1;
+        self.slots[slot].take()
+    }
+
+    // Rebuilds the table with space for `additional` more pairs. If the table is at most half
+    // full, it keeps its size and only drops the `DELETED` slots.
+    fn reserve_rehash(&mut self, additional: usize) {
+        let needed = self
+            .count
+            .checked_add(additional)
+            .expect("capacity overflow");
+        let capacity = self.capacity();
+        let groups = if needed <= capacity / 2 {
+            self.ctrl.len()
+        } else {
+            self.groups_for(needed.max(capacity + 1))
+        };
+        self.resize(groups);
+    }
+
+    // Returns the number of pairs that fit into `groups` groups.
+    fn capacity_of(&self, groups: usize) -> usize {
+        groups * GROUP * self.load_factor / 1000
+    }
+
+    // Returns the number of groups needed for `capacity` pairs, which is a power of two.
+    fn groups_for(&self, capacity: usize) -> usize {
+        let per_group = GROUP * self.load_factor;
+        capacity
+            .checked_mul(1000)
+            .map(|c| c / per_group + usize::from(c % per_group != 0))
+            .and_then(usize::checked_next_power_of_two)
+            .filter(|groups| groups.checked_mul(GROUP).is_some())
+            .expect("capacity overflow")
+    }
+
+    fn resize(&mut self, groups: usize) {
+        let hashes = crate::precompute_hashes(self.slots.iter().flatten().map(|kv| kv.0), hash);
+
+        let slots = std::mem::replace(&mut self.slots, (0..groups * GROUP).map(|_| None).collect());
+        self.ctrl = vec![u64::MAX; groups];
+        self.group_bits = groups.trailing_zeros();
+        self.growth_left = self.capacity_of(groups) - self.count;
+
+        for (kv, hash) in slots.into_iter().flatten().zip(hashes) {
+            let slot = self.find_insert_slot(hash);
+            self.set_ctrl_byte(slot, h2(hash));
+            self.slots[slot] = Some(kv);
+        }
+    }
+
+    // Marks all slots of an empty map as `EMPTY`, dropping the `DELETED` ones.
+    fn reset_ctrl(&mut self) {
+        debug_assert_eq!(self.count, 0);
+        self.ctrl.iter_mut().for_each(|group| *group = u64::MAX);
+        self.growth_left = self.capacity();
+    }
+
+    fn ctrl_byte(&self, slot: usize) -> u8 {
+        (self.ctrl[slot / GROUP] >> (slot % GROUP * 8)) as u8
+    }
+
+    fn set_ctrl_byte(&mut self, slot: usize, byte: u8) {
+        let shift = slot % GROUP * 8;
+        let group = &mut self.ctrl[slot / GROUP];
+        *group = (*group & !(0xFF << shift)) | (u64::from(byte) << shift);
+    }
+}
+
+// ***************** Set operations *********************
+
+impl<K: IntKey, V> IntSwissMap<K, V> {
+    /// Moves all pairs of `other` into `self` and combines the values of keys that are present in
+    /// both maps with `f(key, value_of_self, value_of_other)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntSwissMap;
+    ///
+    /// let mut a: IntSwissMap<u64, u64> = [(1, 10), (2, 20)].into_iter().collect();
+    /// let b: IntSwissMap<u64, u64> = [(2, 2), (3, 3)].into_iter().collect();
+    /// a.merge_with(b, |_, a, b| a + b);
+    ///
+    /// assert_eq!(a, [(1, 10), (2, 22), (3, 3)].into_iter().collect());
+    /// ```
+    pub fn merge_with<F>(&mut self, other: IntSwissMap<K, V>, mut f: F)
+    where
+        F: FnMut(K, V, V) -> V,
+    {
+        self.reserve(other.len());
+        for (key, value) in other {
+            let hash = hash(key);
+            match self.find(key, hash) {
+                Some(slot) => {
+                    // The pair is missing while `f` runs, so the count must not include it.
+                    let (key, old) = self.remove_at(slot).expect("occupied slot");
+                    let value = f(key, old, value);
+                    self.insert_new(hash, key, value);
+                }
+                None => self.insert_new(hash, key, value),
+            }
+        }
+    }
+
+    /// Moves the pairs of `other` whose keys are absent in `self` into `self`.
+    ///
+    /// The values of `self` are kept for keys that are present in both maps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntSwissMap;
+    ///
+    /// let mut a: IntSwissMap<u64, &str> = [(1, "a"), (2, "b")].into_iter().collect();
+    /// a.union([(2, "x"), (3, "c")].into_iter().collect());
+    ///
+    /// assert_eq!(a, [(1, "a"), (2, "b"), (3, "c")].into_iter().collect());
+    /// ```
+    pub fn union(&mut self, other: IntSwissMap<K, V>) {
+        self.reserve(other.len());
+        for (key, value) in other {
+            self.insert_checked(key, value);
+        }
+    }
+
+    /// Removes the keys that are present in both maps and moves the remaining pairs of `other`
+    /// into `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntSwissMap;
+    ///
+    /// let mut a: IntSwissMap<u64, &str> = [(1, "a"), (2, "b")].into_iter().collect();
+    /// a.symmetric_difference([(2, "x"), (3, "c")].into_iter().collect());
+    ///
+    /// assert_eq!(a, [(1, "a"), (3, "c")].into_iter().collect());
+    /// ```
+    pub fn symmetric_difference(&mut self, other: IntSwissMap<K, V>) {
+        for (key, value) in other {
+            let hash = hash(key);
+            match self.find(key, hash) {
+                Some(slot) => {
+                    self.remove_at(slot);
+                }
+                None => self.insert_new(hash, key, value),
+            }
+        }
+    }
+
+    /// Retains only the keys that are present in `other` and updates their values with
+    /// `f(key, value_of_self, value_of_other)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntSwissMap;
+    ///
+    /// let mut a: IntSwissMap<u64, u64> = [(1, 10), (2, 20)].into_iter().collect();
+    /// let b: IntSwissMap<u64, u8> = [(2, 2), (3, 3)].into_iter().collect();
+    /// a.intersection_with(&b, |_, a, &b| *a += b as u64);
+    ///
+    /// assert_eq!(a, [(2, 22)].into_iter().collect());
+    /// ```
+    pub fn intersection_with<W, F>(&mut self, other: &IntSwissMap<K, W>, mut f: F)
+    where
+        F: FnMut(K, &mut V, &W),
+    {
+        self.retain_mut(|key, value| match other.get(key) {
+            Some(other_value) => {
+                f(key, value, other_value);
+                true
+            }
+            None => false,
+        });
+    }
+
+    /// Retains only the keys that are present in `other`, e.g. an `IntSwissMap<K, ()>` used as
+    /// set.
+    pub fn retain_keys_in<W>(&mut self, other: &IntSwissMap<K, W>) {
+        self.retain(|key, _| other.contains_key(key));
+    }
+
+    /// Removes the keys that are present in `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntSwissMap;
+    ///
+    /// let mut a: IntSwissMap<u64, &str> = [(1, "a"), (2, "b")].into_iter().collect();
+    /// let b: IntSwissMap<u64, ()> = [(2, ())].into_iter().collect();
+    /// a.difference(&b);
+    ///
+    /// assert_eq!(a, [(1, "a")].into_iter().collect());
+    /// ```
+    pub fn difference<W>(&mut self, other: &IntSwissMap<K, W>) {
+        self.retain(|key, _| !other.contains_key(key));
+    }
+
+    /// Returns true if every pair of `self` is also present in `other` with an equal value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::IntSwissMap;
+    ///
+    /// let a: IntSwissMap<u64, u64> = [(1, 1)].into_iter().collect();
+    /// let b: IntSwissMap<u64, u64> = [(1, 1), (2, 2)].into_iter().collect();
+    /// assert!(a.is_submap_of(&b));
+    /// assert!(!b.is_submap_of(&a));
+    /// ```
+    pub fn is_submap_of(&self, other: &IntSwissMap<K, V>) -> bool
+    where
+        V: PartialEq,
+    {
+        self.count <= other.count
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<K, V> Default for IntSwissMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[inline(always)]
+fn hash<K: IntKey>(key: K) -> u64 {
+    let k = key.into_int().into_u128();
+    ((k as u64) ^ ((k >> 64) as u64)).wrapping_mul(GOLDEN)
+}
+
+// The 7 highest bits of the hash, which are stored in the control byte of a full slot.
+#[inline(always)]
+fn h2(hash: u64) -> u8 {
+    (hash >> 57) as u8
+}
+
+// Returns the high bit of every byte of `group` that equals `byte`.
+//
+// This can report false positives for bytes right after a match, which are filtered out by
+// comparing the keys.
+#[inline(always)]
+fn match_byte(group: u64, byte: u8) -> u64 {
+    let cmp = group ^ (LSB * u64::from(byte));
+    cmp.wrapping_sub(LSB) & !cmp & MSB
+}
+
+// Returns the high bit of every `EMPTY` byte, which are the only ones with the two highest bits
+// set.
+#[inline(always)]
+fn match_empty(group: u64) -> u64 {
+    group & (group << 1) & MSB
+}
+
+// Returns the high bit of every `EMPTY` or `DELETED` byte.
+#[inline(always)]
+fn match_empty_or_deleted(group: u64) -> u64 {
+    group & MSB
+}
+
+// Iterates over the offsets of the bytes whose high bit is set.
+struct BitMask(u64);
+
+impl Iterator for BitMask {
+    type Item = usize;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<usize> {
+        if self.0 == 0 {
+            return None;
+        }
+
+        let offset = self.0.trailing_zeros() as usize / 8;
+        self.0 &= self.0 - 1;
+        Some(offset)
+    }
+}
+
+// Triangular probing over the groups, which visits every group if their number is a power of two.
+struct Probe {
+    group: usize,
+    stride: usize,
+    mask: usize,
+}
+
+impl Probe {
+    #[inline(always)]
+    fn next(&mut self) {
+        self.stride += 1;
+        self.group = (self.group + self.stride) & self.mask;
+    }
+}
+
+// ***************** Equality *********************
+
+impl<K: IntKey, V: PartialEq> PartialEq for IntSwissMap<K, V> {
+    fn eq(&self, other: &IntSwissMap<K, V>) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+impl<K: IntKey, V: Eq> Eq for IntSwissMap<K, V> {}
+
+// ***************** Debug *********************
+
+impl<K, V> std::fmt::Debug for IntSwissMap<K, V>
+where
+    K: IntKey + std::fmt::Debug,
+    V: std::fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_map().entries(self.iter()).finish()
+    }
+}
+
+// ***************** Extend *********************
+
+impl<K: IntKey, V> Extend<(K, V)> for IntSwissMap<K, V> {
+    #[inline]
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+// ***************** FromIterator *********************
+
+impl<K: IntKey, V> FromIterator<(K, V)> for IntSwissMap<K, V> {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let iter = iter.into_iter();
+        let mut map = IntSwissMap::with_capacity(iter.size_hint().0);
+        map.extend(iter);
+        map
+    }
+}
+
+// ***************** Conversion *********************
+
+impl<K: IntKey, V> From<IntMap<K, V>> for IntSwissMap<K, V> {
+    fn from(map: IntMap<K, V>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<K: IntKey, V> From<IntSwissMap<K, V>> for IntMap<K, V> {
+    fn from(map: IntSwissMap<K, V>) -> Self {
+        let mut int_map = IntMap::with_capacity(map.len());
+        int_map.extend(map);
+        int_map
+    }
+}
+
+// ***************** Entry *********************
+
+/// A view into a single entry in a [`IntSwissMap`], which may either be vacant or occupied.
+///
+/// The entry can be constructed by calling [`IntSwissMap::entry`] with a key.
+pub enum Entry<'a, K: IntKey, V> {
+    /// The entry is occupied.
+    Occupied(OccupiedEntry<'a, K, V>),
+    /// The entry is vacant.
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+impl<'a, K: IntKey, V> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting the provided value if empty, and returns
+    /// a mutable reference to the value in the entry.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the result of the provided function if empty,
+    /// and returns a mutable reference to the value in the entry.
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Provides in-place mutable access to an occupied entry before any potential inserts.
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K: IntKey, V: Default> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting the default value if empty, and returns a
+    /// mutable reference to the value in the entry.
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(Default::default)
+    }
+}
+
+/// A view into an occupied entry in a [`IntSwissMap`]. It is part of the [`Entry`] enum.
+pub struct OccupiedEntry<'a, K: IntKey, V> {
+    map: &'a mut IntSwissMap<K, V>,
+    slot: usize,
+}
+
+impl<'a, K: IntKey, V> OccupiedEntry<'a, K, V> {
+    /// Gets a reference to the value in the entry.
+    pub fn get(&self) -> &V {
+        &self.map.slots[self.slot].as_ref().expect("occupied slot").1
+    }
+
+    /// Gets a mutable reference to the value in the entry.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.slots[self.slot].as_mut().expect("occupied slot").1
+    }
+
+    /// Converts the entry into a mutable reference to its value.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.slots[self.slot].as_mut().expect("occupied slot").1
+    }
+
+    /// Sets the value of the entry and returns the old value.
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the value out of the entry.
+    pub fn remove(self) -> V {
+        self.map.remove_at(self.slot).expect("occupied slot").1
+    }
+}
+
+/// A view into a vacant entry in a [`IntSwissMap`]. It is part of the [`Entry`] enum.
+pub struct VacantEntry<'a, K: IntKey, V> {
+    map: &'a mut IntSwissMap<K, V>,
+    key: K,
+    hash: u64,
+}
+
+impl<'a, K: IntKey, V> VacantEntry<'a, K, V> {
+    /// Inserts the value and returns a mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.map.insert_new(self.hash, self.key, value);
+        let slot = self
+            .map
+            .find(self.key, self.hash)
+            .expect("the pair was inserted");
+        &mut self.map.slots[slot].as_mut().expect("occupied slot").1
+    }
+}
+
+// ***************** Iter *********************
+
+impl<'a, K: IntKey, V> IntoIterator for &'a IntSwissMap<K, V> {
+    type Item = (K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the pairs of a [`IntSwissMap`].
+///
+/// This struct is created by [`IntSwissMap::iter`].
+#[derive(Clone, Debug)]
+pub struct Iter<'a, K, V> {
+    inner: Flatten<std::slice::Iter<'a, Option<(K, V)>>>,
+    remaining: usize,
+}
+
+impl<'a, K: IntKey, V> Iterator for Iter<'a, K, V> {
+    type Item = (K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, &'a V)> {
+        let (k, v) = self.inner.next()?;
+        self.remaining -= 1;
+        Some((*k, v))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for Iter<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, &'a V)> {
+        let (k, v) = self.inner.next_back()?;
+        self.remaining -= 1;
+        Some((*k, v))
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for Iter<'a, K, V> {}
+impl<'a, K: IntKey, V> std::iter::FusedIterator for Iter<'a, K, V> {}
+
+// ***************** Iter Mut *********************
+
+impl<'a, K: IntKey, V> IntoIterator for &'a mut IntSwissMap<K, V> {
+    type Item = (K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// A mutable iterator over the pairs of a [`IntSwissMap`].
+///
+/// This struct is created by [`IntSwissMap::iter_mut`].
+#[derive(Debug)]
+pub struct IterMut<'a, K, V> {
+    inner: Flatten<std::slice::IterMut<'a, Option<(K, V)>>>,
+    remaining: usize,
+}
+
+impl<'a, K: IntKey, V> Iterator for IterMut<'a, K, V> {
+    type Item = (K, &'a mut V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, &'a mut V)> {
+        let (k, v) = self.inner.next()?;
+        self.remaining -= 1;
+        Some((*k, v))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for IterMut<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, &'a mut V)> {
+        let (k, v) = self.inner.next_back()?;
+        self.remaining -= 1;
+        Some((*k, v))
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for IterMut<'a, K, V> {}
+impl<'a, K: IntKey, V> std::iter::FusedIterator for IterMut<'a, K, V> {}
+
+// ***************** Keys Iter *********************
+
+/// An iterator over the keys of a [`IntSwissMap`].
+///
+/// This struct is created by [`IntSwissMap::keys`].
+#[derive(Clone, Debug)]
+pub struct Keys<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K: IntKey, V> Iterator for Keys<'a, K, V> {
+    type Item = K;
+
+    #[inline]
+    fn next(&mut self) -> Option<K> {
+        self.inner.next().map(|kv| kv.0)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for Keys<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<K> {
+        self.inner.next_back().map(|kv| kv.0)
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for Keys<'a, K, V> {}
+impl<'a, K: IntKey, V> std::iter::FusedIterator for Keys<'a, K, V> {}
+
+// ***************** Values Iter *********************
+
+/// An iterator over the values of a [`IntSwissMap`].
+///
+/// This struct is created by [`IntSwissMap::values`].
+#[derive(Clone, Debug)]
+pub struct Values<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K: IntKey, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a V> {
+        self.inner.next().map(|kv| kv.1)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for Values<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a V> {
+        self.inner.next_back().map(|kv| kv.1)
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for Values<'a, K, V> {}
+impl<'a, K: IntKey, V> std::iter::FusedIterator for Values<'a, K, V> {}
+
+// ***************** Values Mut Iter *********************
+
+/// A mutable iterator over the values of a [`IntSwissMap`].
+///
+/// This struct is created by [`IntSwissMap::values_mut`].
+#[derive(Debug)]
+pub struct ValuesMut<'a, K, V> {
+    inner: IterMut<'a, K, V>,
+}
+
+impl<'a, K: IntKey, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a mut V> {
+        self.inner.next().map(|kv| kv.1)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for ValuesMut<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a mut V> {
+        self.inner.next_back().map(|kv| kv.1)
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for ValuesMut<'a, K, V> {}
+impl<'a, K: IntKey, V> std::iter::FusedIterator for ValuesMut<'a, K, V> {}
+
+// ***************** Into Iter *********************
+
+impl<K: IntKey, V> IntoIterator for IntSwissMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            remaining: self.count,
+            inner: self.slots.into_iter().flatten(),
+        }
+    }
+}
+
+/// An owning iterator over the pairs of a [`IntSwissMap`].
+///
+/// This struct is created by [`IntSwissMap::into_iter`].
+#[derive(Debug)]
+pub struct IntoIter<K, V> {
+    inner: Flatten<std::vec::IntoIter<Option<(K, V)>>>,
+    remaining: usize,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, V)> {
+        let kv = self.inner.next()?;
+        self.remaining -= 1;
+        Some(kv)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, V)> {
+        let kv = self.inner.next_back()?;
+        self.remaining -= 1;
+        Some(kv)
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+impl<K, V> std::iter::FusedIterator for IntoIter<K, V> {}
+
+// ***************** Drain Iter *********************
+
+/// A draining iterator for [`IntSwissMap`].
+///
+/// This struct is created by [`IntSwissMap::drain`].
+pub struct Drain<'a, K: IntKey, V> {
+    // The slots that were not drained yet are `map.slots[front..back]`. Every pair is removed
+    // with `remove_at`, so the map stays consistent if a value panics while being dropped.
+    map: &'a mut IntSwissMap<K, V>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, K: IntKey, V> Iterator for Drain<'a, K, V> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, V)> {
+        while self.front < self.back {
+            let slot = self.front;
+            self.front += 1;
+            if self.map.slots[slot].is_some() {
+                return self.map.remove_at(slot);
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.map.count, Some(self.map.count))
+    }
+}
+
+impl<'a, K: IntKey, V> DoubleEndedIterator for Drain<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(K, V)> {
+        while self.front < self.back {
+            self.back -= 1;
+            if self.map.slots[self.back].is_some() {
+                return self.map.remove_at(self.back);
+            }
+        }
+        None
+    }
+}
+
+impl<'a, K: IntKey, V> ExactSizeIterator for Drain<'a, K, V> {}
+impl<'a, K: IntKey, V> std::iter::FusedIterator for Drain<'a, K, V> {}
+
+impl<'a, K: IntKey, V> std::fmt::Debug for Drain<'a, K, V> {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_struct("Drain")
+            .field("remaining", &self.map.count)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, K: IntKey, V> Drop for Drain<'a, K, V> {
+    fn drop(&mut self) {
+        // Continues removing the remaining pairs if dropping a value panics.
+        struct DropGuard<'r, 'a, K: IntKey, V>(&'r mut Drain<'a, K, V>);
+
+        impl<'r, 'a, K: IntKey, V> Drop for DropGuard<'r, 'a, K, V> {
+            fn drop(&mut self) {
+                self.0.for_each(drop);
+                self.0.map.reset_ctrl();
+            }
+        }
+
+        while let Some(kv) = self.next() {
+            let guard = DropGuard(self);
+            drop(kv);
+            std::mem::forget(guard);
+        }
+
+        self.map.reset_ctrl();
+    }
+}
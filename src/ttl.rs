@@ -0,0 +1,510 @@
+//! A map whose entries expire after a time-to-live, see [`IntTtlMap`].
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::{IntKey, IntMap};
+
+/// A source of the current time for [`IntTtlMap`].
+pub trait Clock {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+}
+
+/// A [`Clock`] that returns the real time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A [`Clock`] that only moves when it's advanced, e.g. for tests.
+///
+/// Clones share the same time, so a clone can be moved into an [`IntTtlMap`] while the original
+/// is used to advance the time.
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    start: Instant,
+    // The nanoseconds since `start`.
+    elapsed: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    /// Creates a new [`ManualClock`] that starts at the current real time.
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Moves the time forward.
+    ///
+    /// The time saturates at about 584 years after the creation of the clock.
+    pub fn advance(&self, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        let _ = self
+            .elapsed
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |elapsed| {
+                Some(elapsed.saturating_add(nanos))
+            });
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + Duration::from_nanos(self.elapsed.load(Ordering::SeqCst))
+    }
+}
+
+/// A map that maps an integer based `K` to `V` where every entry expires after a time-to-live.
+///
+/// Expired entries are hidden right away, but they only release their memory once they are
+/// removed by [`IntTtlMap::purge_expired`]. The expiries are kept in a hierarchical timing wheel,
+/// so purging only touches the expired entries instead of scanning all of them. Removing or
+/// replacing an entry leaves its old expiry in the wheel, where it's skipped once it's due. The
+/// wheel is rebuilt when these stale expiries outnumber the entries, so both operations take
+/// amortized constant time.
+///
+/// The time is taken from a [`Clock`], which is the real time by default.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use intmap::ttl::{Clock, ManualClock};
+/// use intmap::IntTtlMap;
+///
+/// let clock = ManualClock::new();
+/// let mut sessions: IntTtlMap<u64, &str, _> = IntTtlMap::with_clock(clock.clone());
+/// sessions.insert(1, "alice", Duration::from_secs(60));
+/// sessions.insert(2, "bob", Duration::from_secs(120));
+///
+/// clock.advance(Duration::from_secs(90));
+/// assert_eq!(sessions.get(1), None);
+/// assert_eq!(sessions.get(2), Some(&"bob"));
+///
+/// let purged = sessions.purge_expired(clock.now());
+/// assert_eq!(purged, [(1, "alice")]);
+/// assert_eq!(sessions.len(), 1);
+/// ```
+pub struct IntTtlMap<K, V, C = SystemClock> {
+    entries: IntMap<K, TtlEntry<V>>,
+    wheel: TimingWheel<K>,
+    // The number of expiries in `wheel` that belong to removed or replaced entries.
+    stale: usize,
+    clock: C,
+    // The time of tick 0.
+    start: Instant,
+    // The duration of a tick, always at least one nanosecond.
+    resolution: Duration,
+}
+
+struct TtlEntry<V> {
+    value: V,
+    // `None` if the expiry doesn't fit into an `Instant`.
+    expires_at: Option<Instant>,
+    // The first tick at which the entry is expired, `None` if it never expires.
+    tick: Option<u64>,
+}
+
+impl<K: IntKey, V> IntTtlMap<K, V> {
+    /// Creates a new [`IntTtlMap`] that uses the real time.
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+}
+
+impl<K: IntKey, V> Default for IntTtlMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: IntKey, V, C: Clock> IntTtlMap<K, V, C> {
+    /// Creates a new [`IntTtlMap`] that takes the time from the given clock and purges with a
+    /// resolution of one millisecond.
+    pub fn with_clock(clock: C) -> Self {
+        Self::with_resolution(clock, Duration::from_millis(1))
+    }
+
+    /// Creates a new [`IntTtlMap`] that takes the time from the given clock and purges with the
+    /// given resolution.
+    ///
+    /// [`IntTtlMap::purge_expired`] rounds the expiries up to multiples of `resolution`, so
+    /// entries might be purged up to `resolution` after they expired. A coarser resolution makes
+    /// purging cheaper.
+    pub fn with_resolution(clock: C, resolution: Duration) -> Self {
+        Self {
+            entries: IntMap::new(),
+            wheel: TimingWheel::new(),
+            stale: 0,
+            start: clock.now(),
+            clock,
+            resolution: resolution.max(Duration::from_nanos(1)),
+        }
+    }
+
+    /// Returns the clock of the map.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Inserts a key/value pair that expires after `ttl`.
+    ///
+    /// Returns the previous value of the key if it wasn't expired.
+    pub fn insert(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        let now = self.clock.now();
+        self.insert_entry(key, value, now.checked_add(ttl), now)
+    }
+
+    /// Inserts a key/value pair that expires at `expires_at`.
+    ///
+    /// Returns the previous value of the key if it wasn't expired.
+    pub fn insert_until(&mut self, key: K, value: V, expires_at: Instant) -> Option<V> {
+        let now = self.clock.now();
+        self.insert_entry(key, value, Some(expires_at), now)
+    }
+
+    fn insert_entry(
+        &mut self,
+        key: K,
+        value: V,
+        expires_at: Option<Instant>,
+        now: Instant,
+    ) -> Option<V> {
+        let tick = expires_at.map(|expires_at| self.ticks(expires_at, true));
+        let entry = TtlEntry {
+            value,
+            expires_at,
+            tick,
+        };
+        let old = self.entries.insert(key, entry);
+
+        // An old expiry at the same tick is still valid, so it isn't scheduled twice.
+        let old_tick = old.as_ref().and_then(|old| old.tick);
+        if old_tick != tick {
+            if let Some(tick) = tick {
+                self.wheel.schedule(key, tick);
+            }
+            if old_tick.is_some() {
+                self.release_expiry();
+            }
+        }
+        old.filter(|old| !old.is_expired(now)).map(|old| old.value)
+    }
+
+    /// Returns the value of the key if it isn't expired.
+    pub fn get(&self, key: K) -> Option<&V> {
+        let now = self.clock.now();
+        self.entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(now))
+            .map(|entry| &entry.value)
+    }
+
+    /// Returns the value of the key if it isn't expired.
+    pub fn get_mut(&mut self, key: K) -> Option<&mut V> {
+        let now = self.clock.now();
+        self.entries
+            .get_mut(key)
+            .filter(|entry| !entry.is_expired(now))
+            .map(|entry| &mut entry.value)
+    }
+
+    /// Returns when the key expires if it isn't expired yet.
+    ///
+    /// Returns `None` for absent keys and `Some(None)` for keys whose expiry is too far in the
+    /// future to be represented.
+    pub fn expires_at(&self, key: K) -> Option<Option<Instant>> {
+        let now = self.clock.now();
+        self.entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(now))
+            .map(|entry| entry.expires_at)
+    }
+
+    /// Returns true if the key is present and not expired.
+    pub fn contains_key(&self, key: K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Removes the key and returns its value if it wasn't expired.
+    pub fn remove(&mut self, key: K) -> Option<V> {
+        let now = self.clock.now();
+        let entry = self.entries.remove(key)?;
+        if entry.tick.is_some() {
+            self.release_expiry();
+        }
+        (!entry.is_expired(now)).then(|| entry.value)
+    }
+
+    // Counts the expiry of a removed or replaced entry as stale, and rebuilds the wheel once the
+    // stale expiries outnumber the entries.
+    fn release_expiry(&mut self) {
+        self.stale += 1;
+        if self.stale <= self.entries.len().max(MIN_STALE_REBUILD) {
+            return;
+        }
+
+        self.wheel.clear();
+        for (key, entry) in self.entries.iter() {
+            if let Some(tick) = entry.tick {
+                self.wheel.schedule(key, tick);
+            }
+        }
+        self.stale = 0;
+    }
+
+    /// Removes all entries that are expired at `now` and returns them.
+    ///
+    /// The expiries are rounded up to the resolution of the map, so entries that expired less
+    /// than one resolution before `now` might be kept until the next call.
+    pub fn purge_expired(&mut self, now: Instant) -> Vec<(K, V)> {
+        let mut due = Vec::new();
+        self.wheel.advance(self.ticks(now, false), &mut due);
+
+        let mut purged = Vec::new();
+        for (key, tick) in due {
+            // Expiries of removed and replaced entries stay in the wheel, so they are skipped
+            // here.
+            if self.entries.get(key).and_then(|entry| entry.tick) == Some(tick) {
+                if let Some(entry) = self.entries.remove(key) {
+                    purged.push((key, entry.value));
+                }
+            } else {
+                self.stale -= 1;
+            }
+        }
+        purged
+    }
+
+    /// Removes all entries, but keeps the allocated memory for reuse.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.wheel.clear();
+        self.stale = 0;
+    }
+
+    /// Returns the number of entries, including expired ones that weren't purged yet.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the map contains no entries, including expired ones that weren't purged
+    /// yet.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the number of expiries in the timing wheel, including stale ones.
+    ///
+    /// Only for testing.
+    #[doc(hidden)]
+    pub fn scheduled_len(&self) -> usize {
+        self.wheel.len()
+    }
+
+    /// Returns an iterator over the key/value pairs that aren't expired.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.entries.iter(),
+            now: self.clock.now(),
+        }
+    }
+
+    // Converts the time to ticks since `start`, rounding up or down.
+    fn ticks(&self, time: Instant, round_up: bool) -> u64 {
+        let nanos = time.saturating_duration_since(self.start).as_nanos();
+        let resolution = self.resolution.as_nanos();
+        let ticks = if round_up {
+            (nanos + resolution - 1) / resolution
+        } else {
+            nanos / resolution
+        };
+        u64::try_from(ticks).unwrap_or(u64::MAX)
+    }
+}
+
+impl<V> TtlEntry<V> {
+    fn is_expired(&self, now: Instant) -> bool {
+        match self.expires_at {
+            Some(expires_at) => expires_at <= now,
+            None => false,
+        }
+    }
+}
+
+// ***************** Iter *********************
+
+/// An iterator over the key/value pairs of a [`IntTtlMap`] that aren't expired.
+///
+/// This struct is created by [`IntTtlMap::iter`].
+pub struct Iter<'a, K: IntKey, V> {
+    inner: crate::Iter<'a, K, TtlEntry<V>>,
+    // The time the iterator was created at.
+    now: Instant,
+}
+
+impl<'a, K: IntKey, V> Iterator for Iter<'a, K, V> {
+    type Item = (K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, &'a V)> {
+        let now = self.now;
+        self.inner
+            .by_ref()
+            .find(|(_, entry)| !entry.is_expired(now))
+            .map(|(key, entry)| (key, &entry.value))
+    }
+}
+
+// ***************** Timing Wheel *********************
+
+// The number of stale expiries that are always tolerated, so small maps aren't rebuilt on every
+// removal.
+const MIN_STALE_REBUILD: usize = 64;
+
+// The number of bits of a tick covered by each level.
+const LEVEL_BITS: u32 = 6;
+const LEVEL_MASK: u64 = (1 << LEVEL_BITS) - 1;
+
+// A hierarchical timing wheel that keeps track of the key/tick pairs that are due.
+//
+// Level `l` has 64 slots that each cover `64^l` ticks. A key is stored at the level of the
+// highest 6 bit group in which its tick differs from `current`, so level 0 contains the ticks of
+// the current 64 ticks, level 1 the ticks of the current 4096 ticks and so on. When `current`
+// reaches the start of a slot in a higher level, its keys are moved to the lower levels.
+struct TimingWheel<K> {
+    // Created on demand.
+    levels: Vec<Level<K>>,
+    // Keys whose tick is not after `current`.
+    due: Vec<(K, u64)>,
+    // The last processed tick.
+    current: u64,
+}
+
+struct Level<K> {
+    // Bit `i` is set if `slots[i]` is non-empty.
+    occupied: u64,
+    slots: Vec<Vec<(K, u64)>>,
+}
+
+impl<K: IntKey> TimingWheel<K> {
+    fn new() -> Self {
+        Self {
+            levels: Vec::new(),
+            due: Vec::new(),
+            current: 0,
+        }
+    }
+
+    // Returns the level and the slot in which a tick after `current` is stored.
+    fn position(&self, tick: u64) -> (usize, u64) {
+        let highest_diff_bit = 63 - (tick ^ self.current).leading_zeros();
+        let level = (highest_diff_bit / LEVEL_BITS) as usize;
+        let slot = (tick >> (level as u32 * LEVEL_BITS)) & LEVEL_MASK;
+        (level, slot)
+    }
+
+    fn schedule(&mut self, key: K, tick: u64) {
+        if tick <= self.current {
+            self.due.push((key, tick));
+            return;
+        }
+
+        let (level, slot) = self.position(tick);
+        while self.levels.len() <= level {
+            self.levels.push(Level {
+                occupied: 0,
+                slots: (0..=LEVEL_MASK).map(|_| Vec::new()).collect(),
+            });
+        }
+        let level = &mut self.levels[level];
+        level.occupied |= 1 << slot;
+        level.slots[slot as usize].push((key, tick));
+    }
+
+    // Moves forward to `target` and appends all key/tick pairs that are due until then.
+    fn advance(&mut self, target: u64, due: &mut Vec<(K, u64)>) {
+        while self.current < target {
+            self.current = self.next_event(target);
+            let current = self.current;
+
+            // Move the keys of the slots that start at `current` to lower levels, beginning at the
+            // highest level. Level 0 has no lower level, so its keys go to `due` right away.
+            for level in (0..self.levels.len()).rev() {
+                let shift = level as u32 * LEVEL_BITS;
+                if level > 0 && current & ((1 << shift) - 1) != 0 {
+                    continue;
+                }
+
+                let slot = (current >> shift) & LEVEL_MASK;
+                let level = &mut self.levels[level];
+                if level.occupied & (1 << slot) == 0 {
+                    continue;
+                }
+                level.occupied &= !(1 << slot);
+                let keys = std::mem::take(&mut level.slots[slot as usize]);
+                for (key, tick) in keys {
+                    self.schedule(key, tick);
+                }
+            }
+        }
+
+        due.append(&mut self.due);
+    }
+
+    // Returns the next tick after `current`, but at most `target`, at which a slot starts.
+    fn next_event(&self, target: u64) -> u64 {
+        let mut next = target;
+
+        for (level, slots) in self.levels.iter().enumerate() {
+            let shift = level as u32 * LEVEL_BITS;
+            let position = (self.current >> shift) & LEVEL_MASK;
+            // Slots at or before the position of `current` are always empty.
+            let later = slots.occupied & (!0 << position << 1);
+            if later == 0 {
+                continue;
+            }
+
+            let slot = u64::from(later.trailing_zeros());
+            let level_start = match 1u64.checked_shl(shift + LEVEL_BITS) {
+                Some(level_len) => self.current & !(level_len - 1),
+                None => 0,
+            };
+            next = next.min(level_start + (slot << shift));
+        }
+
+        next
+    }
+
+    fn len(&self) -> usize {
+        let scheduled: usize = self
+            .levels
+            .iter()
+            .flat_map(|level| &level.slots)
+            .map(Vec::len)
+            .sum();
+        scheduled + self.due.len()
+    }
+
+    fn clear(&mut self) {
+        for level in &mut self.levels {
+            level.occupied = 0;
+            level.slots.iter_mut().for_each(Vec::clear);
+        }
+        self.due.clear();
+    }
+}
@@ -0,0 +1,291 @@
+//! Finds a good [`IntKey::PRIME`] and [`IntKey::into_int`] for a sample of keys.
+//!
+//! [`IntMap`] hashes a key by multiplying it with [`IntKey::PRIME`] and keeping the lowest bits of
+//! the product. Because the lowest bits of the product only depend on the lowest bits of the key,
+//! keys that only differ in their upper bits (e.g. aligned addresses or shifted ids) end up in the
+//! same slot no matter which multiplier is used. [`tune`] evaluates several multipliers combined
+//! with several [`HashStrategy`]s that move the relevant bits into place, using the same index
+//! calculation as [`IntMap`].
+//!
+//! The command line tool `intmap-tune` wraps [`tune`] and reads the sample from a file or stdin:
+//!
+//! ```txt
+//! cargo run --release --bin intmap-tune -- --bits 64 --type MyKey keys.txt
+//! ```
+//!
+//! # Examples
+//!
+//! ```
+//! use intmap::tune::{tune, HashStrategy, TuneOptions};
+//!
+//! // Keys that are multiples of 2^32 collide with every multiplier.
+//! let keys: Vec<u64> = (0..1000).map(|i| i << 32).collect();
+//!
+//! let candidates = tune(&keys, &TuneOptions::default());
+//! let best = &candidates[0];
+//! assert_ne!(best.strategy, HashStrategy::Identity);
+//! assert!(best.stats[0].max_chain_len < 10);
+//!
+//! println!("{}", best.int_key_impl("MyKey", "self.0"));
+//! ```
+//!
+//! [`IntMap`]: crate::IntMap
+//! [`IntKey::PRIME`]: crate::IntKey::PRIME
+//! [`IntKey::into_int`]: crate::IntKey::into_int
+
+use std::cmp::Ordering;
+
+use crate::{Int, IntKey, MapStats};
+
+/// Options for [`tune`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TuneOptions {
+    /// The number of random multipliers that are evaluated in addition to the default
+    /// [`IntKey::PRIME`] and a multiplier based on the golden ratio.
+    pub random_candidates: usize,
+    /// The seed for generating the random multipliers.
+    pub seed: u64,
+    /// The capacities the candidates are evaluated at. Each one is rounded up to the next power
+    /// of two.
+    ///
+    /// If empty, the capacity an [`IntMap`](crate::IntMap) with the default load factor would
+    /// have for the sample is used, together with half and twice that capacity.
+    pub capacities: Vec<usize>,
+    /// The share of lookups for present keys, see [`MapStats::estimated_lookup_cost`].
+    pub hit_ratio: f64,
+}
+
+impl Default for TuneOptions {
+    fn default() -> Self {
+        Self {
+            random_candidates: 64,
+            seed: 0,
+            capacities: Vec::new(),
+            hit_ratio: 0.5,
+        }
+    }
+}
+
+/// A transformation of the key that is applied before hashing.
+///
+/// Every strategy is a bijection, so distinct keys stay distinct as required by
+/// [`IntKey::into_int`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HashStrategy {
+    /// Uses the key as is.
+    Identity,
+    /// Rotates the bits to the right, which moves bits that are equal for all keys (e.g. the
+    /// trailing zeros of aligned addresses) out of the lowest bits.
+    RotateRight(u32),
+    /// Reverses the byte order, which helps if the upper bytes vary more than the lower ones.
+    SwapBytes,
+    /// XORs the key with itself shifted to the right, which mixes the upper bits into the lower
+    /// bits.
+    XorShiftRight(u32),
+}
+
+impl HashStrategy {
+    /// Applies the strategy to the key.
+    pub fn apply<I: Int>(self, key: I) -> I {
+        let bits = I::BITS;
+        let key = key.into_u128();
+        let value = match self {
+            Self::Identity => key,
+            Self::RotateRight(n) => {
+                let n = n % bits;
+                if n == 0 {
+                    key
+                } else {
+                    (key >> n) | (key << (bits - n))
+                }
+            }
+            Self::SwapBytes => key.swap_bytes() >> (128 - bits),
+            Self::XorShiftRight(n) => {
+                if n >= bits {
+                    key
+                } else {
+                    key ^ (key >> n)
+                }
+            }
+        };
+        I::from_u128(value)
+    }
+
+    /// Returns a Rust expression that applies the strategy to the expression `key`.
+    pub fn expr(self, key: &str) -> String {
+        match self {
+            Self::Identity => key.to_string(),
+            Self::RotateRight(n) => format!("{key}.rotate_right({n})"),
+            Self::SwapBytes => format!("{key}.swap_bytes()"),
+            Self::XorShiftRight(n) => format!("{key} ^ ({key} >> {n})"),
+        }
+    }
+}
+
+/// A combination of multiplier and [`HashStrategy`] evaluated by [`tune`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate<I> {
+    /// The multiplier, i.e. the value for [`IntKey::PRIME`].
+    pub prime: I,
+    /// The transformation for [`IntKey::into_int`].
+    pub strategy: HashStrategy,
+    /// The statistics for each evaluated capacity, in the order of the capacities.
+    pub stats: Vec<MapStats>,
+    /// The average [`MapStats::estimated_lookup_cost`] over all capacities. Lower is better.
+    pub cost: f64,
+}
+
+impl<I: Int> Candidate<I> {
+    /// Returns an [`IntKey`] impl for the type `type_name` that uses this candidate.
+    ///
+    /// `key` is the expression that returns the underlying integer of `self`, e.g. `self.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intmap::tune::{tune, TuneOptions};
+    ///
+    /// let keys: Vec<u32> = (0..100).collect();
+    /// let best = &tune(&keys, &TuneOptions::default())[0];
+    /// let code = best.int_key_impl("MyKey", "self.0");
+    /// assert!(code.starts_with("impl IntKey for MyKey {"));
+    /// assert!(code.contains("type Int = u32;"));
+    /// ```
+    pub fn int_key_impl(&self, type_name: &str, key: &str) -> String {
+        format!(
+            "impl IntKey for {type_name} {{\n    \
+                type Int = {int};\n\n    \
+                const PRIME: Self::Int = {prime:#x};\n\n    \
+                fn into_int(self) -> Self::Int {{\n        \
+                    {expr}\n    \
+                }}\n\
+            }}\n",
+            int = I::NAME,
+            prime = self.prime.into_u128(),
+            expr = self.strategy.expr(key),
+        )
+    }
+}
+
+/// Evaluates multipliers and [`HashStrategy`]s for the sample `keys`, using the same index
+/// calculation as [`IntMap`](crate::IntMap).
+///
+/// Returns all candidates sorted by [`Candidate::cost`], so the first one is the best. On a tie the
+/// default [`IntKey::PRIME`] without transformation comes first. Duplicate
+/// keys in the sample are ignored.
+///
+/// The random multipliers are odd but not necessarily prime, since being odd is all that matters
+/// for multiplying modulo a power of two.
+pub fn tune<I>(keys: &[I], options: &TuneOptions) -> Vec<Candidate<I>>
+where
+    I: Int + IntKey<Int = I>,
+{
+    let mut keys: Vec<u128> = keys.iter().map(|k| k.into_u128()).collect();
+    keys.sort_unstable();
+    keys.dedup();
+    let keys: Vec<I> = keys.into_iter().map(I::from_u128).collect();
+
+    let capacities = capacities(keys.len(), &options.capacities);
+    let strategies = strategies(&keys);
+
+    let mut candidates = Vec::new();
+    for prime in primes::<I>(options) {
+        for &strategy in &strategies {
+            let stats: Vec<MapStats> = capacities
+                .iter()
+                .map(|&capacity| evaluate(&keys, prime, strategy, capacity))
+                .collect();
+            let cost = stats
+                .iter()
+                .map(|s| s.estimated_lookup_cost(options.hit_ratio))
+                .sum::<f64>()
+                / stats.len() as f64;
+
+            candidates.push(Candidate {
+                prime,
+                strategy,
+                stats,
+                cost,
+            });
+        }
+    }
+
+    candidates.sort_by(|a, b| a.cost.partial_cmp(&b.cost).unwrap_or(Ordering::Equal));
+    candidates
+}
+
+fn evaluate<I: Int>(keys: &[I], prime: I, strategy: HashStrategy, capacity: usize) -> MapStats {
+    let mut chain_lens = vec![0; capacity];
+    for &key in keys {
+        chain_lens[strategy.apply(key).calc_index(capacity - 1, prime)] += 1;
+    }
+    MapStats::new(chain_lens.into_iter())
+}
+
+fn capacities(len: usize, requested: &[usize]) -> Vec<usize> {
+    let mut capacities: Vec<usize> = if requested.is_empty() {
+        // Mirrors the growth of `IntMap` with the default load factor of 90.9%.
+        let mut capacity = 1;
+        while len * 1000 / capacity > 909 {
+            capacity *= 2;
+        }
+        vec![capacity / 2, capacity, capacity * 2]
+    } else {
+        requested.iter().map(|c| c.next_power_of_two()).collect()
+    };
+
+    capacities.retain(|&c| c > 0);
+    capacities.sort_unstable();
+    capacities.dedup();
+    capacities
+}
+
+fn strategies<I: Int>(keys: &[I]) -> Vec<HashStrategy> {
+    let mut strategies = vec![HashStrategy::Identity];
+
+    let common_trailing_zeros = keys
+        .iter()
+        .map(|k| k.into_u128().trailing_zeros().min(I::BITS))
+        .min()
+        .unwrap_or(0);
+    if common_trailing_zeros > 0 && common_trailing_zeros < I::BITS {
+        strategies.push(HashStrategy::RotateRight(common_trailing_zeros));
+    }
+    if I::BITS > 8 {
+        strategies.push(HashStrategy::SwapBytes);
+    }
+    strategies.push(HashStrategy::XorShiftRight(I::BITS / 2));
+
+    strategies
+}
+
+fn primes<I: Int + IntKey<Int = I>>(options: &TuneOptions) -> Vec<I> {
+    // 2^128 divided by the golden ratio, as used by Fibonacci hashing.
+    const GOLDEN_RATIO: u128 = 0x9e37_79b9_7f4a_7c15_f39c_c060_5ced_c835;
+
+    let mut primes = vec![
+        I::PRIME,
+        I::from_u128((GOLDEN_RATIO >> (128 - I::BITS)) | 1),
+    ];
+
+    let mut state = options.seed;
+    for _ in 0..options.random_candidates {
+        let high = u128::from(split_mix_64(&mut state));
+        let low = u128::from(split_mix_64(&mut state));
+        let prime = I::from_u128((high << 64 | low) | 1);
+        if !primes.contains(&prime) {
+            primes.push(prime);
+        }
+    }
+
+    primes
+}
+
+fn split_mix_64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
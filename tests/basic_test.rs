@@ -2,7 +2,7 @@ extern crate rand;
 
 extern crate intmap;
 
-use intmap::{Entry, IntMap};
+use intmap::{Entry, IntKey, IntMap, InvariantError};
 
 #[cfg(test)]
 mod tests {
@@ -320,6 +320,16 @@ mod tests {
         assert_eq!(map.len(), 0);
     }
 
+    #[test]
+    fn map_drain_dropped_early() {
+        let mut map: IntMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+
+        assert_eq!(map.drain().take(10).count(), 10);
+        assert_eq!(map.len(), 0);
+        assert!(map.assert_count());
+        assert_eq!(map.iter().count(), 0);
+    }
+
     #[test]
     fn map_into_iter_empty() {
         let mut map: IntMap<u64, u64> = IntMap::new();
@@ -475,7 +485,7 @@ mod tests {
         let cases = [
             (10, 1337),
             (1028390123, 1337),
-            (12098312, 08082934),
+            (12098312, 8082934),
             (123981, 1337),
             (23498902348, 1337),
             (2198312093, 12983),
@@ -508,7 +518,7 @@ mod tests {
         let cases = [
             (10, 1337, 424242, 69),
             (10280123, 1337, 424242, 19283),
-            (12091292, 08082934, 424242, 1029381092),
+            (12091292, 8082934, 424242, 1029381092),
             (12390331, 1337, 429138, 19283),
             (23492348, 3912, 93925, 12309182),
             (21982093, 12983, 491832, 120398213),
@@ -557,14 +567,15 @@ mod tests {
     fn load_factor() {
         let mut map: IntMap<u64, u64> = IntMap::new();
 
-        map.set_load_factor(0.0);
-        assert_eq!(map.get_load_factor(), 0.0);
+        assert!(map.try_set_load_factor(0.0).is_err());
+        map.set_load_factor(0.001);
+        assert_eq!(map.get_load_factor(), 0.001);
 
         for i in 0..12 {
             map.insert(i, i);
         }
 
-        assert_eq!(map.capacity(), 16384);
+        assert_eq!(map.capacity(), 8192);
         assert!(map.load_rate() <= 1.);
         assert!(map.collisions().is_empty());
 
@@ -660,4 +671,1662 @@ mod tests {
         }
         assert_eq!(a, b);
     }
+
+    #[test]
+    fn validate() {
+        let mut map: IntMap<u64, u64> = IntMap::new();
+        assert_eq!(map.validate(), Ok(()));
+
+        for i in 0..1000 {
+            map.insert(i, i);
+        }
+        assert_eq!(map.validate(), Ok(()));
+
+        map.set_load_factor(0.5);
+        map.retain(|k, _| k % 3 == 0);
+        assert_eq!(map.validate(), Ok(()));
+
+        map.drain().take(5).count();
+        assert_eq!(map.validate(), Ok(()));
+    }
+
+    #[test]
+    fn invariant_error_display() {
+        let err = InvariantError::CountMismatch {
+            count: 3,
+            actual: 2,
+        };
+        assert_eq!(
+            err.to_string(),
+            "count is 3 but 2 key/value pairs are stored"
+        );
+    }
+
+    #[test]
+    fn stats() {
+        let map: IntMap<u64, u64> = IntMap::new();
+        let stats = map.stats();
+        assert_eq!(stats.len, 0);
+        assert_eq!(stats.capacity, 0);
+        assert_eq!(stats.max_chain_len, 0);
+        assert_eq!(stats.estimated_lookup_cost(0.5), 0.0);
+
+        let mut map: IntMap<u64, u64> = IntMap::with_capacity(64);
+        map.set_load_factor(4.0);
+        for i in 0..200 {
+            map.insert(i * 64, i);
+        }
+
+        let stats = map.stats();
+        assert_eq!(stats.len, 200);
+        assert_eq!(stats.capacity, map.capacity());
+        assert_eq!(stats.histogram.iter().sum::<usize>(), stats.capacity);
+        let pairs: usize = stats.histogram.iter().enumerate().map(|(l, n)| l * n).sum();
+        assert_eq!(pairs, stats.len);
+        assert_eq!(stats.max_chain_len, stats.histogram.len() - 1);
+        assert_eq!(
+            stats.colliding_slots() as u64,
+            map.collisions().values().sum::<u64>()
+        );
+        assert_eq!(stats.avg_probe_miss, map.load_rate() / 100.0);
+        assert_eq!(
+            stats.empty_slot_ratio,
+            1.0 - map.load() as f64 / map.capacity() as f64
+        );
+        assert!(stats.avg_probe_hit >= 1.0);
+        assert!(stats.estimated_lookup_cost(1.0) >= 3.0);
+    }
+
+    #[test]
+    fn tune() {
+        use intmap::tune::{tune, HashStrategy, TuneOptions};
+
+        for strategy in [
+            HashStrategy::Identity,
+            HashStrategy::RotateRight(3),
+            HashStrategy::SwapBytes,
+            HashStrategy::XorShiftRight(4),
+        ] {
+            let mut seen: Vec<u16> = (0..=u16::MAX).map(|k| strategy.apply(k)).collect();
+            seen.sort_unstable();
+            seen.dedup();
+            assert_eq!(seen.len(), 1 << 16, "{strategy:?} is not a bijection");
+        }
+        assert_eq!(HashStrategy::RotateRight(8).apply(0x12u32), 0x1200_0000);
+        assert_eq!(HashStrategy::SwapBytes.apply(0x1234u16), 0x3412);
+        assert_eq!(HashStrategy::XorShiftRight(4).apply(0xf0u8), 0xff);
+
+        // Keys that only differ in their upper bits collide without transformation.
+        let keys: Vec<u64> = (0..1024).map(|i| i << 40).collect();
+        let options = TuneOptions {
+            random_candidates: 8,
+            capacities: vec![1000, 2048],
+            ..TuneOptions::default()
+        };
+        let candidates = tune(&keys, &options);
+        assert_eq!(candidates.len(), 10 * 4);
+        assert!(candidates.windows(2).all(|w| w[0].cost <= w[1].cost));
+
+        let best = &candidates[0];
+        assert_ne!(best.strategy, HashStrategy::Identity);
+        assert_eq!(best.stats.len(), 2);
+        assert_eq!(best.stats[0].capacity, 1024);
+        assert_eq!(best.stats[1].capacity, 2048);
+        assert_eq!(best.stats[0].len, keys.len());
+
+        let identity = candidates
+            .iter()
+            .find(|c| c.prime == u64::PRIME && c.strategy == HashStrategy::Identity)
+            .unwrap();
+        assert_eq!(identity.stats[0].max_chain_len, keys.len());
+
+        let code = best.int_key_impl("MyKey", "self.0");
+        assert!(code.contains("type Int = u64;"));
+        assert!(code.contains(&format!("const PRIME: Self::Int = {:#x};", best.prime)));
+        assert!(code.contains(&best.strategy.expr("self.0")));
+    }
+
+    #[test]
+    fn multi_map() {
+        use intmap::multi_map::Entry as MultiEntry;
+        use intmap::IntMultiMap;
+
+        let mut map: IntMultiMap<u64, u64> = IntMultiMap::new();
+        assert!(map.is_empty());
+        assert!(map.get_all(1).is_empty());
+        assert_eq!(map.remove_one(1, &1), None);
+        assert!(map.remove_all(1).is_empty());
+
+        for i in 0..100 {
+            for j in 0..i % 4 {
+                map.insert(i, j);
+            }
+        }
+        assert_eq!(map.len(), 25 * (1 + 2 + 3));
+        assert_eq!(map.keys_len(), 75);
+        assert_eq!(map.iter().count(), map.len());
+        assert_eq!(map.keys().count(), map.keys_len());
+        assert_eq!(
+            map.iter_all().map(|(_, v)| v.len()).sum::<usize>(),
+            map.len()
+        );
+        assert_eq!(map.get_all(3), [0, 1, 2]);
+        assert_eq!(map.len_of(2), 2);
+        assert_eq!(map.len_of(4), 0);
+        assert!(!map.contains_key(4));
+
+        // Removing a value keeps the order of the others, removing the last one removes the key.
+        assert_eq!(map.remove_one(3, &1), Some(1));
+        assert_eq!(map.get_all(3), [0, 2]);
+        assert_eq!(map.remove_one(3, &1), None);
+        assert_eq!(map.remove_one(3, &0), Some(0));
+        assert_eq!(map.remove_one(3, &2), Some(2));
+        assert!(!map.contains_key(3));
+        assert_eq!(map.remove_all(7), [0, 1, 2]);
+        assert_eq!(map.len(), 150 - 6);
+
+        for (_, value) in map.iter_mut() {
+            *value += 10;
+        }
+        map.get_all_mut(2).reverse();
+        assert_eq!(map.get_all(2), [11, 10]);
+
+        match map.entry(5) {
+            MultiEntry::Occupied(mut entry) => {
+                assert_eq!(entry.len(), 1);
+                *entry.push(20) += 1;
+                assert_eq!(entry.get_all(), [10, 21]);
+            }
+            MultiEntry::Vacant(_) => panic!("key 5 has a value"),
+        }
+        match map.entry(5) {
+            MultiEntry::Occupied(entry) => assert_eq!(entry.remove_all(), [10, 21]),
+            MultiEntry::Vacant(_) => panic!("key 5 has values"),
+        }
+        assert_eq!(map.entry(5).or_insert(30), [30]);
+        assert_eq!(map.entry(5).or_insert(40), [30]);
+        map.entry(5).push(31);
+        assert_eq!(map.get_all(5), [30, 31]);
+        assert_eq!(map.len(), 150 - 6 - 1 + 2);
+
+        let collected: IntMultiMap<u64, u64> = map.clone().into_iter().collect();
+        assert_eq!(collected, map);
+        let mut pairs: Vec<(u64, u64)> = map.iter().map(|(k, v)| (k, *v)).collect();
+        let mut owned: Vec<(u64, u64)> = map.clone().into_iter().collect();
+        pairs.sort_unstable();
+        owned.sort_unstable();
+        assert_eq!(pairs, owned);
+
+        let mut other = IntMultiMap::new();
+        other.insert(1, 1);
+        other.insert(1, 2);
+        let mut reordered = IntMultiMap::new();
+        reordered.insert(1, 2);
+        reordered.insert(1, 1);
+        assert_ne!(other, reordered);
+        assert_eq!(format!("{other:?}"), "{1: [1, 2]}");
+
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.keys_len(), 0);
+    }
+
+    #[test]
+    fn bi_map() {
+        use intmap::bi_map::Overwritten;
+        use intmap::IntBiMap;
+
+        let mut map: IntBiMap<u32, u64> = IntBiMap::new();
+        for i in 0..100 {
+            assert_eq!(map.insert(i, u64::from(i) * 7), Overwritten::Neither);
+        }
+        assert_eq!(map.len(), 100);
+        assert_eq!(map.get_by_left(10), Some(70));
+        assert_eq!(map.get_by_right(70), Some(10));
+        assert_eq!(map.get_by_right(71), None);
+
+        assert_eq!(map.insert(10, 70), Overwritten::Pair(10, 70));
+        assert_eq!(map.insert(10, 1000), Overwritten::Left(10, 70));
+        assert!(!map.contains_right(70));
+        assert_eq!(map.insert(200, 1000), Overwritten::Right(10, 1000));
+        assert!(!map.contains_left(10));
+        assert_eq!(
+            map.insert(200, 77),
+            Overwritten::Both((200, 1000), (11, 77))
+        );
+        assert!(Overwritten::<u32, u64>::Pair(1, 1).did_overwrite());
+        assert!(!Overwritten::<u32, u64>::Neither.did_overwrite());
+        assert_eq!(map.len(), 99);
+
+        assert_eq!(map.insert_no_overwrite(200, 5), Err((200, 5)));
+        assert_eq!(map.insert_no_overwrite(5, 77), Err((5, 77)));
+        assert_eq!(map.insert_no_overwrite(300, 3000), Ok(()));
+
+        assert_eq!(map.remove_by_left(300), Some((300, 3000)));
+        assert_eq!(map.remove_by_right(77), Some((200, 77)));
+        assert_eq!(map.remove_by_right(77), None);
+        assert_eq!(map.len(), 98);
+
+        map.retain(|l, _| l % 2 == 0);
+        assert_eq!(map.len(), 49);
+        assert_eq!(map.left_values().count(), 49);
+        assert_eq!(map.right_values().count(), 49);
+        for (l, r) in &map {
+            assert_eq!(r, u64::from(l) * 7);
+            assert_eq!(map.get_by_right(r), Some(l));
+        }
+
+        let copy: IntBiMap<u32, u64> = map.clone().into_iter().collect();
+        assert_eq!(copy, map);
+        assert_eq!(
+            format!("{:?}", IntBiMap::<u8, u8>::from_iter([(1, 2)])),
+            "{1: 2}"
+        );
+
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.get_by_right(0), None);
+    }
+
+    #[test]
+    fn counter() {
+        use intmap::IntCounter;
+
+        let keys: Vec<u64> = get_random_range(2000).iter().map(|k| k % 300).collect();
+        let mut counter: IntCounter<u64> = keys.iter().copied().collect();
+        assert_eq!(counter.total(), 2000);
+        assert_eq!(counter.iter().map(|(_, &c)| c).sum::<u64>(), 2000);
+
+        let mut expected: Vec<(u64, u64)> = counter.iter().map(|(k, &c)| (k, c)).collect();
+        expected.sort_unstable_by_key(|&(_, c)| std::cmp::Reverse(c));
+        for n in [0, 1, 5, 50, counter.len(), counter.len() + 10] {
+            let top = counter.most_common(n);
+            assert_eq!(top.len(), n.min(counter.len()));
+            let counts: Vec<u64> = top.iter().map(|&(_, c)| c).collect();
+            let expected_counts: Vec<u64> = expected.iter().take(n).map(|&(_, c)| c).collect();
+            assert_eq!(counts, expected_counts);
+            for &(k, c) in &top {
+                assert_eq!(counter.get(k), c);
+            }
+        }
+
+        let key = expected[0].0;
+        let count = expected[0].1;
+        assert_eq!(counter.add(key), count + 1);
+        assert_eq!(counter.add_n(key, 0), count + 1);
+        assert_eq!(counter.subtract(key, 2), count - 1);
+        assert_eq!(counter.subtract(key, u64::MAX), 0);
+        assert!(!counter.contains_key(key));
+        assert_eq!(counter.subtract(key, 1), 0);
+        assert_eq!(counter.total(), 2000 - count);
+        assert_eq!(counter.remove(expected[1].0), expected[1].1);
+        assert_eq!(counter.remove(expected[1].0), 0);
+        assert_eq!(counter.total(), 2000 - count - expected[1].1);
+
+        let mut merged = IntCounter::new();
+        merged.merge(&counter);
+        merged.merge(&counter);
+        assert_eq!(merged.total(), 2 * counter.total());
+        assert_eq!(merged.len(), counter.len());
+        let doubled: IntCounter<u64> = counter.iter().map(|(k, &c)| (k, 2 * c)).collect();
+        assert_eq!(merged, doubled);
+        assert_eq!(merged.as_map().len(), merged.len());
+
+        let small: IntCounter<u8> = [1, 1].into_iter().collect();
+        assert_eq!(format!("{small:?}"), "{1: 2}");
+        assert_eq!(small.into_map().get(1), Some(&2));
+
+        counter.clear();
+        assert!(counter.is_empty());
+        assert_eq!(counter.total(), 0);
+    }
+
+    #[test]
+    fn counter_total_overflow() {
+        use intmap::IntCounter;
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut counter: IntCounter<u64> = IntCounter::new();
+        counter.add_n(1, u64::MAX - 1);
+        assert_eq!(counter.add(2), 1);
+
+        assert!(catch_unwind(AssertUnwindSafe(|| counter.add(2))).is_err());
+        assert!(catch_unwind(AssertUnwindSafe(|| counter.add_n(3, u64::MAX))).is_err());
+        assert_eq!(counter.total(), u64::MAX);
+        assert_eq!(counter.get(2), 1);
+        assert!(!counter.contains_key(3));
+
+        counter.subtract(1, 10);
+        assert_eq!(counter.add_n(2, 10), 11);
+        assert_eq!(counter.total(), u64::MAX);
+    }
+
+    #[test]
+    fn lru_cache() {
+        use intmap::IntLruCache;
+        use rand::prelude::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        // Compare with a naive model that stores the pairs from most to least recently used.
+        let mut rng = StdRng::seed_from_u64(4242);
+        let mut cache: IntLruCache<u64, u64> = IntLruCache::new(16);
+        let mut model: Vec<(u64, u64)> = Vec::new();
+
+        for i in 0..10_000 {
+            let key = rng.gen_range(0..40);
+            match rng.gen_range(0..5) {
+                0 | 1 => {
+                    let mut expected = Vec::new();
+                    if let Some(pos) = model.iter().position(|&(k, _)| k == key) {
+                        expected.push(model.remove(pos));
+                    }
+                    model.insert(0, (key, i));
+                    if model.len() > 16 {
+                        expected.push(model.pop().unwrap());
+                    }
+                    let evicted: Vec<_> = cache.put(key, i).into_iter().collect();
+                    assert_eq!(evicted, expected);
+                }
+                2 => {
+                    let pos = model.iter().position(|&(k, _)| k == key);
+                    let expected = pos.map(|pos| model.remove(pos));
+                    if let Some(pair) = expected {
+                        model.insert(0, pair);
+                    }
+                    assert_eq!(cache.get(key), expected.map(|(_, v)| v).as_ref());
+                }
+                3 => {
+                    let pos = model.iter().position(|&(k, _)| k == key);
+                    let expected = pos.map(|pos| model.remove(pos).1);
+                    assert_eq!(cache.remove(key), expected);
+                }
+                _ => {
+                    let expected = model.iter().find(|&&(k, _)| k == key).map(|(_, v)| v);
+                    assert_eq!(cache.peek(key), expected);
+                }
+            }
+
+            assert_eq!(cache.len(), model.len());
+            assert_eq!(cache.weight(), model.len() as u64);
+            let pairs: Vec<(u64, u64)> = cache.iter().map(|(k, &v)| (k, v)).collect();
+            assert_eq!(pairs, model);
+            assert_eq!(cache.iter().rev().count(), model.len());
+            assert_eq!(cache.peek_lru(), model.last().map(|(k, v)| (*k, v)));
+        }
+
+        let mut reversed: Vec<_> = cache.iter().rev().map(|(k, _)| k).collect();
+        reversed.reverse();
+        assert_eq!(reversed, model.iter().map(|&(k, _)| k).collect::<Vec<_>>());
+        while let Some(pair) = cache.pop_lru() {
+            assert_eq!(Some(pair), model.pop());
+        }
+        assert!(cache.is_empty());
+
+        let mut weighted: IntLruCache<u64, Vec<u8>> =
+            IntLruCache::with_weigher(10, |_, v| v.len() as u64);
+        assert!(weighted.put(1, vec![0; 4]).is_empty());
+        assert!(weighted.put(2, vec![0; 4]).is_empty());
+        *weighted.get_mut(1).unwrap() = vec![1];
+        let evicted = weighted.put(2, vec![0; 7]);
+        assert_eq!(evicted.len(), 2);
+        assert_eq!(
+            evicted.into_iter().map(|(k, _)| k).collect::<Vec<_>>(),
+            [2, 1]
+        );
+        assert_eq!(weighted.weight(), 7);
+        let evicted = weighted.put(3, vec![0; 11]);
+        assert_eq!(evicted.len(), 2);
+        assert!(weighted.is_empty());
+        assert_eq!(weighted.weight(), 0);
+        assert_eq!(weighted.max_weight(), 10);
+
+        let mut empty: IntLruCache<u64, u64> = IntLruCache::new(0);
+        assert_eq!(empty.put(1, 1).into_iter().collect::<Vec<_>>(), [(1, 1)]);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn ttl_map_rebuilds_stale_expiries() {
+        use intmap::ttl::{Clock, ManualClock};
+        use intmap::IntTtlMap;
+        use std::time::Duration;
+
+        let clock = ManualClock::new();
+        let mut map: IntTtlMap<u64, u64, _> = IntTtlMap::with_clock(clock.clone());
+        for i in 0..1000 {
+            map.insert(i, i, Duration::from_secs(3600));
+        }
+        assert_eq!(map.scheduled_len(), 1000);
+
+        // Replacing an entry with the same expiry keeps its scheduled expiry.
+        for i in 0..1000 {
+            map.insert(i, i, Duration::from_secs(3600));
+        }
+        assert_eq!(map.scheduled_len(), 1000);
+
+        // Replacing an entry with a new expiry leaves the old one behind until the stale
+        // expiries outnumber the entries.
+        for i in 0..1000 {
+            map.insert(i, i, Duration::from_secs(7200));
+        }
+        assert_eq!(map.scheduled_len(), 2000);
+        map.insert(0, 0, Duration::from_secs(60));
+        assert_eq!(map.scheduled_len(), 1000);
+
+        for i in 0..600 {
+            assert_eq!(map.remove(i), Some(i));
+        }
+        assert_eq!(map.scheduled_len(), 400 + 99);
+        assert_eq!(map.len(), 400);
+
+        // Stale expiries are skipped and dropped when they are due.
+        clock.advance(Duration::from_secs(7200));
+        assert_eq!(map.purge_expired(clock.now()).len(), 400);
+        assert_eq!(map.scheduled_len(), 0);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn ttl_map() {
+        use intmap::ttl::{Clock, ManualClock};
+        use intmap::IntTtlMap;
+        use rand::prelude::StdRng;
+        use rand::{Rng, SeedableRng};
+        use std::collections::HashMap;
+        use std::time::Duration;
+
+        let mut rng = StdRng::seed_from_u64(4242);
+        let clock = ManualClock::new();
+        let mut map: IntTtlMap<u64, u64, _> = IntTtlMap::with_clock(clock.clone());
+        // The model maps keys to their value and expiry in milliseconds since the start.
+        let mut model: HashMap<u64, (u64, u64)> = HashMap::new();
+        let mut now = 0;
+
+        for i in 0..5000 {
+            let key = rng.gen_range(0..200);
+            match rng.gen_range(0..10) {
+                0..=4 => {
+                    // TTLs from milliseconds to days, so every level of the wheel is used.
+                    let bits = rng.gen_range(1..37);
+                    let ttl = rng.gen_range(0..1u64 << bits);
+                    let old = map.insert(key, i, Duration::from_millis(ttl));
+                    let expected = model
+                        .insert(key, (i, now + ttl))
+                        .filter(|&(_, expiry)| expiry > now)
+                        .map(|(v, _)| v);
+                    assert_eq!(old, expected);
+                }
+                5 => {
+                    let expected = model
+                        .remove(&key)
+                        .filter(|&(_, expiry)| expiry > now)
+                        .map(|(v, _)| v);
+                    assert_eq!(map.remove(key), expected);
+                }
+                6..=7 => {
+                    let bits = rng.gen_range(1..34);
+                    let step = rng.gen_range(0..1u64 << bits);
+                    clock.advance(Duration::from_millis(step));
+                    now += step;
+                }
+                _ => {
+                    let mut purged = map.purge_expired(clock.now());
+                    purged.sort_unstable();
+                    let mut expected: Vec<(u64, u64)> = model
+                        .iter()
+                        .filter(|(_, &(_, expiry))| expiry <= now)
+                        .map(|(&k, &(v, _))| (k, v))
+                        .collect();
+                    expected.sort_unstable();
+                    assert_eq!(purged, expected);
+                    model.retain(|_, &mut (_, expiry)| expiry > now);
+                    assert_eq!(map.len(), model.len());
+                }
+            }
+
+            let expected = model
+                .get(&key)
+                .filter(|&&(_, expiry)| expiry > now)
+                .map(|(v, _)| v);
+            assert_eq!(map.get(key), expected);
+            assert_eq!(map.contains_key(key), expected.is_some());
+            // Every entry has a finite expiry, and the stale expiries never outnumber them by
+            // much.
+            let stale = map.scheduled_len() - map.len();
+            assert!(stale <= map.len().max(64));
+        }
+
+        let alive = model.values().filter(|&&(_, expiry)| expiry > now).count();
+        assert_eq!(map.iter().count(), alive);
+
+        // An expiry that overflows `Instant` never expires.
+        map.insert(1000, 1, Duration::MAX);
+        assert_eq!(map.expires_at(1000), Some(None));
+        clock.advance(Duration::from_secs(1 << 30));
+        assert!(map
+            .purge_expired(clock.now())
+            .iter()
+            .all(|&(k, _)| k != 1000));
+        assert_eq!(map.get(1000), Some(&1));
+
+        let deadline = clock.now() + Duration::from_millis(5);
+        map.insert_until(1001, 2, deadline);
+        assert_eq!(map.expires_at(1001), Some(Some(deadline)));
+        *map.get_mut(1001).unwrap() += 1;
+        clock.advance(Duration::from_millis(5));
+        assert_eq!(map.get_mut(1001), None);
+        assert_eq!(map.purge_expired(clock.now()), [(1001, 3)]);
+
+        map.clear();
+        assert!(map.is_empty());
+        assert!(map.purge_expired(clock.now()).is_empty());
+
+        // Resolution rounds expiries up.
+        let mut coarse: IntTtlMap<u64, u64, _> =
+            IntTtlMap::with_resolution(clock.clone(), Duration::from_secs(1));
+        coarse.insert(1, 1, Duration::from_millis(1500));
+        clock.advance(Duration::from_millis(1600));
+        assert_eq!(coarse.get(1), None);
+        assert!(coarse.purge_expired(clock.now()).is_empty());
+        clock.advance(Duration::from_millis(400));
+        assert_eq!(coarse.purge_expired(clock.now()), [(1, 1)]);
+    }
+
+    #[test]
+    fn persistent_map() {
+        use intmap::PersistentIntMap;
+        use rand::prelude::StdRng;
+        use rand::{Rng, SeedableRng};
+        use std::collections::HashMap;
+
+        let mut rng = StdRng::seed_from_u64(37);
+        let mut map: PersistentIntMap<u64, u64> = PersistentIntMap::new();
+        let mut model: HashMap<u64, u64> = HashMap::new();
+        let mut versions = Vec::new();
+
+        for i in 0..4000 {
+            // Mix small and full range keys to get both deep and shallow paths.
+            let key = if rng.gen_bool(0.5) {
+                rng.gen_range(0..500)
+            } else {
+                rng.gen::<u64>() | (1 << 63)
+            };
+
+            if rng.gen_range(0..3) == 0 {
+                let next = map.remove(key);
+                assert_eq!(next.len() + model.remove(&key).map_or(0, |_| 1), map.len());
+                map = next;
+            } else {
+                map = map.insert(key, i);
+                model.insert(key, i);
+            }
+            assert_eq!(map.len(), model.len());
+
+            if i % 100 == 0 {
+                versions.push((map.clone(), model.clone()));
+            }
+        }
+
+        // Older versions are not changed by later updates.
+        for (version, model) in &versions {
+            assert_eq!(version.len(), model.len());
+            assert_eq!(version.iter().len(), model.len());
+            for (k, v) in version {
+                assert_eq!(model.get(&k), Some(v));
+            }
+            for (&k, v) in model {
+                assert_eq!(version.get(k), Some(v));
+            }
+        }
+
+        // Removing all keys leaves an empty map.
+        let keys: Vec<u64> = model.keys().copied().collect();
+        let mut emptied = map.clone();
+        for &key in &keys {
+            assert!(emptied.remove_mut(key));
+            assert!(!emptied.remove_mut(key));
+        }
+        assert!(emptied.is_empty());
+        assert_eq!(emptied, PersistentIntMap::new());
+        assert_eq!(map.len(), keys.len());
+
+        // Conversion to and from IntMap.
+        let int_map: IntMap<u64, u64> = IntMap::from(&map);
+        assert_eq!(int_map.len(), map.len());
+        let back = PersistentIntMap::from(int_map);
+        assert_eq!(back, map);
+        assert!(!back.ptr_eq(&map));
+        assert!(map.clone().ptr_eq(&map));
+
+        // Removing an absent key keeps the version.
+        assert!(map.remove(1000).ptr_eq(&map));
+
+        let mut small: PersistentIntMap<u8, &str> = [(1, "a"), (33, "b")].into_iter().collect();
+        assert!(!small.insert_mut(1, "c"));
+        assert_eq!(format!("{small:?}"), r#"{1: "c", 33: "b"}"#);
+    }
+
+    #[test]
+    fn cow_map() {
+        use intmap::cow::Snapshot;
+        use intmap::CowIntMap;
+        use rand::prelude::StdRng;
+        use rand::{Rng, SeedableRng};
+        use std::collections::HashMap;
+
+        let mut rng = StdRng::seed_from_u64(38);
+        let mut map: CowIntMap<u64, u64> = CowIntMap::new();
+        let mut model: HashMap<u64, u64> = HashMap::new();
+        let mut snapshots: Vec<(Snapshot<u64, u64>, HashMap<u64, u64>)> = Vec::new();
+
+        for i in 0..5000 {
+            let key = rng.gen_range(0..800);
+            match rng.gen_range(0..4) {
+                0 => assert_eq!(map.remove(key), model.remove(&key)),
+                1 => {
+                    if let Some(value) = map.get_mut(key) {
+                        *value += 1;
+                    }
+                    if let Some(value) = model.get_mut(&key) {
+                        *value += 1;
+                    }
+                }
+                _ => assert_eq!(map.insert(key, i), model.insert(key, i)),
+            }
+            assert_eq!(map.len(), model.len());
+
+            if i % 250 == 0 {
+                snapshots.push((map.snapshot(), model.clone()));
+            }
+        }
+
+        map.retain(|k, _| k % 3 != 0);
+        model.retain(|k, _| k % 3 != 0);
+        assert_eq!(map.len(), model.len());
+        for (k, v) in &map {
+            assert_eq!(model.get(&k), Some(v));
+        }
+
+        // Snapshots don't see later mutations.
+        for (snapshot, model) in &snapshots {
+            assert_eq!(snapshot.len(), model.len());
+            assert_eq!(snapshot.iter().len(), model.len());
+            for (&k, v) in model {
+                assert_eq!(snapshot.get(k), Some(v));
+            }
+            assert_eq!(snapshot.keys().count(), model.len());
+            assert_eq!(snapshot.values().sum::<u64>(), model.values().sum::<u64>());
+            assert_eq!(snapshot, &snapshot.clone());
+        }
+
+        // Conversion to and from IntMap.
+        let int_map: IntMap<u64, u64> = map.clone().into_int_map();
+        assert_eq!(int_map.len(), model.len());
+        let cow = CowIntMap::from(int_map.clone());
+        assert_eq!(cow, map);
+        assert_eq!(IntMap::from(cow), int_map);
+
+        let snapshot = map.snapshot();
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(snapshot.len(), model.len());
+        map.insert(1, 2);
+        assert_eq!(format!("{map:?}"), "{1: 2}");
+    }
+
+    #[test]
+    fn cow_map_retain_panic() {
+        use intmap::CowIntMap;
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut map: CowIntMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+        let snapshot = map.snapshot();
+        let mut calls = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            map.retain(|_, _| {
+                calls += 1;
+                assert!(calls < 50);
+                calls % 2 == 0
+            })
+        }));
+        assert!(result.is_err());
+
+        // The pairs removed before the panic are counted.
+        assert_eq!(map.len(), map.iter().count());
+        assert!(map.len() < 100);
+        assert_eq!(snapshot.len(), 100);
+    }
+
+    #[test]
+    fn int_map_snapshot() {
+        let mut map: IntMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+        let snapshot = map.snapshot();
+        map.remove(1);
+        map.insert(2, 0);
+
+        assert_eq!(snapshot.len(), 100);
+        assert_eq!(snapshot.get(1), Some(&1));
+        assert_eq!(snapshot.get(2), Some(&2));
+        assert_eq!(map.snapshot().get(2), Some(&0));
+        assert_eq!(snapshot.iter().count(), 100);
+    }
+
+    #[test]
+    fn set_ops() {
+        use rand::prelude::StdRng;
+        use rand::{Rng, SeedableRng};
+        use std::collections::HashMap;
+
+        let mut rng = StdRng::seed_from_u64(39);
+
+        for round in 0..60 {
+            // Alternate between equal and different capacities.
+            let a_len = rng.gen_range(0..300);
+            let b_len = if round % 2 == 0 {
+                a_len
+            } else {
+                rng.gen_range(0..300)
+            };
+            let a: HashMap<u64, u64> = (0..a_len)
+                .map(|_| (rng.gen_range(0..400), rng.gen()))
+                .collect();
+            let b: HashMap<u64, u64> = (0..b_len)
+                .map(|_| (rng.gen_range(0..400), rng.gen()))
+                .collect();
+            let map_a: IntMap<u64, u64> = a.iter().map(|(&k, &v)| (k, v)).collect();
+            let map_b: IntMap<u64, u64> = b.iter().map(|(&k, &v)| (k, v)).collect();
+            let to_map =
+                |model: HashMap<u64, u64>| -> IntMap<u64, u64> { model.into_iter().collect() };
+
+            let mut merged = map_a.clone();
+            merged.merge_with(map_b.clone(), |_, x, y| x.wrapping_add(y));
+            let mut expected = a.clone();
+            for (&k, &v) in &b {
+                let e = expected.entry(k).or_insert(0);
+                *e = e.wrapping_add(v);
+            }
+            assert_eq!(merged, to_map(expected));
+            assert_eq!(merged.validate(), Ok(()));
+
+            let mut union = map_a.clone();
+            union.union(map_b.clone());
+            let mut expected = b.clone();
+            expected.extend(a.iter().map(|(&k, &v)| (k, v)));
+            assert_eq!(union, to_map(expected));
+            assert_eq!(union.validate(), Ok(()));
+
+            let mut sym = map_a.clone();
+            sym.symmetric_difference(map_b.clone());
+            let mut expected: HashMap<u64, u64> = a
+                .iter()
+                .filter(|(k, _)| !b.contains_key(k))
+                .map(|(&k, &v)| (k, v))
+                .collect();
+            expected.extend(b.iter().filter(|(k, _)| !a.contains_key(k)));
+            assert_eq!(sym, to_map(expected));
+            assert_eq!(sym.validate(), Ok(()));
+
+            let mut inter = map_a.clone();
+            inter.intersection_with(&map_b, |_, x, y| *x ^= y);
+            let expected = a
+                .iter()
+                .filter_map(|(&k, &v)| b.get(&k).map(|w| (k, v ^ w)))
+                .collect();
+            assert_eq!(inter, to_map(expected));
+
+            let keys: IntMap<u64, ()> = b.keys().map(|&k| (k, ())).collect();
+            let mut kept = map_a.clone();
+            kept.retain_keys_in(&keys);
+            let expected = a
+                .iter()
+                .filter(|(k, _)| b.contains_key(k))
+                .map(|(&k, &v)| (k, v))
+                .collect();
+            assert_eq!(kept, to_map(expected));
+
+            let mut diff = map_a.clone();
+            diff.difference(&keys);
+            let expected = a
+                .iter()
+                .filter(|(k, _)| !b.contains_key(k))
+                .map(|(&k, &v)| (k, v))
+                .collect();
+            assert_eq!(diff, to_map(expected));
+            assert_eq!(diff.validate(), Ok(()));
+
+            assert!(kept.is_submap_of(&map_a));
+            assert!(diff.is_submap_of(&map_a));
+            assert!(merged.is_submap_of(&merged));
+            assert_eq!(
+                map_a.is_submap_of(&map_b),
+                a.iter().all(|(k, v)| b.get(k) == Some(v))
+            );
+        }
+
+        // Merging into an empty map adopts the capacity of the other map.
+        let other: IntMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+        let mut empty: IntMap<u64, u64> = IntMap::new();
+        empty.union(other.clone());
+        assert_eq!(empty, other);
+        assert_eq!(empty.capacity(), other.capacity());
+    }
+
+    #[test]
+    fn batch() {
+        use rand::prelude::StdRng;
+        use rand::{Rng, SeedableRng};
+        use std::collections::HashMap;
+
+        let mut rng = StdRng::seed_from_u64(40);
+        let mut map: IntMap<u64, u64> = IntMap::new();
+        let mut model: HashMap<u64, u64> = HashMap::new();
+
+        for round in 0..50 {
+            let len = if round % 3 == 0 { 5 } else { 500 };
+            let pairs: Vec<(u64, u64)> = (0..len)
+                .map(|_| (rng.gen_range(0..5000), rng.gen()))
+                .collect();
+            map.insert_batch(pairs.clone());
+            model.extend(pairs);
+            assert_eq!(map.len(), model.len());
+            assert_eq!(map.validate(), Ok(()));
+
+            let keys: Vec<u64> = (0..len).map(|_| rng.gen_range(0..6000)).collect();
+            let expected: Vec<Option<&u64>> = keys.iter().map(|k| model.get(k)).collect();
+            assert_eq!(map.get_many(&keys), expected);
+
+            let mut out = vec![Some(&0); keys.len()];
+            map.get_batch(&keys, &mut out);
+            assert_eq!(out, expected);
+        }
+
+        // Keys that repeat within and across chunks keep their last value.
+        let mut repeated: IntMap<u64, u64> = IntMap::new();
+        repeated.insert_batch((0..40).map(|i| (i % 3, i)));
+        assert_eq!(repeated, [(0, 39), (1, 37), (2, 38)].into_iter().collect());
+        assert_eq!(repeated.validate(), Ok(()));
+
+        // A single batch that grows the map several times.
+        let pairs: Vec<(u64, u64)> = (0..100_000)
+            .map(|_| (rng.gen_range(0..200_000), rng.gen()))
+            .collect();
+        map.insert_batch(pairs.clone());
+        model.extend(pairs);
+        assert_eq!(map.len(), model.len());
+        assert_eq!(map.validate(), Ok(()));
+
+        let keys: Vec<u64> = (0..5000).map(|_| rng.gen_range(0..250_000)).collect();
+        let expected: Vec<Option<&u64>> = keys.iter().map(|k| model.get(k)).collect();
+        assert_eq!(map.get_many(&keys), expected);
+
+        let empty: IntMap<u64, u64> = IntMap::new();
+        assert_eq!(empty.get_many(&[1, 2]), [None, None]);
+
+        // Replacing existing keys doesn't grow a map at its load factor.
+        let mut full: IntMap<u64, u64> = IntMap::with_capacity(1000);
+        let capacity = full.capacity();
+        let len = capacity * 909 / 1000;
+        full.extend((0..len as u64).map(|i| (i, i)));
+        full.insert(len as u64, 0);
+        assert_eq!(full.capacity(), capacity);
+        full.insert_batch((0..len as u64).map(|i| (i, i + 1)));
+        assert_eq!(full.capacity(), capacity);
+        assert_eq!(full.get(0), Some(&1));
+    }
+
+    #[test]
+    fn iter_exact_size_and_double_ended() {
+        let data = get_random_range(1000);
+        let mut map: IntMap<u64, u64> = data.iter().map(|&k| (k, k + 1)).collect();
+        let len = map.len();
+
+        let mut iter = map.iter();
+        assert_eq!(iter.len(), len);
+        iter.next();
+        iter.next_back();
+        assert_eq!(iter.len(), len - 2);
+        assert_eq!(iter.clone().count(), len - 2);
+
+        // Iterating from both ends yields every pair once.
+        let forward: Vec<(u64, u64)> = map.iter().map(|(k, &v)| (k, v)).collect();
+        let mut backward: Vec<(u64, u64)> = map.iter().rev().map(|(k, &v)| (k, v)).collect();
+        backward.reverse();
+        assert_eq!(forward, backward);
+
+        let mut iter = map.iter();
+        let mut seen = Vec::new();
+        while let Some((k, _)) = iter.next() {
+            seen.push(k);
+            if let Some((k, _)) = iter.next_back() {
+                seen.push(k);
+            }
+            assert_eq!(iter.len(), len - seen.len());
+        }
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+        seen.sort_unstable();
+        let mut keys: Vec<u64> = map.keys().collect();
+        keys.sort_unstable();
+        assert_eq!(seen, keys);
+
+        assert_eq!(map.keys().len(), len);
+        assert_eq!(map.values().rev().len(), len);
+        assert_eq!(map.keys().rev().next_back(), map.keys().next());
+        assert_eq!(map.values().zip(map.keys()).len(), len);
+        assert_eq!(map.iter_mut().len(), len);
+        assert_eq!(map.values_mut().rev().len(), len);
+        for (k, v) in map.iter_mut().rev() {
+            assert_eq!(*v, k + 1);
+            *v = k;
+        }
+        assert!(map.iter().all(|(k, &v)| k == v));
+
+        let mut into_iter = map.clone().into_iter();
+        assert_eq!(into_iter.len(), len);
+        into_iter.next_back();
+        assert_eq!(into_iter.size_hint(), (len - 1, Some(len - 1)));
+
+        let mut drain = map.drain();
+        assert_eq!(drain.len(), len);
+        drain.next();
+        drain.next_back();
+        assert_eq!(drain.len(), len - 2);
+        drop(drain);
+        assert!(map.is_empty());
+
+        let small: IntMap<u64, &str> = [(1, "a")].into_iter().collect();
+        assert_eq!(format!("{:?}", small.iter()), r#"[(1, "a")]"#);
+        assert_eq!(format!("{:?}", small.keys()), "[1]");
+        assert_eq!(format!("{:?}", small.values()), r#"["a"]"#);
+        assert_eq!(
+            format!("{:?}", small.into_iter()),
+            "IntoIter { remaining: 1, .. }"
+        );
+    }
+
+    #[test]
+    fn panic_safety() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        // A value that panics when dropped if it's armed.
+        #[derive(Clone)]
+        struct Bomb(bool);
+
+        impl Drop for Bomb {
+            fn drop(&mut self) {
+                if self.0 && !std::thread::panicking() {
+                    panic!("bomb");
+                }
+            }
+        }
+
+        // A key that panics when converted while `FRAGILE` is set.
+        static FRAGILE: AtomicBool = AtomicBool::new(false);
+
+        #[derive(Clone, Copy, Debug)]
+        struct FragileKey(u64);
+
+        impl IntKey for FragileKey {
+            type Int = u64;
+            const PRIME: u64 = u64::PRIME;
+
+            fn into_int(self) -> u64 {
+                assert!(self.0 != 13 || !FRAGILE.load(Ordering::Relaxed), "fragile");
+                self.0
+            }
+        }
+
+        let new_map =
+            |bomb: u64| -> IntMap<u64, Bomb> { (0..200).map(|k| (k, Bomb(k == bomb))).collect() };
+        let disarm = |map: &mut IntMap<u64, Bomb>| map.values_mut().for_each(|bomb| bomb.0 = false);
+
+        // A panicking predicate.
+        let mut map = new_map(u64::MAX);
+        let mut calls = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            map.retain(|k, _| {
+                calls += 1;
+                assert!(calls < 100, "predicate");
+                k % 2 == 0
+            })
+        }));
+        assert!(result.is_err());
+        assert!(map.assert_count());
+        assert!(map.len() < 200 && map.len() > 100);
+
+        // A panicking drop of a removed value.
+        let mut map = new_map(51);
+        assert!(catch_unwind(AssertUnwindSafe(|| map.retain(|k, _| k % 2 == 0))).is_err());
+        assert!(map.assert_count());
+        assert!(!map.contains_key(51));
+        disarm(&mut map);
+
+        // A panicking drop while clearing.
+        let mut map = new_map(7);
+        assert!(catch_unwind(AssertUnwindSafe(|| map.clear())).is_err());
+        assert!(map.assert_count());
+        assert!(!map.contains_key(7));
+        disarm(&mut map);
+
+        // A panicking drop while dropping a partially consumed drain removes all pairs anyway.
+        let mut map = new_map(150);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let mut drain = map.drain();
+            drain
+                .by_ref()
+                .take(10)
+                .for_each(|(_, mut bomb)| bomb.0 = false);
+            if let Some((_, mut bomb)) = drain.next_back() {
+                bomb.0 = false;
+            }
+        }));
+        assert!(result.is_err());
+        assert!(map.assert_count());
+        assert!(map.is_empty());
+
+        // A panicking drop of a yielded pair.
+        let mut map = new_map(150);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            for (k, bomb) in map.drain() {
+                if k == 150 {
+                    drop(bomb);
+                } else {
+                    std::mem::forget(bomb);
+                }
+            }
+        }));
+        assert!(result.is_err());
+        assert!(map.assert_count());
+        assert!(map.is_empty());
+
+        // A panicking iterator.
+        let mut map: IntMap<u64, u64> = (0..10).map(|k| (k, k)).collect();
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            map.extend((10..1000).map(|k| {
+                assert!(k < 500, "iterator");
+                (k, k)
+            }))
+        }));
+        assert!(result.is_err());
+        assert!(map.assert_count());
+        assert_eq!(map.len(), 500);
+        assert_eq!(map.validate(), Ok(()));
+
+        let result = catch_unwind(|| {
+            (0..1000)
+                .map(|k| {
+                    assert!(k < 500, "iterator");
+                    (k, k)
+                })
+                .collect::<IntMap<u64, u64>>()
+        });
+        assert!(result.is_err());
+
+        // A panicking key while growing the map.
+        let mut map: IntMap<FragileKey, u64> = (0..100).map(|k| (FragileKey(k), k)).collect();
+        let capacity = map.capacity();
+        FRAGILE.store(true, Ordering::Relaxed);
+        assert!(catch_unwind(AssertUnwindSafe(|| map.reserve(1000))).is_err());
+        FRAGILE.store(false, Ordering::Relaxed);
+        assert!(map.assert_count());
+        assert_eq!(map.capacity(), capacity);
+        assert_eq!(map.len(), 100);
+        assert_eq!(map.validate(), Ok(()));
+    }
+
+    #[test]
+    fn retain_variants() {
+        use std::collections::HashMap;
+
+        let data = get_random_range(2000);
+        let model: HashMap<u64, u64> = data.iter().map(|&k| (k, k % 7)).collect();
+        let map: IntMap<u64, u64> = model.iter().map(|(&k, &v)| (k, v)).collect();
+
+        let mut counted = map.clone();
+        let removed = counted.retain_count(|_, &v| v < 3);
+        let expected: HashMap<u64, u64> = model
+            .iter()
+            .filter(|(_, &v)| v < 3)
+            .map(|(&k, &v)| (k, v))
+            .collect();
+        assert_eq!(removed, model.len() - expected.len());
+        assert_eq!(counted.len(), expected.len());
+        assert!(counted.iter().all(|(k, v)| expected.get(&k) == Some(v)));
+        assert!(counted.assert_count());
+
+        let mut updated = map.clone();
+        updated.retain_mut(|k, v| {
+            *v += k % 2;
+            *v < 4
+        });
+        let expected: HashMap<u64, u64> = model
+            .iter()
+            .map(|(&k, &v)| (k, v + k % 2))
+            .filter(|&(_, v)| v < 4)
+            .collect();
+        assert_eq!(updated.len(), expected.len());
+        assert!(updated.iter().all(|(k, v)| expected.get(&k) == Some(v)));
+        assert!(updated.assert_count());
+
+        // Stopping early removes only the pairs visited before the error.
+        let mut stopped = map.clone();
+        let mut visited = Vec::new();
+        let result = stopped.try_retain(|k, v| {
+            if visited.len() == 500 {
+                return Err(k);
+            }
+            visited.push(k);
+            *v += 100;
+            Ok(k % 2 == 0)
+        });
+        let stopped_at = result.unwrap_err();
+        assert!(!visited.contains(&stopped_at));
+        assert!(stopped.assert_count());
+        assert_eq!(
+            stopped.len(),
+            model.len() - visited.iter().filter(|&&k| k % 2 != 0).count()
+        );
+        for (k, &v) in stopped.iter() {
+            if visited.contains(&k) {
+                assert_eq!(v, model[&k] + 100);
+            } else {
+                assert_eq!(v, model[&k]);
+            }
+        }
+
+        let mut all = map;
+        assert_eq!(all.try_retain(|_, _| Ok::<_, ()>(false)), Ok(()));
+        assert!(all.is_empty());
+    }
+
+    #[test]
+    fn entry_remove_shrinks() {
+        use intmap::{IntMapBuilder, ShrinkPolicy};
+
+        let mut map: IntMap<u64, u64> = IntMapBuilder::new()
+            .shrink_policy(ShrinkPolicy::BelowLoadFactor(0.25))
+            .build()
+            .unwrap();
+        map.extend((0..1000).map(|i| (i, i)));
+        let grown = map.capacity();
+
+        for i in 0..990 {
+            match map.entry(i) {
+                Entry::Occupied(entry) => assert_eq!(entry.remove(), i),
+                Entry::Vacant(_) => panic!("{i} is missing"),
+            }
+        }
+
+        assert_eq!(map.len(), 10);
+        assert!(map.capacity() < grown / 16);
+        assert!(map.load_rate() >= 25.0);
+        assert_eq!(map.validate(), Ok(()));
+        assert!((990..1000).all(|i| map.get(i) == Some(&i)));
+    }
+
+    #[test]
+    fn cursor_mut_shrinks_on_drop() {
+        use intmap::{IntMapBuilder, ShrinkPolicy};
+
+        let mut map: IntMap<u64, u64> = IntMapBuilder::new()
+            .shrink_policy(ShrinkPolicy::BelowLoadFactor(0.25))
+            .build()
+            .unwrap();
+        map.extend((0..1000).map(|i| (i, i)));
+        let grown = map.capacity();
+
+        let mut cursor = map.cursor_mut();
+        while let Some((key, _)) = cursor.current() {
+            if key < 990 {
+                cursor.remove_current();
+            } else {
+                cursor.move_next();
+            }
+        }
+        drop(cursor);
+
+        assert_eq!(map.len(), 10);
+        assert!(map.capacity() < grown / 16);
+        assert!(map.load_rate() >= 25.0);
+        assert_eq!(map.validate(), Ok(()));
+    }
+
+    #[test]
+    fn cursor_mut() {
+        use rand::prelude::StdRng;
+        use rand::{Rng, SeedableRng};
+        use std::collections::HashMap;
+
+        let mut rng = StdRng::seed_from_u64(44);
+        let data = get_random_range(3000);
+        let mut map: IntMap<u64, u64> = data.iter().map(|&k| (k, k % 1000)).collect();
+        let mut model: HashMap<u64, u64> = data.iter().map(|&k| (k, k % 1000)).collect();
+
+        let mut visited = Vec::new();
+        let mut cursor = map.cursor_mut();
+        while let Some((key, &value)) = cursor.current() {
+            let peeked = cursor.peek_next().map(|(k, _)| k);
+            visited.push(key);
+            let action = rng.gen_range(0..4);
+            match action {
+                0 => {
+                    assert_eq!(cursor.remove_current(), Some((key, value)));
+                    model.remove(&key);
+                }
+                1 => {
+                    assert_eq!(cursor.replace_current(value + 1), Some(value));
+                    model.insert(key, value + 1);
+                    cursor.move_next();
+                }
+                2 => {
+                    // Inserting doesn't move the cursor.
+                    let new_key = rng.gen::<u64>() | 1;
+                    assert_eq!(cursor.insert(new_key, 0), model.insert(new_key, 0));
+                    assert_eq!(cursor.current().map(|(k, _)| k), Some(key));
+                    assert_eq!(cursor.insert(key, value), Some(value));
+                    cursor.move_next();
+                }
+                _ => {
+                    *cursor.current_mut().unwrap().1 *= 2;
+                    model.insert(key, value * 2);
+                    cursor.move_next();
+                }
+            }
+            if action != 2 {
+                // An insert might add a pair to a bucket that was skipped by the peek.
+                assert_eq!(cursor.current().map(|(k, _)| k), peeked);
+            }
+        }
+        assert!(cursor.peek_next().is_none());
+        assert_eq!(cursor.remove_current(), None);
+        drop(cursor);
+
+        // Every original pair is visited exactly once, new pairs at most once.
+        let mut original: Vec<u64> = visited
+            .iter()
+            .copied()
+            .filter(|k| data.contains(k))
+            .collect();
+        original.sort_unstable();
+        let mut expected = data.clone();
+        expected.sort_unstable();
+        assert_eq!(original, expected);
+        let len = visited.len();
+        visited.sort_unstable();
+        visited.dedup();
+        assert_eq!(visited.len(), len);
+
+        assert_eq!(map.len(), model.len());
+        assert!(map.iter().all(|(k, v)| model.get(&k) == Some(v)));
+        assert_eq!(map.validate(), Ok(()));
+        assert!(map.load_rate() <= map.get_load_factor() as f64 * 100.0);
+
+        // Inserting into an empty map doesn't visit the new pair.
+        let mut empty: IntMap<u64, u64> = IntMap::new();
+        let mut cursor = empty.cursor_mut();
+        assert!(cursor.current().is_none());
+        assert_eq!(cursor.insert(1, 1), None);
+        assert!(cursor.current().is_none());
+        drop(cursor);
+        assert_eq!(empty.get(1), Some(&1));
+    }
+
+    #[test]
+    fn index_map() {
+        use intmap::index_map::Entry as IndexEntry;
+        use intmap::IntIndexMap;
+        use rand::prelude::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(4242);
+        let mut map: IntIndexMap<u64, u64> = IntIndexMap::new();
+        let mut model: Vec<(u64, u64)> = Vec::new();
+
+        for _ in 0..20_000 {
+            let key = rng.gen_range(0..500);
+            let pos = model.iter().position(|kv| kv.0 == key);
+            match rng.gen_range(0..6) {
+                0 | 1 => {
+                    let old = match pos {
+                        Some(pos) => Some(std::mem::replace(&mut model[pos].1, key * 2)),
+                        None => {
+                            model.push((key, key * 2));
+                            None
+                        }
+                    };
+                    assert_eq!(map.insert(key, key * 2), old);
+                }
+                2 => {
+                    let removed = pos.map(|pos| model.swap_remove(pos).1);
+                    assert_eq!(map.swap_remove(key), removed);
+                }
+                3 => {
+                    let removed = pos.map(|pos| model.remove(pos).1);
+                    assert_eq!(map.shift_remove(key), removed);
+                }
+                4 => match map.entry(key) {
+                    IndexEntry::Occupied(mut entry) => {
+                        assert_eq!(Some(entry.index()), pos);
+                        *entry.get_mut() += 1;
+                        model[pos.unwrap()].1 += 1;
+                    }
+                    IndexEntry::Vacant(entry) => {
+                        assert_eq!(entry.index(), model.len());
+                        entry.insert(1);
+                        model.push((key, 1));
+                    }
+                },
+                _ => {
+                    assert_eq!(map.get_index_of(key), pos);
+                    assert_eq!(map.get(key), pos.map(|pos| &model[pos].1));
+                }
+            }
+        }
+
+        assert_eq!(map.len(), model.len());
+        assert!(map.iter().map(|(k, v)| (k, *v)).eq(model.iter().copied()));
+        assert!(map
+            .iter()
+            .rev()
+            .map(|(k, v)| (k, *v))
+            .eq(model.iter().rev().copied()));
+        assert_eq!(map.iter().len(), model.len());
+        for (i, (key, value)) in model.iter().enumerate() {
+            assert_eq!(map.get_index(i), Some((*key, value)));
+            assert_eq!(map.get_full(*key), Some((i, value)));
+        }
+
+        map.sort_by(|_, a, _, b| b.cmp(a));
+        model.sort_by_key(|kv| std::cmp::Reverse(kv.1));
+        assert_eq!(map.as_slice(), &model[..]);
+        assert!(model
+            .iter()
+            .enumerate()
+            .all(|(i, kv)| map.get_index_of(kv.0) == Some(i)));
+
+        map.retain(|k, _| k % 3 != 0);
+        model.retain(|kv| kv.0 % 3 != 0);
+        assert_eq!(map.as_slice(), &model[..]);
+        assert!(model
+            .iter()
+            .enumerate()
+            .all(|(i, kv)| map.get_index_of(kv.0) == Some(i)));
+        assert_eq!(map.get(3), None);
+
+        map.sort_keys();
+        model.sort_unstable();
+        assert_eq!(
+            map.keys().collect::<Vec<_>>(),
+            model.iter().map(|kv| kv.0).collect::<Vec<_>>()
+        );
+        assert_eq!(map.swap_remove_index(0), Some(model.swap_remove(0)));
+        assert_eq!(map.shift_remove_index(0), Some(model.remove(0)));
+        assert_eq!(map.pop(), model.pop());
+        assert!(model
+            .iter()
+            .enumerate()
+            .all(|(i, kv)| map.get_index_of(kv.0) == Some(i)));
+
+        // Equality ignores the order.
+        let reversed: IntIndexMap<u64, u64> = map.clone().into_iter().rev().collect();
+        assert_eq!(reversed, map);
+        assert_eq!(reversed.first(), map.last());
+
+        // A panicking comparator leaves the positions consistent.
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            map.sort_by(|a, _, b, _| {
+                assert!(a != model[3].0 && b != model[3].0);
+                a.cmp(&b)
+            })
+        }));
+        assert!(result.is_err());
+        assert!((0..map.len()).all(|i| map.get_index_of(map.get_index(i).unwrap().0) == Some(i)));
+    }
+
+    #[test]
+    fn builder() {
+        use intmap::{ConfigError, IndexStrategy, IntMapBuilder, ShrinkPolicy};
+
+        // Invalid load factors are rejected instead of growing forever.
+        for load_factor in [0.0, -1.0, 0.0004, f32::NAN, f32::INFINITY] {
+            let result = IntMapBuilder::new()
+                .load_factor(load_factor)
+                .build::<u64, u64>();
+            assert!(matches!(result, Err(ConfigError::InvalidLoadFactor { .. })));
+
+            let mut map: IntMap<u64, u64> = IntMap::new();
+            assert!(map.try_set_load_factor(load_factor).is_err());
+            assert_eq!(map.get_load_factor(), 0.909);
+            let result = std::panic::catch_unwind(move || map.set_load_factor(load_factor));
+            assert!(result.is_err());
+        }
+
+        assert_eq!(
+            IntMapBuilder::new()
+                .load_factor(1.0)
+                .shrink_policy(ShrinkPolicy::BelowLoadFactor(0.6))
+                .build::<u64, u64>(),
+            Err(ConfigError::InvalidShrinkLoadFactor {
+                shrink_load_factor: 0.6,
+                load_factor: 1.0
+            })
+        );
+        assert_eq!(
+            IntMapBuilder::new().seed(1).build::<u64, u64>(),
+            Err(ConfigError::SeedWithoutMultiplyShift)
+        );
+        assert_eq!(
+            IntMapBuilder::new()
+                .capacity(usize::MAX)
+                .build::<u64, u64>(),
+            Err(ConfigError::CapacityOverflow {
+                capacity: usize::MAX
+            })
+        );
+
+        // The capacity takes the load factor into account.
+        let mut map: IntMap<u64, u64> = IntMapBuilder::new()
+            .capacity(1000)
+            .load_factor(0.5)
+            .build()
+            .unwrap();
+        let capacity = map.capacity();
+        assert!(capacity >= 2000);
+        map.extend((0..1000).map(|i| (i, i)));
+        assert_eq!(map.capacity(), capacity);
+
+        // The shrink load factor must stay compatible with the load factor.
+        let mut shrinking: IntMap<u64, u64> = IntMapBuilder::new()
+            .shrink_policy(ShrinkPolicy::BelowLoadFactor(0.2))
+            .build()
+            .unwrap();
+        assert!(shrinking.try_set_load_factor(0.3).is_err());
+        assert_eq!(shrinking.try_set_load_factor(0.4), Ok(()));
+
+        let data = get_random_range(10_000);
+        shrinking.extend(data.iter().map(|&k| (k, k)));
+        let grown = shrinking.capacity();
+        for &k in &data[100..] {
+            assert_eq!(shrinking.remove(k), Some(k));
+        }
+        assert!(shrinking.capacity() < grown / 16);
+        assert!(shrinking.load_rate() >= 20.0);
+        assert_eq!(shrinking.validate(), Ok(()));
+        shrinking.retain(|k, _| k % 2 == 0);
+        assert!(shrinking.load_rate() >= 20.0 || shrinking.capacity() == 2);
+        assert_eq!(shrinking.validate(), Ok(()));
+        assert!(data[..100]
+            .iter()
+            .all(|&k| (shrinking.get(k) == Some(&k)) == (k % 2 == 0)));
+
+        // Maps with every index strategy behave the same.
+        let builders = [
+            IntMapBuilder::new(),
+            IntMapBuilder::new().index_strategy(IndexStrategy::MultiplyShift),
+            IntMapBuilder::new()
+                .index_strategy(IndexStrategy::MultiplyShift)
+                .seed(4242),
+        ];
+        let mut maps: Vec<IntMap<u64, u64>> = builders
+            .iter()
+            .map(|builder| builder.build().unwrap())
+            .collect();
+        for map in &mut maps {
+            // Keys that only differ in their high bits are spread by multiply-shift.
+            map.extend((0..1000).map(|i| (i << 40, i)));
+            map.extend(data.iter().map(|&k| (k, k)));
+            assert_eq!(map.validate(), Ok(()));
+        }
+        assert_eq!(maps[0], maps[1]);
+        assert_eq!(maps[1], maps[2]);
+        assert!(maps[1].stats().max_chain_len < maps[0].stats().max_chain_len);
+
+        // Set operations between maps with different index strategies.
+        let mut union = maps[1].clone();
+        union.union(maps[0].clone());
+        assert_eq!(union, maps[0]);
+        let mut difference = maps[2].clone();
+        difference.difference(&maps[0]);
+        assert!(difference.is_empty());
+        assert!(maps[0].is_submap_of(&maps[2]));
+
+        // Shrinking to fit releases everything of an empty map.
+        let mut map = maps.pop().unwrap();
+        map.retain(|k, _| k < 10);
+        map.shrink_to_fit();
+        assert!(map.capacity() <= 16);
+        assert_eq!(map.validate(), Ok(()));
+        map.clear();
+        map.shrink_to_fit();
+        assert_eq!(map.capacity(), 0);
+        map.insert(1, 1);
+        assert_eq!(map.get(1), Some(&1));
+
+        // Converting to `CowIntMap` keeps the index strategy, also when the map grows.
+        let mut cow: intmap::CowIntMap<u64, u64> = maps.pop().unwrap().into();
+        let snapshot = cow.snapshot();
+        cow.extend((0..10_000).map(|i| (i << 32, i)));
+        assert_eq!(snapshot.len(), maps[0].len());
+        let grown = cow.into_int_map();
+        assert_eq!(grown.validate(), Ok(()));
+        assert!((0..10_000).all(|i| grown.get(i << 32) == Some(&i)));
+        assert!(maps[0]
+            .iter()
+            .all(|(k, v)| grown.get(k) == Some(v) || k % (1 << 32) == 0));
+    }
+
+    #[test]
+    fn table_sizing_needs_multiply_shift() {
+        use intmap::{ConfigError, IntMapBuilder, TableSizing};
+
+        assert_eq!(
+            IntMapBuilder::new()
+                .table_sizing(TableSizing::OneAndAHalf)
+                .build::<u64, u64>(),
+            Err(ConfigError::TableSizingWithoutMultiplyShift)
+        );
+    }
+
+    #[test]
+    fn table_sizing_grows_by_half() {
+        use intmap::{IndexStrategy, IntMapBuilder, TableSizing};
+
+        let data = get_random_range(10_000);
+        let mut map: IntMap<u64, u64> = IntMapBuilder::new()
+            .index_strategy(IndexStrategy::MultiplyShift)
+            .table_sizing(TableSizing::OneAndAHalf)
+            .build()
+            .unwrap();
+
+        let mut capacities = Vec::new();
+        for &k in &data {
+            assert!(map.insert_checked(k, k));
+            if capacities.last() != Some(&map.capacity()) {
+                capacities.push(map.capacity());
+            }
+        }
+        assert_eq!(&capacities[..6], &[2, 3, 4, 6, 9, 13]);
+        assert_eq!(map.len(), data.len());
+        assert_eq!(map.validate(), Ok(()));
+
+        for &k in &data {
+            assert_eq!(map.get(k), Some(&k));
+        }
+
+        // The table never has more than 1.5 times the slots needed for the load factor.
+        let needed = (map.len() as f32 / map.get_load_factor()).ceil() as usize;
+        assert!(map.capacity() >= needed);
+        assert!(map.capacity() <= needed * 3 / 2 + 1);
+    }
+
+    #[test]
+    fn table_sizing_with_capacity() {
+        use intmap::{IndexStrategy, IntMapBuilder, TableSizing};
+
+        // Preallocation picks the smallest table that fits.
+        let map: IntMap<u64, u64> = IntMapBuilder::new()
+            .index_strategy(IndexStrategy::MultiplyShift)
+            .table_sizing(TableSizing::OneAndAHalf)
+            .capacity(1000)
+            .load_factor(1.0)
+            .build()
+            .unwrap();
+        assert_eq!(map.capacity(), 1066);
+        assert_eq!(map.validate(), Ok(()));
+    }
+
+    #[test]
+    fn table_sizing_shrink_to_fit() {
+        use intmap::{IndexStrategy, IntMapBuilder, TableSizing};
+
+        let data = get_random_range(10_000);
+        let mut map: IntMap<u64, u64> = IntMapBuilder::new()
+            .index_strategy(IndexStrategy::MultiplyShift)
+            .table_sizing(TableSizing::OneAndAHalf)
+            .build()
+            .unwrap();
+        map.extend(data.iter().map(|&k| (k, k)));
+
+        for &k in &data[..9_000] {
+            assert_eq!(map.remove(k), Some(k));
+        }
+        map.shrink_to_fit();
+        assert!(map.capacity() < 2 * 1000);
+        assert_eq!(map.len(), 1000);
+        assert_eq!(map.validate(), Ok(()));
+
+        for &k in &data[..9_000] {
+            assert!(!map.contains_key(k));
+        }
+        for &k in &data[9_000..] {
+            assert_eq!(map.get(k), Some(&k));
+        }
+    }
+
+    #[test]
+    fn table_sizing_shrink_policy() {
+        use intmap::{IndexStrategy, IntMapBuilder, ShrinkPolicy, TableSizing};
+
+        let data = get_random_range(10_000);
+        let mut map: IntMap<u64, u64> = IntMapBuilder::new()
+            .index_strategy(IndexStrategy::MultiplyShift)
+            .table_sizing(TableSizing::OneAndAHalf)
+            .shrink_policy(ShrinkPolicy::BelowLoadFactor(0.25))
+            .build()
+            .unwrap();
+        map.extend(data.iter().map(|&k| (k, k)));
+
+        let capacity = map.capacity();
+        for &k in &data[..9_900] {
+            assert_eq!(map.remove(k), Some(k));
+        }
+        assert!(map.capacity() < capacity / 10);
+        assert_eq!(map.len(), 100);
+        assert_eq!(map.validate(), Ok(()));
+
+        for &k in &data[9_900..] {
+            assert_eq!(map.get(k), Some(&k));
+        }
+    }
 }
@@ -0,0 +1,237 @@
+extern crate intmap;
+
+use intmap::small::Entry;
+use intmap::{ConfigError, IntMap, SmallIntMap};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_map_inline() {
+        let mut map: SmallIntMap<u64, u64, 4> = SmallIntMap::new();
+        for i in 0..4 {
+            assert!(map.insert_checked(i, i));
+        }
+        assert_eq!(map.capacity(), 4);
+
+        assert_eq!(map.insert(2, 20), Some(2));
+        assert!(!map.insert_checked(2, 0));
+        assert_eq!(map.remove(0), Some(0));
+        assert_eq!(map.remove(0), None);
+        assert!(map.insert_checked(4, 4));
+        assert!(!map.spilled());
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.get(2), Some(&20));
+        assert!(!map.contains_key(0));
+
+        *map.get_mut(4).unwrap() += 1;
+        map.values_mut().for_each(|v| *v *= 2);
+        assert!(!map.spilled());
+        assert_eq!(
+            map,
+            [(1, 2), (2, 40), (3, 6), (4, 10)].into_iter().collect()
+        );
+        assert_eq!(
+            IntMap::from(map),
+            [(1, 2), (2, 40), (3, 6), (4, 10)].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn small_map_spills() {
+        let mut map: SmallIntMap<u64, u64, 4> = SmallIntMap::new();
+        for i in 0..14 {
+            assert_eq!(map.insert(i, i), None);
+        }
+        assert!(map.spilled());
+        assert_eq!(map.len(), 14);
+        assert!((0..14).all(|i| map.get(i) == Some(&i)));
+
+        assert_eq!(map.remove(13), Some(13));
+        assert!(map.spilled());
+        assert_eq!(IntMap::from(map), (0..13).map(|i| (i, i)).collect());
+    }
+
+    #[test]
+    fn small_map_entry() {
+        for count in [2, 14] {
+            let mut map: SmallIntMap<u64, u64, 4> = (0..count).map(|i| (i, i)).collect();
+
+            *map.entry(30).or_insert(0) += 1;
+            *map.entry(31).or_default() += 2;
+            map.entry(1).and_modify(|v| *v = 10);
+            assert_eq!(map.get(30), Some(&1));
+            assert_eq!(map.get(31), Some(&2));
+            assert_eq!(map.get(1), Some(&10));
+
+            match map.entry(1) {
+                Entry::Occupied(entry) => assert_eq!(entry.remove(), 10),
+                Entry::Vacant(_) => panic!("key 1 is occupied"),
+            }
+            assert!(matches!(map.entry(1), Entry::Vacant(_)));
+            assert_eq!(map.len(), count as usize + 1);
+        }
+    }
+
+    #[test]
+    fn small_map_shrink_to_fit() {
+        let mut map: SmallIntMap<u64, u64, 4> = (0..14).map(|i| (i, i)).collect();
+
+        // Moves back inline once the pairs fit.
+        map.retain(|k, _| k < 3);
+        assert!(map.spilled());
+        map.shrink_to_fit();
+        assert!(!map.spilled());
+        assert_eq!(map.capacity(), 4);
+
+        let mut pairs: Vec<(u64, u64)> = map.clone().into_iter().collect();
+        pairs.sort_unstable();
+        assert_eq!(pairs, vec![(0, 0), (1, 1), (2, 2)]);
+        assert_eq!(map.keys().rev().count(), 3);
+        assert_eq!(map.values().sum::<u64>(), 3);
+    }
+
+    #[test]
+    fn small_map_collect_and_with_capacity() {
+        let collected: SmallIntMap<u64, u64, 4> = (0..10).map(|k| (k, k)).collect();
+        assert!(collected.spilled());
+        assert_eq!(collected.len(), 10);
+
+        assert!(!SmallIntMap::<u64, u64, 4>::with_capacity(4).spilled());
+        assert!(SmallIntMap::<u64, u64, 4>::with_capacity(5).spilled());
+    }
+
+    #[test]
+    fn small_map_clear() {
+        for count in [3, 14] {
+            let mut map: SmallIntMap<u64, u64, 4> = (0..count).map(|i| (i, i)).collect();
+            map.clear();
+            assert!(map.is_empty());
+            assert_eq!(map.iter().next(), None);
+            assert_eq!(map.get(1), None);
+        }
+    }
+
+    #[test]
+    fn small_map_retain() {
+        for count in [4, 14] {
+            let mut map: SmallIntMap<u64, u64, 4> = (0..count).map(|i| (i, i)).collect();
+
+            assert_eq!(map.retain_count(|k, _| k != 1), 1);
+            map.retain_mut(|k, v| {
+                *v += 1;
+                k != 2
+            });
+            assert_eq!(map.len(), count as usize - 2);
+            assert!(map.iter().all(|(k, v)| *v == k + 1));
+
+            let mut budget = 1;
+            let result = map.try_retain(|_, _| {
+                if budget == 0 {
+                    return Err("out of budget");
+                }
+                budget -= 1;
+                Ok(false)
+            });
+            assert_eq!(result, Err("out of budget"));
+            assert_eq!(map.len(), count as usize - 3);
+        }
+    }
+
+    #[test]
+    fn small_map_drain() {
+        let mut map: SmallIntMap<u64, u64, 4> = (0..3).map(|i| (i, i)).collect();
+        let mut pairs: Vec<(u64, u64)> = map.drain().collect();
+        pairs.sort_unstable();
+        assert_eq!(pairs, vec![(0, 0), (1, 1), (2, 2)]);
+        assert!(map.is_empty());
+        assert!(!map.spilled());
+
+        let mut map: SmallIntMap<u64, u64, 4> = (0..14).map(|i| (i, i)).collect();
+        let capacity = map.capacity();
+        let mut drain = map.drain();
+        assert_eq!(drain.len(), 14);
+        assert!(drain.next().is_some());
+        assert!(drain.next_back().is_some());
+        drop(drain);
+        assert!(map.is_empty());
+        assert!(map.spilled());
+        assert_eq!(map.capacity(), capacity);
+    }
+
+    #[test]
+    fn small_map_load_factor() {
+        let mut map: SmallIntMap<u64, u64, 4> = SmallIntMap::new();
+        assert_eq!(map.get_load_factor(), 0.909);
+
+        map.set_load_factor(0.5);
+        assert_eq!(map.get_load_factor(), 0.5);
+        map.extend((0..100).map(|i| (i, i)));
+        assert!(map.spilled());
+        assert_eq!(map.capacity(), 256);
+
+        map.set_load_factor(2.0);
+        assert_eq!(map.get_load_factor(), 2.0);
+        assert_eq!(
+            map.try_set_load_factor(0.0),
+            Err(ConfigError::InvalidLoadFactor { load_factor: 0.0 })
+        );
+        assert_eq!(map.get_load_factor(), 2.0);
+
+        let mut map: SmallIntMap<u64, u64, 4> = SmallIntMap::new();
+        assert_eq!(
+            map.try_set_load_factor(f32::INFINITY),
+            Err(ConfigError::InvalidLoadFactor {
+                load_factor: f32::INFINITY
+            })
+        );
+        assert_eq!(map.get_load_factor(), 0.909);
+    }
+
+    #[test]
+    fn small_map_set_ops() {
+        // Inline and spilled maps on either side.
+        for (a_len, b_len) in [(2, 2), (2, 14), (14, 2), (14, 14)] {
+            let a: SmallIntMap<u64, u64, 4> = (0..a_len).map(|i| (i, i)).collect();
+            let b: SmallIntMap<u64, u64, 4> = (1..b_len + 1).map(|i| (i, 100)).collect();
+            let a_model: IntMap<u64, u64> = IntMap::from(a.clone());
+            let b_model: IntMap<u64, u64> = IntMap::from(b.clone());
+
+            let mut merged = a.clone();
+            merged.merge_with(b.clone(), |_, a, b| a + b);
+            let mut model = a_model.clone();
+            model.merge_with(b_model.clone(), |_, a, b| a + b);
+            assert_eq!(IntMap::from(merged), model);
+
+            let mut union = a.clone();
+            union.union(b.clone());
+            let mut model = a_model.clone();
+            model.union(b_model.clone());
+            assert_eq!(IntMap::from(union), model);
+
+            let mut symmetric = a.clone();
+            symmetric.symmetric_difference(b.clone());
+            let mut model = a_model.clone();
+            model.symmetric_difference(b_model.clone());
+            assert_eq!(IntMap::from(symmetric), model);
+
+            let mut intersection = a.clone();
+            intersection.intersection_with(&b, |_, a, b| *a += b);
+            let mut model = a_model.clone();
+            model.intersection_with(&b_model, |_, a, b| *a += b);
+            assert_eq!(IntMap::from(intersection), model);
+
+            let mut kept = a.clone();
+            kept.retain_keys_in(&b);
+            assert_eq!(kept.len(), a_len.min(b_len + 1) as usize - 1);
+            assert!(kept.is_submap_of(&a));
+            assert!(!kept.is_submap_of(&b));
+
+            let mut difference = a.clone();
+            difference.difference(&b);
+            assert!(difference.keys().all(|k| k == 0 || k > b_len));
+            assert!(difference.is_submap_of(&a));
+        }
+    }
+}
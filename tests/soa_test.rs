@@ -0,0 +1,244 @@
+extern crate rand;
+
+extern crate intmap;
+
+use intmap::soa::Entry;
+use intmap::{
+    ConfigError, IndexStrategy, IntMap, IntMapBuilder, IntSoaMap, ShrinkPolicy, TableSizing,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_random_range(count: usize) -> Vec<u64> {
+        use rand::prelude::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut vec = Vec::new();
+        let mut rng = StdRng::seed_from_u64(4242);
+
+        for _ in 0..count {
+            vec.push(rng.gen::<u64>());
+        }
+
+        vec.sort();
+        vec.dedup();
+
+        vec
+    }
+
+    #[test]
+    fn soa_map_get_insert() {
+        let count = 20_000;
+        let data = get_random_range(count);
+        let mut map: IntSoaMap<u64, [u64; 8]> = IntSoaMap::new();
+
+        for s in data.iter() {
+            assert!(
+                map.insert_checked(*s, [*s; 8]),
+                "insert failed! key: {:?}",
+                s
+            );
+            assert!(!map.insert_checked(*s, [0; 8]));
+        }
+
+        assert_eq!(map.len(), count);
+        assert!(map.capacity().is_power_of_two());
+
+        for s in data.iter() {
+            assert_eq!(map.get(*s), Some(&[*s; 8]), "get failed! key: {:?}", s);
+            assert!(map.contains_key(*s));
+            assert!(!map.contains_key(s.wrapping_add(1)));
+        }
+
+        assert_eq!(map.insert(data[0], [1; 8]), Some([data[0]; 8]));
+        map.get_mut(data[0]).unwrap()[0] = 2;
+        assert_eq!(map.get(data[0]), Some(&[2, 1, 1, 1, 1, 1, 1, 1]));
+    }
+
+    #[test]
+    fn soa_map_remove() {
+        let mut map: IntSoaMap<u64, u64> = (0..20_000).map(|i| (i, i + 1)).collect();
+
+        // Every removal moves the last pair, whose chain must be updated.
+        for i in (0..20_000).step_by(3) {
+            assert_eq!(map.remove(i), Some(i + 1));
+            assert_eq!(map.remove(i), None);
+        }
+
+        assert_eq!(map.len(), 13_333);
+        for i in 0..20_000 {
+            assert_eq!(map.get(i), (i % 3 != 0).then(|| i + 1).as_ref());
+        }
+
+        for i in 0..20_000 {
+            map.remove(i);
+        }
+        assert!(map.is_empty());
+        map.insert(1, 1);
+        assert_eq!(map.get(1), Some(&1));
+    }
+
+    #[test]
+    fn soa_map_retain() {
+        let mut map: IntSoaMap<u64, u64> = (0..1_000).map(|i| (i, i * 2)).collect();
+
+        map.retain_keys(|k| k % 5 != 0);
+        assert_eq!(map.len(), 800);
+        assert!(map.keys().all(|k| k % 5 != 0));
+
+        map.retain(|_, v| v % 3 != 0);
+        assert_eq!(map.len(), 533);
+        assert!(map.iter().all(|(k, v)| *v == k * 2 && k % 3 != 0));
+        assert!((0..1_000).all(|k| map.contains_key(k) == (k % 5 != 0 && k % 3 != 0)));
+    }
+
+    #[test]
+    fn soa_map_entry() {
+        let mut map: IntSoaMap<u64, [u64; 8]> = IntSoaMap::new();
+
+        *map.entry(1).or_default() = [1; 8];
+        map.entry(1).and_modify(|v| v[0] = 2);
+        *map.entry(2).or_insert([2; 8]) = [3; 8];
+        map.entry(3).or_insert_with(|| [4; 8])[1] = 5;
+        assert_eq!(map.get(1), Some(&[2, 1, 1, 1, 1, 1, 1, 1]));
+        assert_eq!(map.get(2), Some(&[3; 8]));
+        assert_eq!(map.get(3), Some(&[4, 5, 4, 4, 4, 4, 4, 4]));
+
+        match map.entry(1) {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(entry.insert([0; 8]), [2, 1, 1, 1, 1, 1, 1, 1]);
+                assert_eq!(entry.remove(), [0; 8]);
+            }
+            Entry::Vacant(_) => panic!("key 1 is occupied"),
+        }
+        assert!(matches!(map.entry(1), Entry::Vacant(_)));
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn soa_map_keys_and_values_stay_aligned() {
+        let mut map: IntSoaMap<u64, [u64; 8]> = (0..1_000).map(|i| (i, [i; 8])).collect();
+        for i in (0..1_000).step_by(7) {
+            map.remove(i);
+        }
+
+        map.values_mut().for_each(|v| v[1] = 1);
+        assert!(map.iter_mut().all(|(k, v)| v[0] == k && v[1] == 1));
+
+        assert!(map
+            .keys()
+            .zip(map.values())
+            .all(|(k, v)| map.get(k) == Some(v)));
+        assert_eq!(map.iter().len(), map.len());
+        assert!(map
+            .iter()
+            .rev()
+            .eq(map.keys().rev().zip(map.values().rev())));
+
+        let int_map = IntMap::from(map.clone());
+        assert_eq!(int_map.len(), map.len());
+        assert_eq!(IntSoaMap::from(int_map), map);
+
+        let mut pairs: Vec<(u64, [u64; 8])> = map.into_iter().collect();
+        pairs.sort_unstable();
+        assert_eq!(pairs.len(), 857);
+        assert!(pairs.iter().all(|(k, v)| v[0] == *k && k % 7 != 0));
+    }
+
+    #[test]
+    fn soa_map_clear_and_reserve() {
+        let mut map: IntSoaMap<u64, u64> = (0..1_000).map(|i| (i, i)).collect();
+
+        let capacity = map.capacity();
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.iter().count(), 0);
+        assert_eq!(map.capacity(), capacity);
+        assert_eq!(map.get(1), None);
+
+        let mut map: IntSoaMap<u64, u64> = IntSoaMap::with_capacity(100);
+        assert_eq!(map.capacity(), 128);
+        map.reserve(200);
+        assert_eq!(map.capacity(), 256);
+    }
+
+    #[test]
+    fn soa_map_load_factor() {
+        let mut map: IntSoaMap<u64, u64> = IntSoaMap::new();
+        assert_eq!(map.get_load_factor(), 0.909);
+
+        map.set_load_factor(2.0);
+        map.extend((0..1_000).map(|i| (i, i)));
+        assert_eq!(map.capacity(), 512);
+
+        map.set_load_factor(0.5);
+        assert_eq!(map.capacity(), 2_048);
+        assert!((0..1_000).all(|i| map.get(i) == Some(&i)));
+
+        assert_eq!(
+            map.try_set_load_factor(0.0),
+            Err(ConfigError::InvalidLoadFactor { load_factor: 0.0 })
+        );
+        assert_eq!(map.get_load_factor(), 0.5);
+    }
+
+    #[test]
+    fn soa_map_shrink_to_fit() {
+        let mut map: IntSoaMap<u64, u64> = IntSoaMap::with_capacity(1_000);
+        assert_eq!(map.capacity(), 1_024);
+
+        map.extend((0..10).map(|i| (i, i)));
+        map.shrink_to_fit();
+        assert_eq!(map.capacity(), 16);
+        assert!((0..10).all(|i| map.get(i) == Some(&i)));
+
+        map.clear();
+        map.shrink_to_fit();
+        assert_eq!(map.capacity(), 0);
+        assert_eq!(map.get(1), None);
+        map.insert(1, 1);
+        assert_eq!(map.get(1), Some(&1));
+    }
+
+    #[test]
+    fn soa_map_builder() {
+        let mut map: IntSoaMap<u64, u64> = IntMapBuilder::new()
+            .capacity(1_000)
+            .load_factor(0.5)
+            .shrink_policy(ShrinkPolicy::BelowLoadFactor(0.1))
+            .index_strategy(IndexStrategy::MultiplyShift)
+            .seed(42)
+            .table_sizing(TableSizing::OneAndAHalf)
+            .build_soa()
+            .unwrap();
+        assert_eq!(map.get_load_factor(), 0.5);
+        let capacity = map.capacity();
+        assert!(capacity >= 2_000);
+
+        // Keys that only differ in their high bits.
+        for i in 0..1_000 {
+            map.insert(i << 40, i);
+        }
+        assert_eq!(map.capacity(), capacity);
+        assert!((0..1_000).all(|i| map.get(i << 40) == Some(&i)));
+
+        // Shrinks below the load factor of the shrink policy.
+        map.retain_keys(|k| k < 50 << 40);
+        assert_eq!(map.len(), 50);
+        assert!(map.capacity() < capacity);
+        assert!((0..50).all(|i| map.get(i << 40) == Some(&i)));
+
+        assert_eq!(
+            IntMapBuilder::new().seed(1).build_soa::<u64, u64>(),
+            Err(ConfigError::SeedWithoutMultiplyShift)
+        );
+        assert!(matches!(
+            IntMapBuilder::new()
+                .capacity(u32::MAX as usize)
+                .build_soa::<u64, u64>(),
+            Err(ConfigError::CapacityOverflow { .. })
+        ));
+    }
+}
@@ -0,0 +1,286 @@
+extern crate rand;
+
+extern crate intmap;
+
+use intmap::swiss::Entry;
+use intmap::{ConfigError, IntMap, IntSwissMap};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_random_range(count: usize) -> Vec<u64> {
+        use rand::prelude::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut vec = Vec::new();
+        let mut rng = StdRng::seed_from_u64(4242);
+
+        for _ in 0..count {
+            vec.push(rng.gen::<u64>());
+        }
+
+        vec.sort();
+        vec.dedup();
+
+        vec
+    }
+
+    #[test]
+    fn swiss_map_get_insert() {
+        let count = 20_000;
+        let data = get_random_range(count);
+        let mut map: IntSwissMap<u64, u64> = IntSwissMap::new();
+
+        for s in data.iter() {
+            assert!(map.insert_checked(*s, *s), "insert failed! key: {:?}", s);
+            assert!(!map.insert_checked(*s, 0));
+        }
+
+        assert_eq!(map.len(), count);
+        assert!(map.capacity() >= count);
+
+        for s in data.iter() {
+            assert_eq!(map.get(*s), Some(s), "get failed! key: {:?}", s);
+            assert!(!map.contains_key(s.wrapping_add(1) | 1 << 63));
+        }
+
+        assert_eq!(map.insert(data[0], 1), Some(data[0]));
+        *map.get_mut(data[0]).unwrap() += 1;
+        assert_eq!(map.get(data[0]), Some(&2));
+    }
+
+    #[test]
+    fn swiss_map_remove() {
+        let mut map: IntSwissMap<u64, u64> = (0..20_000).map(|i| (i, i + 1)).collect();
+
+        // Removed pairs leave tombstones that lookups must probe past.
+        for i in (0..20_000).step_by(2) {
+            assert_eq!(map.remove(i), Some(i + 1));
+            assert_eq!(map.remove(i), None);
+        }
+
+        assert_eq!(map.len(), 10_000);
+        for i in 0..20_000 {
+            assert_eq!(map.contains_key(i), i % 2 == 1);
+        }
+
+        // Reinserting reuses the tombstones without growing.
+        let capacity = map.capacity();
+        for i in (0..20_000).step_by(2) {
+            assert!(map.insert_checked(i, i + 1));
+        }
+        assert_eq!(map.capacity(), capacity);
+        assert!((0..20_000).all(|i| map.get(i) == Some(&(i + 1))));
+    }
+
+    #[test]
+    fn swiss_map_every_small_key() {
+        let mut map: IntSwissMap<u8, u8> = (0..=255).map(|k| (k, k)).collect();
+        assert_eq!(map.len(), 256);
+        assert!((0..=255).all(|k| map.get(k) == Some(&k)));
+
+        for k in 0..=255 {
+            assert_eq!(map.remove(k), Some(k));
+            map.insert(k, !k);
+        }
+        assert!((0..=255).all(|k| map.get(k) == Some(&!k)));
+    }
+
+    #[test]
+    fn swiss_map_entry() {
+        let mut map: IntSwissMap<u64, u64> = IntSwissMap::new();
+
+        *map.entry(1).or_insert(0) += 5;
+        *map.entry(1).or_default() += 5;
+        *map.entry(2).or_insert_with(|| 7) += 1;
+        map.entry(2).and_modify(|v| *v *= 2);
+        assert_eq!(map.get(1), Some(&10));
+        assert_eq!(map.get(2), Some(&16));
+
+        match map.entry(1) {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(entry.insert(11), 10);
+                assert_eq!(entry.remove(), 11);
+            }
+            Entry::Vacant(_) => panic!("key 1 is occupied"),
+        }
+        assert!(matches!(map.entry(1), Entry::Vacant(_)));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn swiss_map_iter() {
+        let mut map: IntSwissMap<u64, u64> = (0..1_000).map(|i| (i, i)).collect();
+
+        map.values_mut().for_each(|v| *v += 1);
+        for (k, v) in map.iter_mut() {
+            *v += k;
+        }
+
+        let mut pairs: Vec<(u64, u64)> = map.iter().map(|(k, v)| (k, *v)).collect();
+        pairs.sort_unstable();
+        assert_eq!(
+            pairs,
+            (0..1_000).map(|i| (i, 2 * i + 1)).collect::<Vec<_>>()
+        );
+        assert_eq!(map.keys().len(), 1_000);
+        assert_eq!(map.values().sum::<u64>(), 1_000 * 1_000);
+
+        let int_map = IntMap::from(map.clone());
+        assert_eq!(int_map.len(), 1_000);
+        assert_eq!(IntSwissMap::from(int_map), map);
+    }
+
+    #[test]
+    fn swiss_map_retain() {
+        let mut map: IntSwissMap<u64, u64> = (0..1_000).map(|i| (i, i)).collect();
+
+        map.retain(|k, _| k % 3 != 0);
+        assert_eq!(map.len(), 666);
+        assert_eq!(map.retain_count(|k, _| k % 3 != 1), 333);
+        assert_eq!(map.len(), 333);
+        assert!(map.keys().all(|k| k % 3 == 2));
+
+        map.retain_mut(|k, v| {
+            *v += 1;
+            k < 500
+        });
+        assert_eq!(map.len(), 166);
+        assert!(map.iter().all(|(k, v)| *v == k + 1));
+
+        let mut budget = 10;
+        let result = map.try_retain(|_, _| {
+            if budget == 0 {
+                return Err("out of budget");
+            }
+            budget -= 1;
+            Ok(false)
+        });
+        assert_eq!(result, Err("out of budget"));
+        assert_eq!(map.len(), 156);
+    }
+
+    #[test]
+    fn swiss_map_drain() {
+        let mut map: IntSwissMap<u64, u64> = (0..1_000).map(|i| (i, i)).collect();
+        let capacity = map.capacity();
+
+        let mut drain = map.drain();
+        assert_eq!(drain.len(), 1_000);
+        assert!(drain.next().is_some());
+        assert!(drain.next_back().is_some());
+        assert_eq!(drain.len(), 998);
+        drop(drain);
+
+        assert!(map.is_empty());
+        assert_eq!(map.capacity(), capacity);
+
+        // The tombstones were dropped, so the map can be refilled without growing.
+        map.extend((0..capacity as u64).map(|i| (i, i)));
+        assert_eq!(map.capacity(), capacity);
+
+        let mut pairs: Vec<(u64, u64)> = map.drain().collect();
+        pairs.sort_unstable();
+        assert_eq!(
+            pairs,
+            (0..capacity as u64).map(|i| (i, i)).collect::<Vec<_>>()
+        );
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn swiss_map_clear() {
+        let mut map: IntSwissMap<u64, u64> = (0..1_000).map(|i| (i, i)).collect();
+
+        let capacity = map.capacity();
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.iter().count(), 0);
+        assert_eq!(map.capacity(), capacity);
+        assert_eq!(map.get(1), None);
+    }
+
+    #[test]
+    fn swiss_map_load_factor() {
+        let mut map: IntSwissMap<u64, u64> = IntSwissMap::new();
+        assert_eq!(map.get_load_factor(), 0.875);
+
+        map.set_load_factor(0.5);
+        map.extend((0..1_000).map(|i| (i, i)));
+        assert_eq!(map.get_load_factor(), 0.5);
+        assert_eq!(map.capacity(), 1_024);
+
+        // Lowering the load factor doubles the table, so the capacity is kept.
+        map.set_load_factor(0.25);
+        assert_eq!(map.capacity(), 1_024);
+        assert!((0..1_000).all(|i| map.get(i) == Some(&i)));
+
+        map.set_load_factor(2.0);
+        assert_eq!(map.get_load_factor(), 0.875);
+        assert!(map.try_set_load_factor(f32::NAN).is_err());
+        assert_eq!(
+            map.try_set_load_factor(0.0),
+            Err(ConfigError::InvalidLoadFactor { load_factor: 0.0 })
+        );
+        assert_eq!(map.get_load_factor(), 0.875);
+    }
+
+    #[test]
+    fn swiss_map_shrink_to_fit() {
+        let mut map: IntSwissMap<u64, u64> = IntSwissMap::with_capacity(1_000);
+        assert!(map.capacity() >= 1_000);
+
+        map.extend((0..10).map(|i| (i, i)));
+        map.shrink_to_fit();
+        assert_eq!(map.capacity(), 14);
+        assert!((0..10).all(|i| map.get(i) == Some(&i)));
+
+        map.clear();
+        map.shrink_to_fit();
+        assert_eq!(map.capacity(), 0);
+        assert_eq!(map.get(1), None);
+        map.insert(1, 1);
+        assert_eq!(map.get(1), Some(&1));
+    }
+
+    #[test]
+    fn swiss_map_set_ops() {
+        let a: IntSwissMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+        let b: IntSwissMap<u64, u64> = (50..150).map(|i| (i, 1)).collect();
+
+        let mut merged = a.clone();
+        merged.merge_with(b.clone(), |_, a, b| a + b);
+        assert_eq!(merged.len(), 150);
+        assert!((0..50).all(|i| merged.get(i) == Some(&i)));
+        assert!((50..100).all(|i| merged.get(i) == Some(&(i + 1))));
+        assert!((100..150).all(|i| merged.get(i) == Some(&1)));
+
+        let mut union = a.clone();
+        union.union(b.clone());
+        assert_eq!(union.len(), 150);
+        assert!((0..100).all(|i| union.get(i) == Some(&i)));
+
+        let mut symmetric = a.clone();
+        symmetric.symmetric_difference(b.clone());
+        assert_eq!(symmetric.len(), 100);
+        assert!(symmetric.keys().all(|k| !(50..100).contains(&k)));
+
+        let mut intersection = a.clone();
+        intersection.intersection_with(&b, |_, a, b| *a += b);
+        assert_eq!(intersection.len(), 50);
+        assert!(intersection.iter().all(|(k, v)| *v == k + 1));
+
+        let mut kept = a.clone();
+        kept.retain_keys_in(&b);
+        assert_eq!(kept.len(), 50);
+        assert!(kept.is_submap_of(&a));
+        assert!(!a.is_submap_of(&kept));
+        assert!(!kept.is_submap_of(&b));
+
+        let mut difference = a.clone();
+        difference.difference(&b);
+        assert_eq!(difference.len(), 50);
+        assert!(difference.keys().all(|k| k < 50));
+    }
+}